@@ -0,0 +1,359 @@
+//! Orthonormal bases, linear independence and coordinates of `Vector<f64>` in a basis.
+//!
+//! Tolerances are relative: a vector lies in a span when the part of it orthogonal to the span
+//! has at most `tol` times its magnitude.
+
+use crate::error::DatasError;
+use crate::matrix::{Matrix, MatrixError};
+use crate::vector::{Vector, VectorError};
+
+/// Fails with `DimensionMismatch` unless all vectors have the dimensions of the first one
+fn check_dimensions(vectors: &[Vector<f64>]) -> Result<(), VectorError> {
+    match vectors.split_first() {
+        Some((first, rest)) => rest
+            .iter()
+            .try_for_each(|vector| first.check_dimensions(vector)),
+        None => Ok(()),
+    }
+}
+
+fn norm(values: &[f64]) -> f64 {
+    values.iter().map(|value| value * value).sum::<f64>().sqrt()
+}
+
+/// Part of `vector` orthogonal to the orthonormal `basis`, projections are removed one at a time
+/// from the updated residual (modified Gram-Schmidt) which keeps rounding errors from accumulating
+fn residual(basis: &[Vec<f64>], vector: &[f64]) -> Vec<f64> {
+    let mut residual = vector.to_vec();
+    for direction in basis {
+        let projection: f64 = direction.iter().zip(&residual).map(|(a, b)| a * b).sum();
+        residual
+            .iter_mut()
+            .zip(direction)
+            .for_each(|(value, d)| *value -= projection * d);
+    }
+    residual
+}
+
+/// Adds the normalized residual of `vector` to `basis` unless the vector lies in its span,
+/// returns whether a direction was added
+fn extend(basis: &mut Vec<Vec<f64>>, vector: &[f64], tol: f64) -> bool {
+    let residual = residual(basis, vector);
+    let length = norm(&residual);
+    if length == 0.0 || length <= tol * norm(vector) {
+        return false;
+    }
+    basis.push(residual.iter().map(|value| value / length).collect());
+    true
+}
+
+/// Orthonormal vectors spanning the same space as `vectors`, in the same order,
+/// fails with `LinearlyDependent` at the first vector lying in the span of the previous ones
+/// ```
+/// use datas::basis;
+/// use datas::vector::Vector;
+/// let vectors = vec![
+///     Vector::<f64>::new(vec![3.0, 4.0]),
+///     Vector::<f64>::new(vec![1.0, 0.0]),
+/// ];
+/// let orthonormal = basis::gram_schmidt(&vectors, 1e-10).unwrap();
+/// assert_eq!(orthonormal[0].components(), &[0.6, 0.8]);
+/// assert!(orthonormal[1].dot_product_f(&orthonormal[0]).unwrap().abs() < 1e-12);
+/// ```
+pub fn gram_schmidt(vectors: &[Vector<f64>], tol: f64) -> Result<Vec<Vector<f64>>, VectorError> {
+    check_dimensions(vectors)?;
+    let mut basis = Vec::with_capacity(vectors.len());
+    for (index, vector) in vectors.iter().enumerate() {
+        if !extend(&mut basis, vector.components(), tol) {
+            return Err(VectorError::LinearlyDependent {
+                index: index as u64,
+            });
+        }
+    }
+    Ok(basis.into_iter().map(Vector::<f64>::new).collect())
+}
+
+/// Orthonormal basis of the span of `vectors`, vectors lying in the span of the previous ones are skipped
+pub fn orthonormal_basis(
+    vectors: &[Vector<f64>],
+    tol: f64,
+) -> Result<Vec<Vector<f64>>, VectorError> {
+    check_dimensions(vectors)?;
+    let mut basis = Vec::new();
+    for vector in vectors {
+        extend(&mut basis, vector.components(), tol);
+    }
+    Ok(basis.into_iter().map(Vector::<f64>::new).collect())
+}
+
+/// Whether none of the vectors is a linear combination of the others, an empty set is independent
+pub fn are_linearly_independent(vectors: &[Vector<f64>], tol: f64) -> Result<bool, VectorError> {
+    match gram_schmidt(vectors, tol) {
+        Ok(_) => Ok(true),
+        Err(VectorError::LinearlyDependent { .. }) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Whether `vector` is a linear combination of `vectors`
+/// ```
+/// use datas::basis;
+/// use datas::vector::Vector;
+/// let plane = vec![
+///     Vector::<f64>::new(vec![1.0, 0.0, 0.0]),
+///     Vector::<f64>::new(vec![1.0, 1.0, 0.0]),
+/// ];
+/// let inside = Vector::<f64>::new(vec![2.0, -3.0, 0.0]);
+/// let outside = Vector::<f64>::new(vec![0.0, 0.0, 1.0]);
+/// assert_eq!(basis::span_contains(&plane, &inside, 1e-10), Ok(true));
+/// assert_eq!(basis::span_contains(&plane, &outside, 1e-10), Ok(false));
+/// ```
+pub fn span_contains(
+    vectors: &[Vector<f64>],
+    vector: &Vector<f64>,
+    tol: f64,
+) -> Result<bool, VectorError> {
+    if let Some(first) = vectors.first() {
+        first.check_dimensions(vector)?;
+    }
+    let mut basis: Vec<Vec<f64>> = orthonormal_basis(vectors, tol)?
+        .iter()
+        .map(|direction| direction.components().to_vec())
+        .collect();
+    Ok(!extend(&mut basis, vector.components(), tol))
+}
+
+/// Coordinates of `vector` in `basis`, the vector is the sum of basis vectors scaled by its coordinates.
+/// The basis needs as many vectors as they have dimensions, `Singular` if they are linearly dependent
+/// ```
+/// use datas::basis;
+/// use datas::vector::Vector;
+/// let skewed = vec![
+///     Vector::<f64>::new(vec![1.0, 0.0]),
+///     Vector::<f64>::new(vec![1.0, 2.0]),
+/// ];
+/// let point = Vector::<f64>::new(vec![3.0, 4.0]);
+/// let coordinates = basis::coordinates(&skewed, &point).unwrap();
+/// assert_eq!(coordinates.components(), &[1.0, 2.0]);
+/// assert_eq!(basis::from_coordinates(&skewed, &coordinates), Ok(point));
+/// ```
+pub fn coordinates(basis: &[Vector<f64>], vector: &Vector<f64>) -> Result<Vector<f64>, DatasError> {
+    check_dimensions(basis)?;
+    let dimensions = vector.dimensions();
+    if let Some(first) = basis.first() {
+        first.check_dimensions(vector)?;
+    }
+    if basis.len() as u64 != dimensions {
+        return Err(MatrixError::DimensionMismatch {
+            expected: (dimensions, dimensions),
+            actual: (dimensions, basis.len() as u64),
+        }
+        .into());
+    }
+    let columns: Vec<&[f64]> = basis.iter().map(|vector| vector.components()).collect();
+    let matrix = Matrix::<f64>::new(transpose(&columns, dimensions as usize))?;
+    let solution = matrix
+        .solve(vector.components().to_vec())
+        .ok_or(MatrixError::Singular)?;
+    Ok(Vector::<f64>::new(solution))
+}
+
+/// Vector with the given coordinates in `basis`, the inverse of `coordinates`
+pub fn from_coordinates(
+    basis: &[Vector<f64>],
+    coordinates: &Vector<f64>,
+) -> Result<Vector<f64>, VectorError> {
+    check_dimensions(basis)?;
+    if basis.len() as u64 != coordinates.dimensions() {
+        return Err(VectorError::DimensionMismatch {
+            expected: basis.len() as u64,
+            actual: coordinates.dimensions(),
+        });
+    }
+    let dimensions = basis
+        .first()
+        .map_or(0, |vector| vector.dimensions() as usize);
+    let mut result = vec![0.0; dimensions];
+    for (vector, coordinate) in basis.iter().zip(coordinates.components()) {
+        result
+            .iter_mut()
+            .zip(vector.components())
+            .for_each(|(value, component)| *value += coordinate * component);
+    }
+    Ok(Vector::<f64>::new(result))
+}
+
+/// Matrix converting coordinates in basis `from` into coordinates in basis `to`,
+/// its columns are the coordinates of the `from` vectors in `to`
+/// ```
+/// use datas::basis;
+/// use datas::vector::Vector;
+/// let standard = vec![
+///     Vector::<f64>::new(vec![1.0, 0.0]),
+///     Vector::<f64>::new(vec![0.0, 1.0]),
+/// ];
+/// let doubled = vec![
+///     Vector::<f64>::new(vec![2.0, 0.0]),
+///     Vector::<f64>::new(vec![0.0, 2.0]),
+/// ];
+/// let matrix = basis::change_of_basis(&standard, &doubled).unwrap();
+/// assert_eq!(matrix.data(), &[vec![0.5, 0.0], vec![0.0, 0.5]]);
+/// ```
+pub fn change_of_basis(
+    from: &[Vector<f64>],
+    to: &[Vector<f64>],
+) -> Result<Matrix<f64>, DatasError> {
+    let columns = from
+        .iter()
+        .map(|vector| coordinates(to, vector))
+        .collect::<Result<Vec<Vector<f64>>, DatasError>>()?;
+    let columns: Vec<&[f64]> = columns.iter().map(|vector| vector.components()).collect();
+    Ok(Matrix::<f64>::new(transpose(&columns, to.len()))?)
+}
+
+/// Rows of the matrix with the given columns of length `rows`
+fn transpose(columns: &[&[f64]], rows: usize) -> Vec<Vec<f64>> {
+    (0..rows)
+        .map(|row| columns.iter().map(|column| column[row]).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close;
+
+    fn vectors(components: &[&[f64]]) -> Vec<Vector<f64>> {
+        components
+            .iter()
+            .map(|components| Vector::<f64>::new(components.to_vec()))
+            .collect()
+    }
+
+    fn assert_orthonormal(basis: &[Vector<f64>]) {
+        for (i, a) in basis.iter().enumerate() {
+            for (j, b) in basis.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!(close(a.dot_product_f(b).unwrap(), expected));
+            }
+        }
+    }
+
+    #[test]
+    fn orthonormalization() {
+        let input = vectors(&[&[1.0, 1.0, 0.0], &[1.0, 0.0, 1.0], &[0.0, 1.0, 1.0]]);
+        let basis = gram_schmidt(&input, 1e-10).unwrap();
+        assert_eq!(basis.len(), 3);
+        assert_orthonormal(&basis);
+        // the first vector keeps its direction
+        let first = input[0].normalize_f().unwrap();
+        assert!(close(basis[0].dot_product_f(&first).unwrap(), 1.0));
+        assert_eq!(gram_schmidt(&[], 1e-10), Ok(vec![]));
+    }
+
+    #[test]
+    fn nearly_dependent_vectors() {
+        // classical Gram-Schmidt loses orthogonality on this Läuchli style input
+        let epsilon = 1e-8;
+        let input = vectors(&[
+            &[1.0, epsilon, 0.0, 0.0],
+            &[1.0, 0.0, epsilon, 0.0],
+            &[1.0, 0.0, 0.0, epsilon],
+        ]);
+        let basis = gram_schmidt(&input, 1e-12).unwrap();
+        for (i, a) in basis.iter().enumerate() {
+            for b in &basis[i + 1..] {
+                assert!(a.dot_product_f(b).unwrap().abs() < 1e-7);
+            }
+        }
+    }
+
+    #[test]
+    fn linear_dependence() {
+        let input = vectors(&[&[1.0, 2.0, 3.0], &[0.0, 1.0, 0.0], &[2.0, 5.0, 6.0]]);
+        assert_eq!(
+            gram_schmidt(&input, 1e-10),
+            Err(VectorError::LinearlyDependent { index: 2 })
+        );
+        assert_eq!(are_linearly_independent(&input, 1e-10), Ok(false));
+        assert_eq!(are_linearly_independent(&input[..2], 1e-10), Ok(true));
+        let zero = vectors(&[&[0.0, 0.0]]);
+        assert_eq!(
+            gram_schmidt(&zero, 1e-10),
+            Err(VectorError::LinearlyDependent { index: 0 })
+        );
+
+        let basis = orthonormal_basis(&input, 1e-10).unwrap();
+        assert_eq!(basis.len(), 2);
+        assert_orthonormal(&basis);
+        let mixed = vectors(&[&[1.0, 2.0], &[1.0]]);
+        assert_eq!(
+            are_linearly_independent(&mixed, 1e-10),
+            Err(VectorError::DimensionMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn span() {
+        let line = vectors(&[&[1.0, 1.0], &[-2.0, -2.0]]);
+        let on_line = Vector::<f64>::new(vec![3.0, 3.0]);
+        let off_line = Vector::<f64>::new(vec![3.0, 3.1]);
+        assert_eq!(span_contains(&line, &on_line, 1e-10), Ok(true));
+        assert_eq!(span_contains(&line, &off_line, 1e-10), Ok(false));
+        assert_eq!(span_contains(&line, &off_line, 0.1), Ok(true));
+        let zero = Vector::<f64>::new(vec![0.0, 0.0]);
+        assert_eq!(span_contains(&[], &zero, 1e-10), Ok(true));
+        assert_eq!(span_contains(&[], &on_line, 1e-10), Ok(false));
+        assert_eq!(
+            span_contains(&line, &Vector::<f64>::new(vec![1.0]), 1e-10),
+            Err(VectorError::DimensionMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn change_of_coordinates() {
+        let basis = vectors(&[&[1.0, 1.0, 0.0], &[0.0, 1.0, 1.0], &[1.0, 0.0, 1.0]]);
+        let point = Vector::<f64>::new(vec![2.0, 3.0, 5.0]);
+        let coordinates = coordinates(&basis, &point).unwrap();
+        assert_eq!(coordinates.components(), &[0.0, 3.0, 2.0]);
+        assert_eq!(from_coordinates(&basis, &coordinates), Ok(point.clone()));
+
+        let standard = vectors(&[&[1.0, 0.0, 0.0], &[0.0, 1.0, 0.0], &[0.0, 0.0, 1.0]]);
+        let matrix = change_of_basis(&standard, &basis).unwrap();
+        assert_eq!(matrix.vector_multiplication_f(&point).unwrap(), coordinates);
+        let back = change_of_basis(&basis, &standard).unwrap();
+        let identity = back.matrix_multiplication_f(&matrix).unwrap();
+        for (i, row) in identity.data().iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                assert!(close(value, if i == j { 1.0 } else { 0.0 }));
+            }
+        }
+
+        let dependent = vectors(&[&[1.0, 2.0], &[2.0, 4.0]]);
+        let plane_point = Vector::<f64>::new(vec![1.0, 1.0]);
+        assert!(matches!(
+            super::coordinates(&dependent, &plane_point),
+            Err(DatasError::Matrix(MatrixError::Singular))
+        ));
+        assert!(matches!(
+            super::coordinates(&dependent[..1], &plane_point),
+            Err(DatasError::Matrix(MatrixError::DimensionMismatch {
+                expected: (2, 2),
+                actual: (2, 1)
+            }))
+        ));
+        assert_eq!(
+            from_coordinates(&dependent, &point),
+            Err(VectorError::DimensionMismatch {
+                expected: 2,
+                actual: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,312 @@
+use crate::matrix::Matrix;
+use crate::norm::nan_max;
+use crate::vector::{Component, Vector, VectorError};
+
+/// Pairs of components converted to `f64`, after checking that dimensions match
+fn pairs<'a, A: Component, B: Component>(
+    a: &'a Vector<A>,
+    b: &'a Vector<B>,
+) -> Result<impl Iterator<Item = (f64, f64)> + 'a, VectorError> {
+    a.check_dimensions(b)?;
+    Ok(a.components()
+        .iter()
+        .zip(b.components())
+        .map(|(&x, &y)| (x.as_f64(), y.as_f64())))
+}
+
+/// Straight line distance between two points
+/// ```
+/// use datas::distance;
+/// use datas::vector::Vector;
+/// let a = Vector::<i64>::new(vec![0, 0]);
+/// let b = Vector::<f64>::new(vec![3.0, 4.0]);
+/// assert_eq!(distance::euclidean(&a, &b), Ok(5.0));
+/// ```
+pub fn euclidean<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    Ok(squared_euclidean(a, b)?.sqrt())
+}
+
+/// Euclidean distance without the square root, preserves ordering of distances
+pub fn squared_euclidean<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    Ok(pairs(a, b)?.map(|(x, y)| (x - y).powi(2)).sum())
+}
+
+/// Sum of absolute differences, also known as taxicab or L1 distance
+pub fn manhattan<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    Ok(pairs(a, b)?.map(|(x, y)| (x - y).abs()).sum())
+}
+
+/// Largest absolute difference, also known as L-infinity distance. NaN if any difference is NaN
+pub fn chebyshev<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    Ok(pairs(a, b)?.map(|(x, y)| (x - y).abs()).fold(0.0, nan_max))
+}
+
+/// Generalization of Manhattan (`p = 1`), Euclidean (`p = 2`) and Chebyshev (`p = f64::INFINITY`) distances,
+/// fails with `InvalidExponent` when `p` is NaN or below 1
+pub fn minkowski<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+    p: f64,
+) -> Result<f64, VectorError> {
+    if p.is_nan() || p < 1.0 {
+        return Err(VectorError::InvalidExponent { p });
+    }
+    if p.is_infinite() {
+        return chebyshev(a, b);
+    }
+    Ok(pairs(a, b)?
+        .map(|(x, y)| (x - y).abs().powf(p))
+        .sum::<f64>()
+        .powf(1.0 / p))
+}
+
+/// Cosine of the angle between vectors, fails with `ZeroVector` when either has zero magnitude
+/// ```
+/// use datas::distance;
+/// use datas::vector::Vector;
+/// let a = Vector::<i64>::new(vec![1, 0]);
+/// let b = Vector::<i64>::new(vec![5, 0]);
+/// assert_eq!(distance::cosine_similarity(&a, &b), Ok(1.0));
+/// ```
+pub fn cosine_similarity<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    let (dot, a_squared, b_squared) = pairs(a, b)?.fold((0.0, 0.0, 0.0), |acc, (x, y)| {
+        (acc.0 + x * y, acc.1 + x * x, acc.2 + y * y)
+    });
+    if a_squared == 0.0 || b_squared == 0.0 {
+        return Err(VectorError::ZeroVector);
+    }
+    Ok(dot / (a_squared.sqrt() * b_squared.sqrt()))
+}
+
+/// One minus cosine similarity, in range `[0, 2]`
+pub fn cosine_distance<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    Ok(1.0 - cosine_similarity(a, b)?)
+}
+
+/// Fraction of components that differ, 0 for empty vectors
+pub fn hamming<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    let differing = pairs(a, b)?.filter(|(x, y)| x != y).count();
+    if a.dimensions() == 0 {
+        return Ok(0.0);
+    }
+    Ok(differing as f64 / a.dimensions() as f64)
+}
+
+/// Jaccard distance of the sets of nonzero components, 0 when both vectors are zero
+pub fn jaccard<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    let (intersection, union) = pairs(a, b)?.fold((0usize, 0usize), |acc, (x, y)| {
+        let (x, y) = (x != 0.0, y != 0.0);
+        (acc.0 + (x && y) as usize, acc.1 + (x || y) as usize)
+    });
+    if union == 0 {
+        return Ok(0.0);
+    }
+    Ok(1.0 - intersection as f64 / union as f64)
+}
+
+/// Sum of `|x - y| / (|x| + |y|)`, pairs of zeros contribute nothing
+pub fn canberra<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    Ok(pairs(a, b)?
+        .map(|(x, y)| {
+            let denominator = x.abs() + y.abs();
+            if denominator == 0.0 {
+                0.0
+            } else {
+                (x - y).abs() / denominator
+            }
+        })
+        .sum())
+}
+
+/// `sum |x - y| / sum |x + y|`, 0 when vectors are equal,
+/// fails with `ZeroVector` when different vectors sum to zero
+pub fn bray_curtis<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    let (difference, sum) = pairs(a, b)?.fold((0.0, 0.0), |acc, (x, y)| {
+        (acc.0 + (x - y).abs(), acc.1 + (x + y).abs())
+    });
+    if difference == 0.0 {
+        return Ok(0.0);
+    }
+    if sum == 0.0 {
+        return Err(VectorError::ZeroVector);
+    }
+    Ok(difference / sum)
+}
+
+/// Distance accounting for correlations described by the `covariance` matrix,
+/// fails with `SingularCovariance` when it cannot be inverted
+/// ```
+/// use datas::distance;
+/// use datas::matrix::Matrix;
+/// use datas::vector::Vector;
+/// let covariance = Matrix::<f64>::new(vec![vec![4.0, 0.0], vec![0.0, 1.0]]).unwrap();
+/// let a = Vector::<f64>::new(vec![2.0, 0.0]);
+/// let b = Vector::<f64>::new(vec![0.0, 0.0]);
+/// assert_eq!(distance::mahalanobis(&a, &b, &covariance).unwrap(), 1.0);
+/// ```
+pub fn mahalanobis<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+    covariance: &Matrix<f64>,
+) -> Result<f64, VectorError> {
+    let difference: Vec<f64> = pairs(a, b)?.map(|(x, y)| x - y).collect();
+    for size in [covariance.rows(), covariance.cols()] {
+        if size != a.dimensions() {
+            return Err(VectorError::DimensionMismatch {
+                expected: size,
+                actual: a.dimensions(),
+            });
+        }
+    }
+    let solution = covariance
+        .solve(difference.clone())
+        .ok_or(VectorError::SingularCovariance)?;
+    let squared: f64 = difference.iter().zip(&solution).map(|(d, s)| d * s).sum();
+    Ok(squared.max(0.0).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close;
+
+    #[test]
+    fn lp_distances() {
+        let a = Vector::<i64>::new(vec![1, 2, 3]);
+        let b = Vector::<f64>::new(vec![4.0, 0.0, 3.0]);
+        assert_eq!(squared_euclidean(&a, &b), Ok(13.0));
+        assert_eq!(euclidean(&a, &b), Ok(13f64.sqrt()));
+        assert_eq!(manhattan(&a, &b), Ok(5.0));
+        assert_eq!(chebyshev(&a, &b), Ok(3.0));
+        let nan = Vector::<f64>::new(vec![f64::NAN, 1.0]);
+        let finite = Vector::<f64>::new(vec![0.0, 1.0]);
+        assert!(chebyshev(&nan, &finite).unwrap().is_nan());
+        assert!(minkowski(&finite, &nan, f64::INFINITY).unwrap().is_nan());
+        assert_eq!(minkowski(&a, &b, 1.0), Ok(5.0));
+        assert!(close(minkowski(&a, &b, 2.0).unwrap(), 13f64.sqrt()));
+        assert!(close(minkowski(&a, &b, 3.0).unwrap(), 35f64.cbrt()));
+        assert_eq!(minkowski(&a, &b, f64::INFINITY), Ok(3.0));
+        for p in [0.5, 0.0, -1.0, f64::NAN, f64::NEG_INFINITY] {
+            assert!(matches!(
+                minkowski(&a, &b, p),
+                Err(VectorError::InvalidExponent { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn cosine() {
+        let a = Vector::<f64>::new(vec![1.0, 0.0]);
+        let b = Vector::<f64>::new(vec![1.0, 1.0]);
+        assert!(close(
+            cosine_similarity(&a, &b).unwrap(),
+            std::f64::consts::FRAC_1_SQRT_2
+        ));
+        assert!(close(cosine_distance(&a, &-a.clone()).unwrap(), 2.0));
+        let zero = Vector::<i64>::new(vec![0, 0]);
+        assert_eq!(cosine_similarity(&a, &zero), Err(VectorError::ZeroVector));
+    }
+
+    #[test]
+    fn set_like_distances() {
+        let a = Vector::<i64>::new(vec![1, 0, 3, 0]);
+        let b = Vector::<i64>::new(vec![1, 2, 0, 0]);
+        assert_eq!(hamming(&a, &b), Ok(0.5));
+        assert!(close(jaccard(&a, &b).unwrap(), 2.0 / 3.0));
+        let zero = Vector::<i64>::new(vec![0, 0]);
+        assert_eq!(jaccard(&zero, &zero), Ok(0.0));
+        assert_eq!(
+            hamming(&Vector::<i64>::new(vec![]), &Vector::<i64>::new(vec![])),
+            Ok(0.0)
+        );
+    }
+
+    #[test]
+    fn canberra_and_bray_curtis() {
+        let a = Vector::<f64>::new(vec![1.0, 0.0, 2.0]);
+        let b = Vector::<f64>::new(vec![3.0, 0.0, 2.0]);
+        assert_eq!(canberra(&a, &b), Ok(0.5));
+        assert_eq!(bray_curtis(&a, &b), Ok(0.25));
+        assert_eq!(bray_curtis(&a, &a), Ok(0.0));
+        let zero = Vector::<f64>::new(vec![0.0, 0.0]);
+        assert_eq!(bray_curtis(&zero, &zero), Ok(0.0));
+        assert_eq!(bray_curtis(&a, &-a.clone()), Err(VectorError::ZeroVector));
+    }
+
+    #[test]
+    fn mahalanobis_distance() {
+        let covariance = Matrix::<f64>::new(vec![vec![2.0, 1.0], vec![1.0, 2.0]]).unwrap();
+        let a = Vector::<f64>::new(vec![1.0, 1.0]);
+        let b = Vector::<i64>::new(vec![0, 0]);
+        // inverse covariance is [[2, -1], [-1, 2]] / 3
+        assert!(close(
+            mahalanobis(&a, &b, &covariance).unwrap(),
+            (2.0f64 / 3.0).sqrt()
+        ));
+
+        let singular = Matrix::<f64>::new(vec![vec![1.0, 2.0], vec![2.0, 4.0]]).unwrap();
+        assert_eq!(
+            mahalanobis(&a, &b, &singular),
+            Err(VectorError::SingularCovariance)
+        );
+        let wrong = Matrix::<f64>::new(vec![vec![1.0]]).unwrap();
+        assert_eq!(
+            mahalanobis(&a, &b, &wrong),
+            Err(VectorError::DimensionMismatch {
+                expected: 1,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn dimension_mismatch() {
+        let a = Vector::<i64>::new(vec![1, 2]);
+        let b = Vector::<i64>::new(vec![1, 2, 3]);
+        let mismatch = Err(VectorError::DimensionMismatch {
+            expected: 2,
+            actual: 3,
+        });
+        assert_eq!(euclidean(&a, &b), mismatch);
+        assert_eq!(squared_euclidean(&a, &b), mismatch);
+        assert_eq!(manhattan(&a, &b), mismatch);
+        assert_eq!(chebyshev(&a, &b), mismatch);
+        assert_eq!(minkowski(&a, &b, 3.0), mismatch);
+        assert_eq!(cosine_similarity(&a, &b), mismatch);
+        assert_eq!(cosine_distance(&a, &b), mismatch);
+        assert_eq!(hamming(&a, &b), mismatch);
+        assert_eq!(jaccard(&a, &b), mismatch);
+        assert_eq!(canberra(&a, &b), mismatch);
+        assert_eq!(bray_curtis(&a, &b), mismatch);
+    }
+}
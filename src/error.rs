@@ -0,0 +1,140 @@
+use std::fmt;
+
+use crate::io::IoError;
+use crate::matrix::MatrixError;
+use crate::vector::VectorError;
+use crate::StatsError;
+
+/// Crate wide error, errors of every module convert into it so `?` works across modules.
+///
+/// Wrapped errors keep their context (expected and actual shapes, offending index)
+/// and are exposed through `std::error::Error::source`.
+#[derive(Debug)]
+pub enum DatasError {
+    Matrix(MatrixError),
+    Vector(VectorError),
+    Io(IoError),
+    Stats(StatsError),
+}
+
+impl fmt::Display for DatasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatasError::Matrix(error) => write!(f, "matrix error: {}", error),
+            DatasError::Vector(error) => write!(f, "vector error: {}", error),
+            DatasError::Io(error) => write!(f, "io error: {}", error),
+            DatasError::Stats(error) => write!(f, "stats error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for DatasError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatasError::Matrix(error) => Some(error),
+            DatasError::Vector(error) => Some(error),
+            DatasError::Io(error) => Some(error),
+            DatasError::Stats(error) => Some(error),
+        }
+    }
+}
+
+impl From<MatrixError> for DatasError {
+    fn from(error: MatrixError) -> Self {
+        DatasError::Matrix(error)
+    }
+}
+
+impl From<VectorError> for DatasError {
+    fn from(error: VectorError) -> Self {
+        DatasError::Vector(error)
+    }
+}
+
+impl From<IoError> for DatasError {
+    fn from(error: IoError) -> Self {
+        DatasError::Io(error)
+    }
+}
+
+impl From<StatsError> for DatasError {
+    fn from(error: StatsError) -> Self {
+        DatasError::Stats(error)
+    }
+}
+
+impl From<std::io::Error> for DatasError {
+    fn from(error: std::io::Error) -> Self {
+        DatasError::Io(IoError::Io(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Matrix;
+    use crate::vector::Vector;
+    use std::error::Error;
+
+    fn mixed(a: &[i64], b: &[i64]) -> Result<i64, DatasError> {
+        let matrix = Matrix::<i64>::new(vec![a.to_vec(), b.to_vec()])?;
+        let dot = Vector::<i64>::new(a.to_vec()).dot_product(&Vector::<i64>::new(b.to_vec()))?;
+        Ok(dot + matrix.rows() as i64)
+    }
+
+    #[test]
+    fn question_mark_across_modules() {
+        assert_eq!(mixed(&[1, 2], &[3, 4]).unwrap(), 13);
+        let error = mixed(&[1, 2], &[3]).unwrap_err();
+        assert!(matches!(
+            error,
+            DatasError::Matrix(MatrixError::InconsistentColumnSizes {
+                row: 1,
+                expected: 2,
+                actual: 1
+            })
+        ));
+        assert_eq!(
+            error.to_string(),
+            "matrix error: row 1 has 1 columns, expected 2"
+        );
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn error_messages() {
+        let error = MatrixError::MultiplicationDimensionMismatch {
+            left: (2, 3),
+            right: (2, 3),
+        };
+        assert_eq!(
+            error.to_string(),
+            "cannot multiply 2x3 matrix by 2x3 matrix"
+        );
+        let error = DatasError::from(VectorError::DimensionMismatch {
+            expected: 3,
+            actual: 2,
+        });
+        assert_eq!(
+            error.to_string(),
+            "vector error: expected vector with 3 dimensions, found 2"
+        );
+        let error = DatasError::from(IoError::Parse {
+            line: 2,
+            column: 5,
+            message: String::from("invalid value `x`"),
+        });
+        assert_eq!(
+            error.to_string(),
+            "io error: invalid value `x` at line 2, column 5"
+        );
+        let error = DatasError::from(StatsError::LengthMismatch {
+            data: 3,
+            weights: 2,
+        });
+        assert_eq!(
+            error.to_string(),
+            "stats error: 2 weights given for 3 values"
+        );
+    }
+}
@@ -0,0 +1,479 @@
+//! Vectors and matrices with dimensions known at compile time, stored inline without heap allocation.
+//!
+//! Combining structures of different dimensions does not compile, so the operations that can
+//! fail with `DimensionMismatch` on `Vector` and `Matrix` are infallible here.
+
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+
+use crate::matrix::{Matrix, MatrixError};
+use crate::vector::{Component, Vector, VectorError};
+
+/// Element types of fixed size vectors and matrices
+pub trait Scalar:
+    Copy
+    + Default
+    + PartialEq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    /// Multiplicative identity, used on the diagonal of identity matrices
+    fn one() -> Self;
+}
+
+impl Scalar for i64 {
+    fn one() -> Self {
+        1
+    }
+}
+
+impl Scalar for f64 {
+    fn one() -> Self {
+        1.0
+    }
+}
+
+/// Vector with `N` components stored in an array
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SVector<T, const N: usize> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(bound(
+            serialize = "T: serde::Serialize",
+            deserialize = "T: serde::Deserialize<'de>"
+        ))
+    )]
+    #[cfg_attr(feature = "serde", serde(with = "serde_array"))]
+    components: [T; N],
+}
+
+pub type SVector2<T> = SVector<T, 2>;
+pub type SVector3<T> = SVector<T, 3>;
+pub type SVector4<T> = SVector<T, 4>;
+
+impl<T: Scalar, const N: usize> SVector<T, N> {
+    /// Public constructor of SVector
+    /// ```
+    /// use datas::fixed::SVector;
+    /// let a = SVector::new([1, 2, 3]);
+    /// let b = SVector::new([4, 5, 6]);
+    /// assert_eq!(a.dot(&b), 32);
+    /// ```
+    pub fn new(components: [T; N]) -> Self {
+        Self { components }
+    }
+
+    /// Vector with every component equal to zero
+    pub fn zeros() -> Self {
+        Self::new([T::default(); N])
+    }
+
+    pub fn components(&self) -> &[T; N] {
+        &self.components
+    }
+
+    pub fn dot(&self, vector: &SVector<T, N>) -> T {
+        self.components
+            .iter()
+            .zip(&vector.components)
+            .fold(T::default(), |sum, (&a, &b)| sum + a * b)
+    }
+
+    pub fn magnitude(&self) -> f64
+    where
+        T: Component,
+    {
+        self.components
+            .iter()
+            .map(|&component| component.as_f64().powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Converts to heap allocated `Vector`
+    pub fn to_vector(&self) -> Vector<T> {
+        Vector::from_components(self.components.to_vec())
+    }
+}
+
+impl<T: Scalar> SVector<T, 3> {
+    /// Cross product, only defined for 3 dimensional vectors
+    /// ```
+    /// use datas::fixed::SVector;
+    /// let x = SVector::new([1.0, 0.0, 0.0]);
+    /// let y = SVector::new([0.0, 1.0, 0.0]);
+    /// assert_eq!(x.cross(&y), SVector::new([0.0, 0.0, 1.0]));
+    /// ```
+    pub fn cross(&self, vector: &SVector<T, 3>) -> SVector<T, 3> {
+        let (a, b) = (&self.components, &vector.components);
+        SVector::new([
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ])
+    }
+}
+
+impl<T: Scalar, const N: usize> From<[T; N]> for SVector<T, N> {
+    fn from(components: [T; N]) -> Self {
+        Self::new(components)
+    }
+}
+
+impl<T: Scalar, const N: usize> TryFrom<&Vector<T>> for SVector<T, N> {
+    type Error = VectorError;
+
+    fn try_from(vector: &Vector<T>) -> Result<Self, VectorError> {
+        let components: [T; N] =
+            vector
+                .components()
+                .try_into()
+                .map_err(|_| VectorError::DimensionMismatch {
+                    expected: N as u64,
+                    actual: vector.dimensions(),
+                })?;
+        Ok(Self::new(components))
+    }
+}
+
+impl<T, const N: usize> Index<usize> for SVector<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.components[index]
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for SVector<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.components[index]
+    }
+}
+
+impl<T: Scalar, const N: usize> Add for SVector<T, N> {
+    type Output = SVector<T, N>;
+
+    fn add(mut self, vector: SVector<T, N>) -> SVector<T, N> {
+        for (a, &b) in self.components.iter_mut().zip(&vector.components) {
+            *a = *a + b;
+        }
+        self
+    }
+}
+
+impl<T: Scalar, const N: usize> Sub for SVector<T, N> {
+    type Output = SVector<T, N>;
+
+    fn sub(mut self, vector: SVector<T, N>) -> SVector<T, N> {
+        for (a, &b) in self.components.iter_mut().zip(&vector.components) {
+            *a = *a - b;
+        }
+        self
+    }
+}
+
+impl<T: Scalar, const N: usize> Neg for SVector<T, N> {
+    type Output = SVector<T, N>;
+
+    fn neg(mut self) -> SVector<T, N> {
+        self.components.iter_mut().for_each(|a| *a = -*a);
+        self
+    }
+}
+
+impl<T: Scalar, const N: usize> Mul<T> for SVector<T, N> {
+    type Output = SVector<T, N>;
+
+    fn mul(mut self, scalar: T) -> SVector<T, N> {
+        self.components.iter_mut().for_each(|a| *a = *a * scalar);
+        self
+    }
+}
+
+/// Matrix with `R` rows and `C` columns stored in nested arrays
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SMatrix<T, const R: usize, const C: usize> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(bound(
+            serialize = "T: serde::Serialize",
+            deserialize = "T: serde::Deserialize<'de>"
+        ))
+    )]
+    #[cfg_attr(feature = "serde", serde(with = "serde_array::nested"))]
+    data: [[T; C]; R],
+}
+
+pub type SMatrix2<T> = SMatrix<T, 2, 2>;
+pub type SMatrix3<T> = SMatrix<T, 3, 3>;
+pub type SMatrix4<T> = SMatrix<T, 4, 4>;
+
+impl<T: Scalar, const R: usize, const C: usize> SMatrix<T, R, C> {
+    /// Public constructor of SMatrix from rows
+    /// ```
+    /// use datas::fixed::{SMatrix, SVector};
+    /// let matrix = SMatrix::new([[1, 2], [3, 4]]);
+    /// assert_eq!(matrix * SVector::new([1, 1]), SVector::new([3, 7]));
+    /// ```
+    pub fn new(data: [[T; C]; R]) -> Self {
+        Self { data }
+    }
+
+    /// Matrix with every element equal to zero
+    pub fn zeros() -> Self {
+        Self::new([[T::default(); C]; R])
+    }
+
+    pub fn data(&self) -> &[[T; C]; R] {
+        &self.data
+    }
+
+    pub fn transpose(&self) -> SMatrix<T, C, R> {
+        let mut data = [[T::default(); R]; C];
+        for (r, row) in self.data.iter().enumerate() {
+            for (c, &value) in row.iter().enumerate() {
+                data[c][r] = value;
+            }
+        }
+        SMatrix::new(data)
+    }
+
+    /// Converts to heap allocated `Matrix`
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let data = self.data.iter().map(|row| row.to_vec()).collect();
+        Matrix::from_rows(data).expect("rows of an array have equal length")
+    }
+}
+
+impl<T: Scalar, const N: usize> SMatrix<T, N, N> {
+    /// Square matrix with ones on the diagonal
+    pub fn identity() -> Self {
+        let mut matrix = Self::zeros();
+        for i in 0..N {
+            matrix.data[i][i] = T::one();
+        }
+        matrix
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> TryFrom<&Matrix<T>> for SMatrix<T, R, C> {
+    type Error = MatrixError;
+
+    fn try_from(matrix: &Matrix<T>) -> Result<Self, MatrixError> {
+        if matrix.rows() != R as u64 || matrix.cols() != C as u64 {
+            return Err(MatrixError::DimensionMismatch {
+                expected: (R as u64, C as u64),
+                actual: (matrix.rows(), matrix.cols()),
+            });
+        }
+        let mut result = Self::zeros();
+        for (target, source) in result.data.iter_mut().zip(matrix.data()) {
+            target.copy_from_slice(source);
+        }
+        Ok(result)
+    }
+}
+
+impl<T, const R: usize, const C: usize> Index<(usize, usize)> for SMatrix<T, R, C> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        &self.data[row][col]
+    }
+}
+
+impl<T, const R: usize, const C: usize> IndexMut<(usize, usize)> for SMatrix<T, R, C> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        &mut self.data[row][col]
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Add for SMatrix<T, R, C> {
+    type Output = SMatrix<T, R, C>;
+
+    fn add(mut self, matrix: SMatrix<T, R, C>) -> SMatrix<T, R, C> {
+        for (row, other) in self.data.iter_mut().zip(&matrix.data) {
+            for (a, &b) in row.iter_mut().zip(other) {
+                *a = *a + b;
+            }
+        }
+        self
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Sub for SMatrix<T, R, C> {
+    type Output = SMatrix<T, R, C>;
+
+    fn sub(mut self, matrix: SMatrix<T, R, C>) -> SMatrix<T, R, C> {
+        for (row, other) in self.data.iter_mut().zip(&matrix.data) {
+            for (a, &b) in row.iter_mut().zip(other) {
+                *a = *a - b;
+            }
+        }
+        self
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Mul<T> for SMatrix<T, R, C> {
+    type Output = SMatrix<T, R, C>;
+
+    fn mul(mut self, scalar: T) -> SMatrix<T, R, C> {
+        self.data
+            .iter_mut()
+            .for_each(|row| row.iter_mut().for_each(|a| *a = *a * scalar));
+        self
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize, const K: usize> Mul<SMatrix<T, C, K>>
+    for SMatrix<T, R, C>
+{
+    type Output = SMatrix<T, R, K>;
+
+    fn mul(self, matrix: SMatrix<T, C, K>) -> SMatrix<T, R, K> {
+        let mut result = SMatrix::<T, R, K>::zeros();
+        for (target, row) in result.data.iter_mut().zip(&self.data) {
+            for (k, value) in target.iter_mut().enumerate() {
+                *value = row
+                    .iter()
+                    .zip(&matrix.data)
+                    .fold(T::default(), |sum, (&a, other)| sum + a * other[k]);
+            }
+        }
+        result
+    }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Mul<SVector<T, C>> for SMatrix<T, R, C> {
+    type Output = SVector<T, R>;
+
+    fn mul(self, vector: SVector<T, C>) -> SVector<T, R> {
+        let mut result = SVector::<T, R>::zeros();
+        for (value, row) in result.components.iter_mut().zip(&self.data) {
+            *value = SVector::new(*row).dot(&vector);
+        }
+        result
+    }
+}
+
+/// Serde has no built in support for arrays of arbitrary const length, they are written as sequences
+#[cfg(feature = "serde")]
+mod serde_array {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T: Serialize, S: Serializer, const N: usize>(
+        array: &[T; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        array.as_slice().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[T; N], D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let len = values.len();
+        values
+            .try_into()
+            .map_err(|_| D::Error::custom(format!("expected {} elements, found {}", N, len)))
+    }
+
+    pub mod nested {
+        use super::*;
+
+        pub fn serialize<T: Serialize, S: Serializer, const R: usize, const C: usize>(
+            array: &[[T; C]; R],
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            let rows: Vec<&[T]> = array.iter().map(|row| row.as_slice()).collect();
+            rows.serialize(serializer)
+        }
+
+        pub fn deserialize<
+            'de,
+            T: Deserialize<'de>,
+            D: Deserializer<'de>,
+            const R: usize,
+            const C: usize,
+        >(
+            deserializer: D,
+        ) -> Result<[[T; C]; R], D::Error> {
+            let rows = Vec::<Vec<T>>::deserialize(deserializer)?;
+            let len = rows.len();
+            let rows = rows
+                .into_iter()
+                .map(|row| {
+                    let len = row.len();
+                    row.try_into().map_err(|_| {
+                        D::Error::custom(format!("expected {} columns, found {}", C, len))
+                    })
+                })
+                .collect::<Result<Vec<[T; C]>, D::Error>>()?;
+            rows.try_into()
+                .map_err(|_| D::Error::custom(format!("expected {} rows, found {}", R, len)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svector_arithmetic() {
+        let a = SVector::new([1.0, 2.0, 2.0]);
+        let b = SVector3::from([3.0, 0.0, -1.0]);
+        assert_eq!(a + b, SVector::new([4.0, 2.0, 1.0]));
+        assert_eq!(a - b, SVector::new([-2.0, 2.0, 3.0]));
+        assert_eq!(-a * 2.0, SVector::new([-2.0, -4.0, -4.0]));
+        assert_eq!(a.dot(&b), 1.0);
+        assert_eq!(a.magnitude(), 3.0);
+        assert_eq!(a.cross(&b), SVector::new([-2.0, 7.0, -6.0]));
+        assert_eq!(a[1], 2.0);
+    }
+
+    #[test]
+    fn svector_vector_conversion() {
+        let i_vector = Vector::<i64>::new(vec![1, 2]);
+        let fixed = SVector2::<i64>::try_from(&i_vector).unwrap();
+        assert_eq!(fixed.to_vector(), i_vector);
+        assert_eq!(
+            SVector3::<i64>::try_from(&i_vector),
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn smatrix_multiplication() {
+        let a = SMatrix::new([[1, 2, 3], [4, 5, 6]]);
+        let b = SMatrix::new([[7, 8], [9, 10], [11, 12]]);
+        assert_eq!(a * b, SMatrix::new([[58, 64], [139, 154]]));
+        assert_eq!(a.transpose(), SMatrix::new([[1, 4], [2, 5], [3, 6]]));
+        assert_eq!(a * SVector::new([1, 0, -1]), SVector::new([-2, -2]));
+        assert_eq!(SMatrix2::<i64>::identity() * b.transpose(), b.transpose());
+        assert_eq!(a + a, a * 2);
+        assert_eq!(a - a, SMatrix::zeros());
+    }
+
+    #[test]
+    fn smatrix_matrix_conversion() {
+        let matrix = Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap();
+        let fixed = SMatrix2::<i64>::try_from(&matrix).unwrap();
+        assert_eq!(fixed[(1, 0)], 3);
+        assert_eq!(fixed.to_matrix(), matrix);
+        assert_eq!(
+            SMatrix3::<i64>::try_from(&matrix),
+            Err(MatrixError::DimensionMismatch {
+                expected: (3, 3),
+                actual: (2, 2)
+            })
+        );
+    }
+}
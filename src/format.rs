@@ -0,0 +1,143 @@
+use std::fmt::Display;
+
+/// Options controlling how matrices and vectors are rendered as text.
+///
+/// Defaults mirror NumPy: arrays with more than `threshold` elements are
+/// summarized, keeping `edge_items` entries at the start and end of each axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintOptions {
+    /// Number of digits after the decimal point, `None` prints values as they are
+    pub precision: Option<usize>,
+    /// Total number of elements above which the output gets elided
+    pub threshold: usize,
+    /// Number of leading and trailing entries kept on each elided axis
+    pub edge_items: usize,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            precision: None,
+            threshold: 1000,
+            edge_items: 3,
+        }
+    }
+}
+
+impl PrintOptions {
+    /// Returns copy of options with given precision
+    /// ```
+    /// use datas::format::PrintOptions;
+    /// let options = PrintOptions::default().with_precision(2);
+    /// assert_eq!(options.precision, Some(2));
+    /// ```
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Returns copy of options that never elides entries
+    pub fn full(mut self) -> Self {
+        self.threshold = usize::MAX;
+        self
+    }
+}
+
+/// Single rendered entry, `Ellipsis` stands for the skipped part of an axis
+#[derive(Debug, PartialEq)]
+pub(crate) enum Cell {
+    Value(String),
+    Ellipsis,
+}
+
+pub(crate) fn format_value<T: Display>(value: &T, precision: Option<usize>) -> String {
+    match precision {
+        Some(p) => format!("{:.*}", p, value),
+        None => format!("{}", value),
+    }
+}
+
+/// Indices of an axis that stay visible, `None` marks the place of elision
+pub(crate) fn visible_indices(len: usize, elide: bool, edge_items: usize) -> Vec<Option<usize>> {
+    if elide && len > 2 * edge_items {
+        (0..edge_items)
+            .map(Some)
+            .chain(std::iter::once(None))
+            .chain((len - edge_items..len).map(Some))
+            .collect()
+    } else {
+        (0..len).map(Some).collect()
+    }
+}
+
+/// Renders a row of values, eliding its middle when the whole structure is too big
+pub(crate) fn render_row<T: Display>(row: &[T], elide: bool, options: &PrintOptions) -> Vec<Cell> {
+    visible_indices(row.len(), elide, options.edge_items)
+        .into_iter()
+        .map(|index| match index {
+            Some(i) => Cell::Value(format_value(&row[i], options.precision)),
+            None => Cell::Ellipsis,
+        })
+        .collect()
+}
+
+/// Renders grid of values, `None` rows stand for elided rows
+pub(crate) fn render_grid<T: Display>(
+    data: &[Vec<T>],
+    options: &PrintOptions,
+) -> Vec<Option<Vec<Cell>>> {
+    let size: usize = data.iter().map(|row| row.len()).sum();
+    let elide = size > options.threshold;
+    visible_indices(data.len(), elide, options.edge_items)
+        .into_iter()
+        .map(|index| index.map(|i| render_row(&data[i], elide, options)))
+        .collect()
+}
+
+/// Width of the widest rendered value, used to right align columns
+pub(crate) fn cell_width<'a>(cells: impl Iterator<Item = &'a Cell>) -> usize {
+    cells
+        .map(|cell| match cell {
+            Cell::Value(s) => s.chars().count(),
+            Cell::Ellipsis => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Joins cells NumPy style, values right aligned to `width` and separated with spaces
+pub(crate) fn join_cells(cells: &[Cell], width: usize) -> String {
+    cells
+        .iter()
+        .map(|cell| match cell {
+            Cell::Value(s) => format!("{:>width$}", s, width = width),
+            Cell::Ellipsis => String::from("..."),
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visible_indices_elision() {
+        assert_eq!(
+            visible_indices(10, true, 2),
+            vec![Some(0), Some(1), None, Some(8), Some(9)]
+        );
+        assert_eq!(
+            visible_indices(4, true, 2),
+            vec![Some(0), Some(1), Some(2), Some(3)]
+        );
+        assert_eq!(visible_indices(10, false, 2).len(), 10);
+    }
+
+    #[test]
+    fn format_value_precision() {
+        assert_eq!(format_value(&1.23456, Some(2)), "1.23");
+        assert_eq!(format_value(&7, Some(2)), "7");
+        assert_eq!(format_value(&1.5, None), "1.5");
+    }
+}
@@ -0,0 +1,710 @@
+//! Rotations and affine transforms of points given as `Vector<f64>`.
+//!
+//! Angles are in radians, rotations are counterclockwise about the axis (right handed)
+//! and matrices act on column vectors.
+
+use std::ops::{Mul, Neg};
+
+use crate::fixed::{SMatrix3, SMatrix4, SVector};
+use crate::matrix::{Matrix, MatrixError};
+use crate::vector::{Vector, VectorError};
+
+/// Components of a 3 dimensional vector, `DimensionMismatch` for other dimensions
+fn components_3d(vector: &Vector<f64>) -> Result<[f64; 3], VectorError> {
+    SVector::<f64, 3>::try_from(vector).map(|vector| *vector.components())
+}
+
+fn x_rotation(angle: f64) -> SMatrix3<f64> {
+    let (sin, cos) = angle.sin_cos();
+    SMatrix3::new([[1.0, 0.0, 0.0], [0.0, cos, -sin], [0.0, sin, cos]])
+}
+
+fn y_rotation(angle: f64) -> SMatrix3<f64> {
+    let (sin, cos) = angle.sin_cos();
+    SMatrix3::new([[cos, 0.0, sin], [0.0, 1.0, 0.0], [-sin, 0.0, cos]])
+}
+
+fn z_rotation(angle: f64) -> SMatrix3<f64> {
+    let (sin, cos) = angle.sin_cos();
+    SMatrix3::new([[cos, -sin, 0.0], [sin, cos, 0.0], [0.0, 0.0, 1.0]])
+}
+
+/// 2x2 matrix rotating points in the plane
+/// ```
+/// use datas::geometry;
+/// use datas::vector::Vector;
+/// let rotation = geometry::rotation_2d(std::f64::consts::FRAC_PI_2);
+/// let point = rotation.vector_multiplication_f(&Vector::<f64>::new(vec![1.0, 0.0])).unwrap();
+/// assert!((point.components()[1] - 1.0).abs() < 1e-12);
+/// ```
+pub fn rotation_2d(angle: f64) -> Matrix<f64> {
+    let (sin, cos) = angle.sin_cos();
+    Matrix::<f64>::new(vec![vec![cos, -sin], vec![sin, cos]]).unwrap()
+}
+
+/// 3x3 matrix rotating about the x axis
+pub fn rotation_x(angle: f64) -> Matrix<f64> {
+    x_rotation(angle).to_matrix()
+}
+
+/// 3x3 matrix rotating about the y axis
+pub fn rotation_y(angle: f64) -> Matrix<f64> {
+    y_rotation(angle).to_matrix()
+}
+
+/// 3x3 matrix rotating about the z axis
+pub fn rotation_z(angle: f64) -> Matrix<f64> {
+    z_rotation(angle).to_matrix()
+}
+
+/// 3x3 matrix rotating about an arbitrary axis, which does not have to be normalized
+pub fn rotation_axis_angle(axis: &Vector<f64>, angle: f64) -> Result<Matrix<f64>, VectorError> {
+    Ok(Quaternion::from_axis_angle(axis, angle)?.to_rotation_matrix())
+}
+
+/// Orientation as roll (about x), pitch (about y) and yaw (about z) in the Z-Y-X convention
+/// used in aerospace and robotics: the rotation matrix is `Rz(yaw) * Ry(pitch) * Rx(roll)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EulerAngles {
+    pub roll: f64,
+    pub pitch: f64,
+    pub yaw: f64,
+}
+
+impl EulerAngles {
+    pub fn new(roll: f64, pitch: f64, yaw: f64) -> Self {
+        Self { roll, pitch, yaw }
+    }
+
+    pub fn to_rotation_matrix(&self) -> Matrix<f64> {
+        (z_rotation(self.yaw) * y_rotation(self.pitch) * x_rotation(self.roll)).to_matrix()
+    }
+
+    /// Angles of a 3x3 rotation matrix, pitch is in `[-pi/2, pi/2]`.
+    /// At a pitch of `±pi/2` (gimbal lock) only yaw and roll combined are determined, roll is reported as 0
+    /// ```
+    /// use datas::geometry::EulerAngles;
+    /// let angles = EulerAngles::new(0.1, -0.2, 0.3);
+    /// let recovered = EulerAngles::from_rotation_matrix(&angles.to_rotation_matrix()).unwrap();
+    /// assert!((recovered.pitch + 0.2).abs() < 1e-12);
+    /// ```
+    pub fn from_rotation_matrix(matrix: &Matrix<f64>) -> Result<Self, MatrixError> {
+        let matrix = SMatrix3::<f64>::try_from(matrix)?;
+        Ok(Self::from_rotation(matrix.data()))
+    }
+
+    fn from_rotation(m: &[[f64; 3]; 3]) -> Self {
+        let cos_pitch = m[0][0].hypot(m[1][0]);
+        let pitch = (-m[2][0]).atan2(cos_pitch);
+        if cos_pitch < 1e-9 {
+            return Self::new(0.0, pitch, (-m[0][1]).atan2(m[1][1]));
+        }
+        Self::new(m[2][1].atan2(m[2][2]), pitch, m[1][0].atan2(m[0][0]))
+    }
+
+    pub fn to_quaternion(&self) -> Quaternion {
+        let (sr, cr) = (self.roll / 2.0).sin_cos();
+        let (sp, cp) = (self.pitch / 2.0).sin_cos();
+        let (sy, cy) = (self.yaw / 2.0).sin_cos();
+        Quaternion::new(
+            cr * cp * cy + sr * sp * sy,
+            sr * cp * cy - cr * sp * sy,
+            cr * sp * cy + sr * cp * sy,
+            cr * cp * sy - sr * sp * cy,
+        )
+    }
+}
+
+/// Quaternion `w + xi + yj + zk`, unit quaternions represent rotations in 3 dimensions
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// Rotation by zero angle
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Unit quaternion rotating by `angle` about `axis`, fails unless the axis is a nonzero 3 dimensional vector
+    /// ```
+    /// use datas::geometry::Quaternion;
+    /// use datas::vector::Vector;
+    /// let axis = Vector::<f64>::new(vec![0.0, 0.0, 2.0]);
+    /// let rotation = Quaternion::from_axis_angle(&axis, std::f64::consts::FRAC_PI_2).unwrap();
+    /// let point = rotation.rotate(&Vector::<f64>::new(vec![1.0, 0.0, 0.0])).unwrap();
+    /// assert!((point.components()[1] - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn from_axis_angle(axis: &Vector<f64>, angle: f64) -> Result<Self, VectorError> {
+        components_3d(axis)?;
+        let axis = axis.normalize_f()?;
+        let [x, y, z] = components_3d(&axis)?;
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Ok(Self::new(cos, x * sin, y * sin, z * sin))
+    }
+
+    /// Unit axis and angle in `[0, 2pi]` of the rotation, the x axis is reported for the identity
+    pub fn to_axis_angle(&self) -> (Vector<f64>, f64) {
+        let sin = (self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        let angle = 2.0 * sin.atan2(self.w);
+        if sin == 0.0 {
+            return (Vector::<f64>::new(vec![1.0, 0.0, 0.0]), angle);
+        }
+        let axis = Vector::<f64>::new(vec![self.x / sin, self.y / sin, self.z / sin]);
+        (axis, angle)
+    }
+
+    /// Quaternion of a 3x3 rotation matrix
+    pub fn from_rotation_matrix(matrix: &Matrix<f64>) -> Result<Self, MatrixError> {
+        let matrix = SMatrix3::<f64>::try_from(matrix)?;
+        let m = matrix.data();
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        // branch on the largest of w, x, y and z to avoid dividing by a small number
+        let quaternion = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new(
+                s / 4.0,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[2][1] - m[1][2]) / s,
+                s / 4.0,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.0,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Self::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.0,
+            )
+        };
+        Ok(quaternion)
+    }
+
+    /// 3x3 rotation matrix, quaternions that are not normalized give the same rotation as their unit quaternion
+    pub fn to_rotation_matrix(&self) -> Matrix<f64> {
+        self.rotation().to_matrix()
+    }
+
+    fn rotation(&self) -> SMatrix3<f64> {
+        let squared = self.dot(self);
+        let s = if squared == 0.0 { 0.0 } else { 2.0 / squared };
+        let Quaternion { w, x, y, z } = *self;
+        SMatrix3::new([
+            [
+                1.0 - s * (y * y + z * z),
+                s * (x * y - w * z),
+                s * (x * z + w * y),
+            ],
+            [
+                s * (x * y + w * z),
+                1.0 - s * (x * x + z * z),
+                s * (y * z - w * x),
+            ],
+            [
+                s * (x * z - w * y),
+                s * (y * z + w * x),
+                1.0 - s * (x * x + y * y),
+            ],
+        ])
+    }
+
+    pub fn from_euler(angles: &EulerAngles) -> Self {
+        angles.to_quaternion()
+    }
+
+    pub fn to_euler(&self) -> EulerAngles {
+        EulerAngles::from_rotation(self.rotation().data())
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Four dimensional dot product, the cosine of half the angle between unit quaternions
+    pub fn dot(&self, quaternion: &Quaternion) -> f64 {
+        self.w * quaternion.w
+            + self.x * quaternion.x
+            + self.y * quaternion.y
+            + self.z * quaternion.z
+    }
+
+    pub fn normalize(&self) -> Result<Quaternion, VectorError> {
+        let norm = self.norm();
+        if norm == 0.0 {
+            return Err(VectorError::ZeroVector);
+        }
+        Ok(self.scale(1.0 / norm))
+    }
+
+    pub fn conjugate(&self) -> Quaternion {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Multiplicative inverse, the opposite rotation
+    pub fn inverse(&self) -> Result<Quaternion, VectorError> {
+        let squared = self.dot(self);
+        if squared == 0.0 {
+            return Err(VectorError::ZeroVector);
+        }
+        Ok(self.conjugate().scale(1.0 / squared))
+    }
+
+    /// Hamilton product `self * quaternion`, the rotation applying `quaternion` first and then `self`
+    pub fn compose(&self, quaternion: &Quaternion) -> Quaternion {
+        let (a, b) = (self, quaternion);
+        Self::new(
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        )
+    }
+
+    /// Rotates a 3 dimensional point
+    pub fn rotate(&self, point: &Vector<f64>) -> Result<Vector<f64>, VectorError> {
+        let point = SVector::new(components_3d(point)?);
+        Ok((self.rotation() * point).to_vector())
+    }
+
+    /// Spherical linear interpolation between unit quaternions along the shorter arc,
+    /// `t = 0` gives `self` and `t = 1` gives `quaternion`
+    /// ```
+    /// use datas::geometry::{EulerAngles, Quaternion};
+    /// let start = Quaternion::identity();
+    /// let end = EulerAngles::new(0.0, 0.0, 1.0).to_quaternion();
+    /// let middle = start.slerp(&end, 0.5).to_euler();
+    /// assert!((middle.yaw - 0.5).abs() < 1e-12);
+    /// ```
+    pub fn slerp(&self, quaternion: &Quaternion, t: f64) -> Quaternion {
+        let (mut end, mut cos) = (*quaternion, self.dot(quaternion));
+        if cos < 0.0 {
+            end = -end;
+            cos = -cos;
+        }
+        if cos > 1.0 - 1e-9 {
+            // sin of the angle is close to zero, linear interpolation is accurate here
+            let lerp = self.combine(1.0 - t, &end, t);
+            return lerp.scale(1.0 / lerp.norm());
+        }
+        let angle = cos.acos();
+        let sin = angle.sin();
+        self.combine(
+            ((1.0 - t) * angle).sin() / sin,
+            &end,
+            (t * angle).sin() / sin,
+        )
+    }
+
+    fn scale(&self, factor: f64) -> Quaternion {
+        Self::new(
+            self.w * factor,
+            self.x * factor,
+            self.y * factor,
+            self.z * factor,
+        )
+    }
+
+    /// Linear combination `a * self + b * quaternion`
+    fn combine(&self, a: f64, quaternion: &Quaternion, b: f64) -> Quaternion {
+        Self::new(
+            a * self.w + b * quaternion.w,
+            a * self.x + b * quaternion.x,
+            a * self.y + b * quaternion.y,
+            a * self.z + b * quaternion.z,
+        )
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, quaternion: Quaternion) -> Quaternion {
+        self.compose(&quaternion)
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Quaternion;
+
+    /// Negated quaternion, represents the same rotation
+    fn neg(self) -> Quaternion {
+        self.scale(-1.0)
+    }
+}
+
+/// Affine transform of 3 dimensional points as a homogeneous 4x4 matrix
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    matrix: SMatrix4<f64>,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            matrix: SMatrix4::identity(),
+        }
+    }
+
+    /// Moves points by a 3 dimensional offset
+    pub fn translation(offset: &Vector<f64>) -> Result<Self, VectorError> {
+        let mut transform = Self::identity();
+        for (row, value) in components_3d(offset)?.into_iter().enumerate() {
+            transform.matrix[(row, 3)] = value;
+        }
+        Ok(transform)
+    }
+
+    /// Scales each axis by its own factor
+    pub fn scaling(factors: &Vector<f64>) -> Result<Self, VectorError> {
+        let mut transform = Self::identity();
+        for (axis, value) in components_3d(factors)?.into_iter().enumerate() {
+            transform.matrix[(axis, axis)] = value;
+        }
+        Ok(transform)
+    }
+
+    pub fn rotation(rotation: &Quaternion) -> Self {
+        let mut transform = Self::identity();
+        for (row, values) in rotation.rotation().data().iter().enumerate() {
+            for (col, &value) in values.iter().enumerate() {
+                transform.matrix[(row, col)] = value;
+            }
+        }
+        transform
+    }
+
+    /// Transform of a 4x4 homogeneous matrix
+    pub fn from_matrix(matrix: &Matrix<f64>) -> Result<Self, MatrixError> {
+        Ok(Self {
+            matrix: SMatrix4::try_from(matrix)?,
+        })
+    }
+
+    pub fn to_matrix(&self) -> Matrix<f64> {
+        self.matrix.to_matrix()
+    }
+
+    /// Translates after applying this transform
+    /// ```
+    /// use datas::geometry::Transform;
+    /// use datas::vector::Vector;
+    /// let transform = Transform::scaling(&Vector::<f64>::new(vec![2.0, 2.0, 2.0]))
+    ///     .unwrap()
+    ///     .translate(&Vector::<f64>::new(vec![1.0, 0.0, 0.0]))
+    ///     .unwrap();
+    /// let point = transform.apply_point(&Vector::<f64>::new(vec![1.0, 1.0, 1.0])).unwrap();
+    /// assert_eq!(point.components(), &[3.0, 2.0, 2.0]);
+    /// ```
+    pub fn translate(self, offset: &Vector<f64>) -> Result<Self, VectorError> {
+        Ok(Self::translation(offset)? * self)
+    }
+
+    /// Rotates after applying this transform
+    pub fn rotate(self, rotation: &Quaternion) -> Self {
+        Self::rotation(rotation) * self
+    }
+
+    /// Scales after applying this transform
+    pub fn scale(self, factors: &Vector<f64>) -> Result<Self, VectorError> {
+        Ok(Self::scaling(factors)? * self)
+    }
+
+    /// Transform undoing this one, fails with `Singular` if it collapses space (for example scaling by zero)
+    pub fn inverse(&self) -> Result<Self, MatrixError> {
+        let mut matrix = *self.matrix.data();
+        let mut inverse = *SMatrix4::<f64>::identity().data();
+        let scale = matrix
+            .iter()
+            .flatten()
+            .fold(0.0_f64, |max, value| max.max(value.abs()));
+        let tolerance = scale * 4.0 * f64::EPSILON;
+        // Gauss-Jordan elimination with partial pivoting
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&i, &j| matrix[i][col].abs().total_cmp(&matrix[j][col].abs()))
+                .unwrap_or(col);
+            if matrix[pivot][col].abs() <= tolerance {
+                return Err(MatrixError::Singular);
+            }
+            matrix.swap(col, pivot);
+            inverse.swap(col, pivot);
+            let divisor = matrix[col][col];
+            matrix[col].iter_mut().for_each(|value| *value /= divisor);
+            inverse[col].iter_mut().for_each(|value| *value /= divisor);
+            let (pivot_row, pivot_inverse) = (matrix[col], inverse[col]);
+            for (row, (values, inverse_values)) in matrix.iter_mut().zip(&mut inverse).enumerate() {
+                if row == col {
+                    continue;
+                }
+                let factor = values[col];
+                for (value, pivot_value) in values.iter_mut().zip(&pivot_row) {
+                    *value -= factor * pivot_value;
+                }
+                for (value, pivot_value) in inverse_values.iter_mut().zip(&pivot_inverse) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+        Ok(Self {
+            matrix: SMatrix4::new(inverse),
+        })
+    }
+
+    /// Transforms a 3 dimensional point, translation applies
+    pub fn apply_point(&self, point: &Vector<f64>) -> Result<Vector<f64>, VectorError> {
+        let [x, y, z] = components_3d(point)?;
+        let result = self.matrix * SVector::new([x, y, z, 1.0]);
+        // w stays 1 for affine transforms, other matrices are projective
+        let w = result[3];
+        Ok(Vector::<f64>::new(vec![
+            result[0] / w,
+            result[1] / w,
+            result[2] / w,
+        ]))
+    }
+
+    /// Transforms a 3 dimensional direction, translation does not apply
+    pub fn apply_vector(&self, direction: &Vector<f64>) -> Result<Vector<f64>, VectorError> {
+        let [x, y, z] = components_3d(direction)?;
+        let result = self.matrix * SVector::new([x, y, z, 0.0]);
+        Ok(Vector::<f64>::new(vec![result[0], result[1], result[2]]))
+    }
+}
+
+impl Mul for Transform {
+    type Output = Transform;
+
+    /// Composition applying `transform` first and then `self`
+    fn mul(self, transform: Transform) -> Transform {
+        Transform {
+            matrix: self.matrix * transform.matrix,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    fn close_vectors(a: &Vector<f64>, b: &[f64]) -> bool {
+        a.components().len() == b.len() && a.components().iter().zip(b).all(|(&x, &y)| close(x, y))
+    }
+
+    fn close_matrices(a: &Matrix<f64>, b: &Matrix<f64>) -> bool {
+        a.rows() == b.rows()
+            && a.data()
+                .iter()
+                .flatten()
+                .zip(b.data().iter().flatten())
+                .all(|(&x, &y)| close(x, y))
+    }
+
+    fn vector(components: &[f64]) -> Vector<f64> {
+        Vector::<f64>::new(components.to_vec())
+    }
+
+    #[test]
+    fn axis_rotations() {
+        let x = vector(&[1.0, 0.0, 0.0]);
+        let rotated = rotation_z(FRAC_PI_2).vector_multiplication_f(&x).unwrap();
+        assert!(close_vectors(&rotated, &[0.0, 1.0, 0.0]));
+        let rotated = rotation_y(FRAC_PI_2).vector_multiplication_f(&x).unwrap();
+        assert!(close_vectors(&rotated, &[0.0, 0.0, -1.0]));
+        let y = vector(&[0.0, 1.0, 0.0]);
+        let rotated = rotation_x(FRAC_PI_2).vector_multiplication_f(&y).unwrap();
+        assert!(close_vectors(&rotated, &[0.0, 0.0, 1.0]));
+        let rotated = rotation_2d(PI)
+            .vector_multiplication_f(&vector(&[1.0, 2.0]))
+            .unwrap();
+        assert!(close_vectors(&rotated, &[-1.0, -2.0]));
+
+        let axis = vector(&[0.0, 5.0, 0.0]);
+        assert!(close_matrices(
+            &rotation_axis_angle(&axis, 0.7).unwrap(),
+            &rotation_y(0.7)
+        ));
+        assert_eq!(
+            rotation_axis_angle(&vector(&[0.0, 0.0, 0.0]), 1.0),
+            Err(VectorError::ZeroVector)
+        );
+        assert_eq!(
+            rotation_axis_angle(&vector(&[1.0, 0.0]), 1.0),
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn quaternion_rotation_matrix_roundtrip() {
+        let axis = vector(&[1.0, -2.0, 0.5]);
+        for angle in [0.3, 2.0, PI - 0.01, 3.5] {
+            let quaternion = Quaternion::from_axis_angle(&axis, angle).unwrap();
+            let matrix = quaternion.to_rotation_matrix();
+            let recovered = Quaternion::from_rotation_matrix(&matrix).unwrap();
+            // q and -q are the same rotation
+            assert!(close(recovered.dot(&quaternion).abs(), 1.0));
+            assert!(close_matrices(&recovered.to_rotation_matrix(), &matrix));
+            let point = vector(&[0.2, 3.0, -1.0]);
+            let by_matrix = matrix.vector_multiplication_f(&point).unwrap();
+            assert!(close_vectors(
+                &quaternion.rotate(&point).unwrap(),
+                by_matrix.components()
+            ));
+        }
+        let (axis, angle) = Quaternion::from_axis_angle(&vector(&[0.0, 0.0, 3.0]), 1.2)
+            .unwrap()
+            .to_axis_angle();
+        assert!(close_vectors(&axis, &[0.0, 0.0, 1.0]));
+        assert!(close(angle, 1.2));
+        assert!(matches!(
+            Quaternion::from_rotation_matrix(&rotation_2d(1.0)),
+            Err(MatrixError::DimensionMismatch {
+                expected: (3, 3),
+                actual: (2, 2)
+            })
+        ));
+    }
+
+    #[test]
+    fn quaternion_algebra() {
+        let a = Quaternion::from_axis_angle(&vector(&[1.0, 0.0, 0.0]), 0.4).unwrap();
+        let b = Quaternion::from_axis_angle(&vector(&[0.0, 1.0, 0.0]), -1.1).unwrap();
+        let composed = (a * b).to_rotation_matrix();
+        let product = a
+            .to_rotation_matrix()
+            .matrix_multiplication_f(&b.to_rotation_matrix())
+            .unwrap();
+        assert!(close_matrices(&composed, &product));
+
+        let q = Quaternion::new(1.0, 2.0, 3.0, 4.0);
+        let identity = q * q.inverse().unwrap();
+        assert!(close(identity.w, 1.0) && close(identity.x, 0.0));
+        assert!(close(identity.y, 0.0) && close(identity.z, 0.0));
+        assert!(close(q.normalize().unwrap().norm(), 1.0));
+        assert_eq!(q.conjugate(), Quaternion::new(1.0, -2.0, -3.0, -4.0));
+        let zero = Quaternion::new(0.0, 0.0, 0.0, 0.0);
+        assert_eq!(zero.normalize(), Err(VectorError::ZeroVector));
+        assert_eq!(zero.inverse(), Err(VectorError::ZeroVector));
+    }
+
+    #[test]
+    fn quaternion_slerp() {
+        let axis = vector(&[1.0, 1.0, 1.0]);
+        let start = Quaternion::from_axis_angle(&axis, 0.2).unwrap();
+        let end = Quaternion::from_axis_angle(&axis, 1.4).unwrap();
+        let middle = start.slerp(&end, 0.25);
+        let (_, angle) = middle.to_axis_angle();
+        assert!(close(angle, 0.5));
+        assert!(close(start.slerp(&end, 0.0).dot(&start), 1.0));
+        assert!(close(start.slerp(&end, 1.0).dot(&end), 1.0));
+        // -end is the same rotation, interpolation takes the shorter arc
+        let (_, angle) = start.slerp(&-end, 0.5).to_axis_angle();
+        assert!(close(angle, 0.8));
+        let same = start.slerp(&start, 0.3);
+        assert!(close(same.dot(&start), 1.0));
+    }
+
+    #[test]
+    fn euler_angles() {
+        let angles = EulerAngles::new(0.3, -0.5, 2.0);
+        let matrix = angles.to_rotation_matrix();
+        let expected = rotation_z(2.0)
+            .matrix_multiplication_f(&rotation_y(-0.5))
+            .unwrap()
+            .matrix_multiplication_f(&rotation_x(0.3))
+            .unwrap();
+        assert!(close_matrices(&matrix, &expected));
+        assert!(close_matrices(
+            &angles.to_quaternion().to_rotation_matrix(),
+            &matrix
+        ));
+        let recovered = Quaternion::from_euler(&angles).to_euler();
+        assert!(close(recovered.roll, 0.3));
+        assert!(close(recovered.pitch, -0.5));
+        assert!(close(recovered.yaw, 2.0));
+
+        let locked = EulerAngles::new(0.4, FRAC_PI_2, 1.0);
+        let recovered = EulerAngles::from_rotation_matrix(&locked.to_rotation_matrix()).unwrap();
+        assert_eq!(recovered.roll, 0.0);
+        assert!(close_matrices(
+            &recovered.to_rotation_matrix(),
+            &locked.to_rotation_matrix()
+        ));
+    }
+
+    #[test]
+    fn transforms() {
+        let rotation = Quaternion::from_axis_angle(&vector(&[0.0, 0.0, 1.0]), FRAC_PI_2).unwrap();
+        let transform = Transform::scaling(&vector(&[2.0, 3.0, 4.0]))
+            .unwrap()
+            .rotate(&rotation)
+            .translate(&vector(&[10.0, 0.0, -1.0]))
+            .unwrap();
+        let point = vector(&[1.0, 1.0, 1.0]);
+        assert!(close_vectors(
+            &transform.apply_point(&point).unwrap(),
+            &[7.0, 2.0, 3.0]
+        ));
+        assert!(close_vectors(
+            &transform.apply_vector(&point).unwrap(),
+            &[-3.0, 2.0, 4.0]
+        ));
+
+        let inverse = transform.inverse().unwrap();
+        let moved = transform.apply_point(&point).unwrap();
+        assert!(close_vectors(
+            &inverse.apply_point(&moved).unwrap(),
+            &[1.0, 1.0, 1.0]
+        ));
+        let identity = (transform * inverse).to_matrix();
+        assert!(close_matrices(
+            &identity,
+            &Transform::identity().to_matrix()
+        ));
+        assert_eq!(
+            Transform::from_matrix(&transform.to_matrix()),
+            Ok(transform)
+        );
+
+        let flat = Transform::scaling(&vector(&[1.0, 0.0, 1.0])).unwrap();
+        assert_eq!(flat.inverse(), Err(MatrixError::Singular));
+        assert_eq!(
+            transform.apply_point(&vector(&[1.0, 2.0])),
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+}
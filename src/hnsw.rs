@@ -0,0 +1,910 @@
+//! Approximate nearest neighbor search with hierarchical navigable small world graphs (HNSW).
+//!
+//! Every point is a node of a proximity graph on layer 0 and, with exponentially decreasing
+//! probability, of sparser graphs on the layers above. Queries descend greedily through the upper
+//! layers and finish with a beam search of width `ef` on layer 0, visiting a small fraction of the
+//! points at the price of occasionally missing a true neighbor.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::io::IoError;
+use crate::neighbors::{Candidate, Metric, Neighbor};
+use crate::simd::{Backend, SimdElement};
+use crate::vector::{Component, Vector, VectorError};
+
+const MAGIC: &[u8] = b"DATASHNSW";
+const VERSION: u8 = 1;
+
+/// Highest layer a point can be placed on
+const MAX_LEVEL: usize = 32;
+
+/// Largest number of components per point accepted when reading an index
+const MAX_DIMENSIONS: usize = 1 << 24;
+
+/// Largest `m` accepted when reading an index
+const MAX_M: usize = u16::MAX as usize;
+
+/// Component types of indexed vectors
+pub trait HnswElement: SimdElement + Component {
+    /// Size in bytes, also identifies the element type in saved indexes
+    const SIZE: usize;
+
+    fn from_f64(value: f64) -> Self;
+
+    fn write_le(self, bytes: &mut Vec<u8>);
+
+    /// Reads value from exactly `SIZE` little endian bytes
+    fn read_le(bytes: &[u8]) -> Self;
+
+    /// `magnitude` of the vector, which does not overflow or underflow in `Self`
+    fn magnitude(vector: &Vector<Self>) -> f64;
+}
+
+impl HnswElement for f32 {
+    const SIZE: usize = 4;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn write_le(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn magnitude(vector: &Vector<f32>) -> f64 {
+        vector.magnitude()
+    }
+}
+
+impl HnswElement for f64 {
+    const SIZE: usize = 8;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn write_le(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_le(bytes: &[u8]) -> Self {
+        let mut buffer = [0; 8];
+        buffer.copy_from_slice(&bytes[..8]);
+        f64::from_le_bytes(buffer)
+    }
+
+    fn magnitude(vector: &Vector<f64>) -> f64 {
+        vector.magnitude()
+    }
+}
+
+/// Parameters of the graph, larger values improve recall at the cost of memory and time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HnswOptions {
+    /// Links kept per point on upper layers, layer 0 keeps twice as many. At least 2
+    pub m: usize,
+    /// Width of the search finding the links of inserted points
+    pub ef_construction: usize,
+    /// Width of the search answering queries, raised to `k` when smaller
+    pub ef_search: usize,
+    /// Seed of the generator drawing the layers of inserted points
+    pub seed: u64,
+}
+
+impl Default for HnswOptions {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+            seed: 0x5eed,
+        }
+    }
+}
+
+impl HnswOptions {
+    /// Returns copy of options with given number of links per point
+    pub fn with_m(mut self, m: usize) -> Self {
+        self.m = m;
+        self
+    }
+
+    /// Returns copy of options with given construction search width
+    pub fn with_ef_construction(mut self, ef_construction: usize) -> Self {
+        self.ef_construction = ef_construction;
+        self
+    }
+
+    /// Returns copy of options with given query search width
+    pub fn with_ef_search(mut self, ef_search: usize) -> Self {
+        self.ef_search = ef_search;
+        self
+    }
+
+    /// Returns copy of options with given seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// HNSW index over `Vector<f32>` or `Vector<f64>`.
+///
+/// Points are numbered in insertion order. Deleted points are only marked, they keep routing
+/// queries through the graph but are never returned.
+/// ```
+/// use datas::hnsw::{Hnsw, HnswOptions};
+/// use datas::neighbors::Metric;
+/// use datas::vector::Vector;
+/// let mut index = Hnsw::<f32>::new(Metric::Euclidean, HnswOptions::default()).unwrap();
+/// for i in 0..100 {
+///     index.insert(&Vector::<f32>::new(vec![i as f32, 0.0])).unwrap();
+/// }
+/// index.delete(42);
+/// let found = index.knn(&Vector::<f32>::new(vec![42.2, 0.0]), 2).unwrap();
+/// assert_eq!(found[0].index, 43);
+/// assert_eq!(found[1].index, 41);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Hnsw<T> {
+    options: HnswOptions,
+    metric: Metric,
+    dimensions: Option<usize>,
+    /// Components of all points stored contiguously, normalized for the cosine metric
+    data: Vec<T>,
+    /// Links of every point, one list per layer the point is on
+    links: Vec<Vec<Vec<u32>>>,
+    deleted: Vec<bool>,
+    entry: Option<usize>,
+    /// State of the generator drawing layers
+    state: u64,
+    backend: Backend,
+}
+
+impl<T: HnswElement> Hnsw<T> {
+    /// Empty index, `m` below 2 is raised to 2. Fails with `InvalidExponent` when the metric is
+    /// `Minkowski` with `p` that is NaN or below 1
+    pub fn new(metric: Metric, options: HnswOptions) -> Result<Self, VectorError> {
+        if let Metric::Minkowski(p) = metric {
+            if p.is_nan() || p < 1.0 {
+                return Err(VectorError::InvalidExponent { p });
+            }
+        }
+        let options = HnswOptions {
+            m: options.m.max(2),
+            ef_construction: options.ef_construction.max(1),
+            ..options
+        };
+        Ok(Self {
+            options,
+            metric,
+            dimensions: None,
+            data: Vec::new(),
+            links: Vec::new(),
+            deleted: Vec::new(),
+            entry: None,
+            state: options.seed,
+            backend: Backend::detect(),
+        })
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn options(&self) -> HnswOptions {
+        self.options
+    }
+
+    /// Number of inserted points, deleted ones included
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    pub fn is_deleted(&self, index: usize) -> bool {
+        self.deleted.get(index).copied().unwrap_or(false)
+    }
+
+    /// Marks point as deleted, returns `false` if it does not exist or was already deleted
+    pub fn delete(&mut self, index: usize) -> bool {
+        match self.deleted.get_mut(index) {
+            Some(deleted) if !*deleted => {
+                *deleted = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds point to the graph and returns its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index already holds `u32::MAX` points
+    pub fn insert(&mut self, point: &Vector<T>) -> Result<usize, VectorError> {
+        let point = self.prepared(point)?;
+        let index = self.links.len();
+        assert!(index < u32::MAX as usize, "HNSW index is full");
+        self.dimensions = Some(point.len());
+        self.data.extend_from_slice(&point);
+        let level = self.random_level();
+        self.links.push(vec![Vec::new(); level + 1]);
+        self.deleted.push(false);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(index);
+            return Ok(index);
+        };
+        let top = self.links[entry].len() - 1;
+        let mut nearest = Candidate {
+            distance: self.distance(&point, self.point(entry)),
+            index: entry,
+        };
+        for layer in (level + 1..=top).rev() {
+            nearest = self.greedy(&point, nearest, layer);
+        }
+        let mut entries = vec![nearest];
+        for layer in (0..=level.min(top)).rev() {
+            let found =
+                self.search_layer(&point, &entries, self.options.ef_construction, layer, false);
+            let selected = self.select(&found, self.options.m);
+            for &neighbor in &selected {
+                self.connect(neighbor as usize, index, layer);
+            }
+            self.links[index][layer] = selected;
+            entries = found;
+        }
+        if level > top {
+            self.entry = Some(index);
+        }
+        Ok(index)
+    }
+
+    /// Approximately `k` closest points that are not deleted, sorted by distance
+    pub fn knn(&self, query: &Vector<T>, k: usize) -> Result<Vec<Neighbor>, VectorError> {
+        self.knn_with_ef(query, k, self.options.ef_search)
+    }
+
+    /// Same as `knn` with a search width other than `ef_search`, wider searches find more of the
+    /// true neighbors
+    pub fn knn_with_ef(
+        &self,
+        query: &Vector<T>,
+        k: usize,
+        ef: usize,
+    ) -> Result<Vec<Neighbor>, VectorError> {
+        let query = self.prepared(query)?;
+        let Some(entry) = self.entry else {
+            return Ok(Vec::new());
+        };
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let mut nearest = Candidate {
+            distance: self.distance(&query, self.point(entry)),
+            index: entry,
+        };
+        for layer in (1..self.links[entry].len()).rev() {
+            nearest = self.greedy(&query, nearest, layer);
+        }
+        let found = self.search_layer(&query, &[nearest], ef.max(k), 0, true);
+        Ok(found
+            .into_iter()
+            .take(k)
+            .map(|candidate| Neighbor {
+                index: candidate.index,
+                distance: self.reported(candidate.distance),
+            })
+            .collect())
+    }
+
+    /// Components in stored form, fails unless the vector has the dimensions of the indexed ones
+    fn prepared(&self, vector: &Vector<T>) -> Result<Vec<T>, VectorError> {
+        if let Some(dimensions) = self.dimensions {
+            if vector.dimensions() != dimensions as u64 {
+                return Err(VectorError::DimensionMismatch {
+                    expected: dimensions as u64,
+                    actual: vector.dimensions(),
+                });
+            }
+        }
+        let components = vector.components();
+        if self.metric != Metric::Cosine {
+            return Ok(components.to_vec());
+        }
+        let magnitude = T::magnitude(vector);
+        if magnitude == 0.0 {
+            return Err(VectorError::ZeroVector);
+        }
+        Ok(components
+            .iter()
+            .map(|&component| T::from_f64(component.as_f64() / magnitude))
+            .collect())
+    }
+
+    fn point(&self, index: usize) -> &[T] {
+        let dimensions = self.dimensions.unwrap_or(0);
+        &self.data[index * dimensions..(index + 1) * dimensions]
+    }
+
+    /// Value increasing with the distance, cheaper to compute than the distance itself
+    fn distance(&self, a: &[T], b: &[T]) -> f64 {
+        let differences = a
+            .iter()
+            .zip(b)
+            .map(|(&x, &y)| (x.as_f64() - y.as_f64()).abs());
+        match self.metric {
+            Metric::Euclidean => differences.map(|d| d * d).sum(),
+            Metric::Manhattan => differences.sum(),
+            Metric::Chebyshev => differences.fold(0.0, f64::max),
+            Metric::Minkowski(p) if p.is_infinite() => differences.fold(0.0, f64::max),
+            Metric::Minkowski(p) => differences.map(|d| d.powf(p)).sum(),
+            // stored points have unit length
+            Metric::Cosine => 1.0 - self.backend.dot(a, b).map_or(f64::NAN, |dot| dot.as_f64()),
+        }
+    }
+
+    /// Distance reported to the caller from the value compared during search
+    fn reported(&self, distance: f64) -> f64 {
+        match self.metric {
+            Metric::Euclidean => distance.sqrt(),
+            Metric::Minkowski(p) if p.is_finite() => distance.powf(1.0 / p),
+            Metric::Cosine => distance.max(0.0),
+            _ => distance,
+        }
+    }
+
+    /// Layer of a new point, layer `l` is reached with probability `m^-l`
+    fn random_level(&mut self) -> usize {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let uniform = ((self.state >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        let level = -uniform.ln() / (self.options.m as f64).ln();
+        (level as usize).min(MAX_LEVEL)
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            2 * self.options.m
+        } else {
+            self.options.m
+        }
+    }
+
+    /// Moves to closer neighbors on `layer` until none is closer
+    fn greedy(&self, query: &[T], mut nearest: Candidate, layer: usize) -> Candidate {
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for &neighbor in &self.links[nearest.index][layer] {
+                let neighbor = neighbor as usize;
+                let distance = self.distance(query, self.point(neighbor));
+                if distance < nearest.distance {
+                    nearest = Candidate {
+                        distance,
+                        index: neighbor,
+                    };
+                    improved = true;
+                }
+            }
+        }
+        nearest
+    }
+
+    /// Beam search of width `ef` on `layer`, returns the closest points found sorted by distance
+    fn search_layer(
+        &self,
+        query: &[T],
+        entries: &[Candidate],
+        ef: usize,
+        layer: usize,
+        skip_deleted: bool,
+    ) -> Vec<Candidate> {
+        let mut visited = vec![0u64; (self.len() + 63) / 64];
+        let mut visit = |index: usize| {
+            let (word, bit) = (index / 64, 1u64 << (index % 64));
+            let new = visited[word] & bit == 0;
+            visited[word] |= bit;
+            new
+        };
+        let mut candidates = BinaryHeap::new();
+        let mut found: BinaryHeap<Candidate> = BinaryHeap::new();
+        for &entry in entries {
+            if visit(entry.index) {
+                candidates.push(Reverse(entry));
+                if !(skip_deleted && self.deleted[entry.index]) {
+                    found.push(entry);
+                }
+            }
+        }
+        while found.len() > ef {
+            found.pop();
+        }
+        while let Some(Reverse(current)) = candidates.pop() {
+            if found.len() >= ef && found.peek().is_some_and(|worst| current > *worst) {
+                break;
+            }
+            for &neighbor in &self.links[current.index][layer] {
+                let neighbor = neighbor as usize;
+                if !visit(neighbor) {
+                    continue;
+                }
+                let candidate = Candidate {
+                    distance: self.distance(query, self.point(neighbor)),
+                    index: neighbor,
+                };
+                if found.len() < ef || found.peek().is_some_and(|worst| candidate < *worst) {
+                    candidates.push(Reverse(candidate));
+                    if !(skip_deleted && self.deleted[neighbor]) {
+                        found.push(candidate);
+                        if found.len() > ef {
+                            found.pop();
+                        }
+                    }
+                }
+            }
+        }
+        found.into_sorted_vec()
+    }
+
+    /// Picks up to `m` links from candidates sorted by distance, skipping candidates that are
+    /// closer to an already picked one than to the linked point so links spread in all directions
+    fn select(&self, candidates: &[Candidate], m: usize) -> Vec<u32> {
+        let mut selected: Vec<usize> = Vec::with_capacity(m);
+        for candidate in candidates {
+            if selected.len() >= m {
+                break;
+            }
+            let point = self.point(candidate.index);
+            if selected
+                .iter()
+                .all(|&other| self.distance(point, self.point(other)) > candidate.distance)
+            {
+                selected.push(candidate.index);
+            }
+        }
+        selected.into_iter().map(|index| index as u32).collect()
+    }
+
+    /// Links `from` to `to` on `layer`, pruning links of `from` that exceed the limit
+    fn connect(&mut self, from: usize, to: usize, layer: usize) {
+        self.links[from][layer].push(to as u32);
+        let max_links = self.max_links(layer);
+        if self.links[from][layer].len() <= max_links {
+            return;
+        }
+        let point = self.point(from);
+        let mut candidates: Vec<Candidate> = self.links[from][layer]
+            .iter()
+            .map(|&neighbor| Candidate {
+                distance: self.distance(point, self.point(neighbor as usize)),
+                index: neighbor as usize,
+            })
+            .collect();
+        candidates.sort_unstable();
+        self.links[from][layer] = self.select(&candidates, max_links);
+    }
+
+    /// Writes the index in a binary format read by `read`
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), IoError> {
+        let (metric, parameter) = match self.metric {
+            Metric::Euclidean => (0, 0.0),
+            Metric::Manhattan => (1, 0.0),
+            Metric::Chebyshev => (2, 0.0),
+            Metric::Minkowski(p) => (3, p),
+            Metric::Cosine => (4, 0.0),
+        };
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&[VERSION, T::SIZE as u8, metric]);
+        header.extend_from_slice(&parameter.to_le_bytes());
+        for value in [
+            self.options.m as u64,
+            self.options.ef_construction as u64,
+            self.options.ef_search as u64,
+            self.options.seed,
+            self.state,
+            self.dimensions.unwrap_or(0) as u64,
+            self.len() as u64,
+            self.entry.map_or(u64::MAX, |entry| entry as u64),
+        ] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        writer.write_all(&header)?;
+
+        let mut bytes = Vec::new();
+        for (index, layers) in self.links.iter().enumerate() {
+            bytes.clear();
+            for &component in self.point(index) {
+                component.write_le(&mut bytes);
+            }
+            bytes.extend_from_slice(&[self.deleted[index] as u8, layers.len() as u8]);
+            for links in layers {
+                bytes.extend_from_slice(&(links.len() as u32).to_le_bytes());
+                for &link in links {
+                    bytes.extend_from_slice(&link.to_le_bytes());
+                }
+            }
+            writer.write_all(&bytes)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads index written by `write`, fails with `IoError::Format` on corrupted data or when it
+    /// stores other components than `T`
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, IoError> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(IoError::Format("not an HNSW index".to_string()));
+        }
+        let [version, size, metric] = read_array(&mut reader)?;
+        if version != VERSION {
+            return Err(IoError::Format(format!(
+                "unsupported HNSW index version {}",
+                version
+            )));
+        }
+        if size as usize != T::SIZE {
+            return Err(IoError::Format(format!(
+                "index stores {} byte components, expected {}",
+                size,
+                T::SIZE
+            )));
+        }
+        let parameter = f64::from_le_bytes(read_array(&mut reader)?);
+        let metric = match metric {
+            0 => Metric::Euclidean,
+            1 => Metric::Manhattan,
+            2 => Metric::Chebyshev,
+            3 if parameter >= 1.0 => Metric::Minkowski(parameter),
+            3 => {
+                return Err(IoError::Format(format!(
+                    "invalid Minkowski p {}",
+                    parameter
+                )))
+            }
+            4 => Metric::Cosine,
+            other => return Err(IoError::Format(format!("unknown metric {}", other))),
+        };
+        let mut values = [0u64; 8];
+        for value in values.iter_mut() {
+            *value = u64::from_le_bytes(read_array(&mut reader)?);
+        }
+        let [m, ef_construction, ef_search, seed, state, dimensions, len, entry] = values;
+        let options = HnswOptions {
+            m: m as usize,
+            ef_construction: ef_construction as usize,
+            ef_search: ef_search as usize,
+            seed,
+        };
+        if options.m < 2
+            || options.m > MAX_M
+            || len >= u32::MAX as u64
+            || dimensions > MAX_DIMENSIONS as u64
+        {
+            return Err(IoError::Format("invalid HNSW index header".to_string()));
+        }
+        let (dimensions, len) = (dimensions as usize, len as usize);
+        let entry = match entry {
+            u64::MAX if len == 0 => None,
+            entry if entry < len as u64 => Some(entry as usize),
+            _ => return Err(IoError::Format("entry point out of bound".to_string())),
+        };
+
+        let mut index =
+            Self::new(metric, options).map_err(|error| IoError::Format(error.to_string()))?;
+        index.state = state;
+        index.entry = entry;
+        if len > 0 {
+            index.dimensions = Some(dimensions);
+        }
+        let mut bytes = vec![0; dimensions * T::SIZE];
+        for _ in 0..len {
+            reader.read_exact(&mut bytes)?;
+            index
+                .data
+                .extend(bytes.chunks_exact(T::SIZE).map(T::read_le));
+            let [deleted, layers] = read_array(&mut reader)?;
+            if deleted > 1 || layers == 0 || layers as usize > MAX_LEVEL + 1 {
+                return Err(IoError::Format("invalid HNSW point".to_string()));
+            }
+            index.deleted.push(deleted == 1);
+            let mut point_links = Vec::with_capacity(layers as usize);
+            for layer in 0..layers as usize {
+                let count = u32::from_le_bytes(read_array(&mut reader)?);
+                if count as usize > index.max_links(layer) {
+                    return Err(IoError::Format("too many links".to_string()));
+                }
+                let links = (0..count)
+                    .map(|_| read_array(&mut reader).map(u32::from_le_bytes))
+                    .collect::<Result<Vec<u32>, _>>()?;
+                point_links.push(links);
+            }
+            index.links.push(point_links);
+        }
+        // links must stay within the graph and point to points present on the same layer
+        for layers in &index.links {
+            for (layer, links) in layers.iter().enumerate() {
+                if links.iter().any(|&link| {
+                    index
+                        .links
+                        .get(link as usize)
+                        .map_or(true, |target| target.len() <= layer)
+                }) {
+                    return Err(IoError::Format("link out of bound".to_string()));
+                }
+            }
+        }
+        if let Some(entry) = index.entry {
+            if index
+                .links
+                .iter()
+                .any(|layers| layers.len() > index.links[entry].len())
+            {
+                return Err(IoError::Format(
+                    "entry point is not on the top layer".to_string(),
+                ));
+            }
+        }
+        Ok(index)
+    }
+
+    /// Saves index to a file at `path`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), IoError> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+
+    /// Loads index saved with `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, IoError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N], IoError> {
+    let mut buffer = [0; N];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neighbors::{BruteForce, NearestNeighbors};
+    use crate::uniform;
+
+    fn points(count: usize, dimensions: usize, seed: u64) -> Vec<Vec<f64>> {
+        uniform(count * dimensions, seed)
+            .chunks(dimensions)
+            .map(|point| point.iter().map(|value| value - 0.5).collect())
+            .collect()
+    }
+
+    fn index<T: HnswElement>(
+        data: &[Vec<f64>],
+        metric: Metric,
+        convert: fn(Vec<f64>) -> Vector<T>,
+    ) -> Hnsw<T> {
+        let options = HnswOptions::default().with_m(8).with_ef_construction(64);
+        let mut index = Hnsw::new(metric, options).unwrap();
+        for point in data {
+            index.insert(&convert(point.clone())).unwrap();
+        }
+        index
+    }
+
+    fn to_f32(components: Vec<f64>) -> Vector<f32> {
+        Vector::<f32>::new(components.into_iter().map(|c| c as f32).collect())
+    }
+
+    /// Share of the true `k` nearest neighbors found by the index
+    fn recall<T: HnswElement>(
+        index: &Hnsw<T>,
+        data: &[Vec<f64>],
+        queries: &[Vec<f64>],
+        metric: Metric,
+        convert: fn(Vec<f64>) -> Vector<T>,
+    ) -> f64 {
+        let vectors: Vec<Vector<f64>> = data.iter().cloned().map(Vector::<f64>::new).collect();
+        let brute = BruteForce::new(&vectors, metric).unwrap();
+        let k = 10;
+        let mut hits = 0;
+        for query in queries {
+            let expected = brute.knn(&Vector::<f64>::new(query.clone()), k).unwrap();
+            let found = index.knn(&convert(query.clone()), k).unwrap();
+            assert_eq!(found.len(), k);
+            assert!(found
+                .windows(2)
+                .all(|pair| pair[0].distance <= pair[1].distance));
+            hits += found
+                .iter()
+                .filter(|neighbor| expected.iter().any(|e| e.index == neighbor.index))
+                .count();
+        }
+        hits as f64 / (queries.len() * k) as f64
+    }
+
+    #[test]
+    fn high_recall() {
+        let data = points(1000, 12, 1);
+        let queries = points(30, 12, 2);
+        for metric in [Metric::Euclidean, Metric::Cosine, Metric::Manhattan] {
+            let index64 = index(&data, metric, Vector::<f64>::new);
+            assert!(recall(&index64, &data, &queries, metric, Vector::<f64>::new) >= 0.95);
+            let index32 = index(&data, metric, to_f32);
+            assert!(recall(&index32, &data, &queries, metric, to_f32) >= 0.95);
+        }
+    }
+
+    #[test]
+    fn reported_distances() {
+        let data = points(200, 4, 3);
+        let query = points(1, 4, 4).remove(0);
+        let vectors: Vec<Vector<f64>> = data.iter().cloned().map(Vector::<f64>::new).collect();
+        for metric in [Metric::Euclidean, Metric::Cosine, Metric::Minkowski(3.0)] {
+            let index = index(&data, metric, Vector::<f64>::new);
+            let found = index
+                .knn_with_ef(&Vector::<f64>::new(query.clone()), 5, 200)
+                .unwrap();
+            let expected = BruteForce::new(&vectors, metric)
+                .unwrap()
+                .knn(&Vector::<f64>::new(query.clone()), 5)
+                .unwrap();
+            for (found, expected) in found.iter().zip(&expected) {
+                assert_eq!(found.index, expected.index);
+                assert!((found.distance - expected.distance).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn deletion() {
+        let data = points(300, 3, 5);
+        let mut index = index(&data, Metric::Euclidean, Vector::<f64>::new);
+        let query = Vector::<f64>::new(data[17].clone());
+        assert_eq!(index.knn(&query, 1).unwrap()[0].index, 17);
+        assert!(index.delete(17));
+        assert!(!index.delete(17));
+        assert!(!index.delete(300));
+        assert!(index.is_deleted(17));
+        let found = index.knn(&query, 10).unwrap();
+        assert_eq!(found.len(), 10);
+        assert!(found.iter().all(|neighbor| neighbor.index != 17));
+        for i in 0..300 {
+            index.delete(i);
+        }
+        assert!(index.knn(&query, 3).unwrap().is_empty());
+        assert_eq!(index.len(), 300);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let mut index = Hnsw::<f64>::new(Metric::Cosine, HnswOptions::default()).unwrap();
+        assert!(index.is_empty());
+        let point = Vector::<f64>::new(vec![1.0, 2.0]);
+        assert_eq!(index.knn(&point, 3), Ok(vec![]));
+        assert_eq!(index.insert(&point), Ok(0));
+        assert_eq!(
+            index.insert(&Vector::<f64>::new(vec![1.0])),
+            Err(VectorError::DimensionMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            index.insert(&Vector::<f64>::new(vec![0.0, 0.0])),
+            Err(VectorError::ZeroVector)
+        );
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.knn(&point, 0), Ok(vec![]));
+
+        // squares of these components overflow or underflow in f32
+        let mut index = Hnsw::<f32>::new(Metric::Cosine, HnswOptions::default()).unwrap();
+        assert_eq!(index.insert(&Vector::<f32>::new(vec![3e-30, 4e-30])), Ok(0));
+        assert_eq!(index.insert(&Vector::<f32>::new(vec![3e30, 4e30])), Ok(1));
+        for found in index.knn(&Vector::<f32>::new(vec![3.0, 4.0]), 2).unwrap() {
+            assert!(found.distance < 1e-6);
+        }
+    }
+
+    #[test]
+    fn write_and_read() {
+        let data = points(400, 6, 6);
+        let mut index = index(&data, Metric::Cosine, to_f32);
+        index.delete(3);
+        let mut bytes = Vec::new();
+        index.write(&mut bytes).unwrap();
+        let mut read = Hnsw::<f32>::read(bytes.as_slice()).unwrap();
+        assert_eq!(read.links, index.links);
+        assert_eq!(read.data, index.data);
+        assert_eq!(read.deleted, index.deleted);
+        assert_eq!(read.options(), index.options());
+        assert_eq!(read.metric(), Metric::Cosine);
+        for query in points(5, 6, 7) {
+            assert_eq!(
+                read.knn(&to_f32(query.clone()), 5).unwrap(),
+                index.knn(&to_f32(query), 5).unwrap()
+            );
+        }
+        // the generator state is restored, so later insertions match too
+        let extra = to_f32(points(1, 6, 8).remove(0));
+        assert_eq!(read.insert(&extra), index.insert(&extra));
+        assert_eq!(read.links, index.links);
+
+        assert!(matches!(
+            Hnsw::<f64>::read(bytes.as_slice()),
+            Err(IoError::Format(_))
+        ));
+        assert!(matches!(
+            Hnsw::<f32>::read(&bytes[..bytes.len() - 3]),
+            Err(IoError::Io(_))
+        ));
+        let mut corrupted = bytes.clone();
+        corrupted[0] = b'X';
+        assert!(matches!(
+            Hnsw::<f32>::read(corrupted.as_slice()),
+            Err(IoError::Format(_))
+        ));
+        // first link of the first point pointing past the last point
+        let header = MAGIC.len() + 3 + 8 + 8 * 8;
+        let first_link = header + 6 * 4 + 2 + 4;
+        let mut corrupted = bytes.clone();
+        corrupted[first_link..first_link + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Hnsw::<f32>::read(corrupted.as_slice()),
+            Err(IoError::Format(_))
+        ));
+        // sizes are checked before anything is allocated for them
+        let dimensions = MAGIC.len() + 3 + 8 + 5 * 8;
+        let mut corrupted = bytes[..header].to_vec();
+        corrupted[dimensions..dimensions + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            Hnsw::<f32>::read(corrupted.as_slice()),
+            Err(IoError::Format(_))
+        ));
+        let mut corrupted = bytes[..header].to_vec();
+        corrupted[MAGIC.len() + 2] = 3;
+        corrupted[MAGIC.len() + 3..MAGIC.len() + 11].copy_from_slice(&0.5f64.to_le_bytes());
+        assert!(matches!(
+            Hnsw::<f32>::read(corrupted.as_slice()),
+            Err(IoError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_minkowski() {
+        for p in [0.5, f64::NAN] {
+            assert!(matches!(
+                Hnsw::<f64>::new(Metric::Minkowski(p), HnswOptions::default()),
+                Err(VectorError::InvalidExponent { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn save_and_load() {
+        let data = points(50, 2, 9);
+        let index = index(&data, Metric::Euclidean, Vector::<f64>::new);
+        let path = std::env::temp_dir().join(format!("datas-hnsw-{}.bin", std::process::id()));
+        index.save(&path).unwrap();
+        let loaded = Hnsw::<f64>::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        let query = Vector::<f64>::new(vec![0.1, 0.2]);
+        assert_eq!(loaded.knn(&query, 4), index.knn(&query, 4));
+    }
+}
@@ -0,0 +1,696 @@
+use std::fmt::{self, Display};
+use std::io::{BufRead, Write};
+use std::ops::Neg;
+use std::str::FromStr;
+
+use crate::matrix::{Matrix, MatrixError};
+
+/// This enum represents possible errors that can happen while reading or writing matrices.
+#[derive(Debug)]
+pub enum IoError {
+    /// Underlying reader or writer failed
+    Io(std::io::Error),
+    /// Input is malformed, `line` and `column` are 1 based
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Data is malformed or does not fit the layout of the format
+    Format(String),
+    /// Parsed data does not form a valid matrix
+    Matrix(MatrixError),
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IoError::Io(error) => write!(f, "{}", error),
+            IoError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "{} at line {}, column {}", message, line, column),
+            IoError::Format(message) => f.write_str(message),
+            IoError::Matrix(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IoError::Io(error) => Some(error),
+            IoError::Matrix(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for IoError {
+    fn from(error: std::io::Error) -> Self {
+        IoError::Io(error)
+    }
+}
+
+impl From<MatrixError> for IoError {
+    fn from(error: MatrixError) -> Self {
+        IoError::Matrix(error)
+    }
+}
+
+/// Element types that can be read from and written to text formats
+pub trait Element: Copy + Default + PartialEq + Display + FromStr + Neg<Output = Self> {
+    /// Matrix Market field written for this type
+    const MARKET_FIELD: &'static str;
+
+    /// Whether values of given Matrix Market field can be stored in this type
+    fn accepts_field(field: &str) -> bool;
+
+    /// Value used for entries of `pattern` matrices
+    fn one() -> Self;
+}
+
+impl Element for i64 {
+    const MARKET_FIELD: &'static str = "integer";
+
+    fn accepts_field(field: &str) -> bool {
+        matches!(field, "integer" | "pattern")
+    }
+
+    fn one() -> Self {
+        1
+    }
+}
+
+impl Element for f64 {
+    const MARKET_FIELD: &'static str = "real";
+
+    fn accepts_field(field: &str) -> bool {
+        matches!(field, "real" | "integer" | "pattern")
+    }
+
+    fn one() -> Self {
+        1.0
+    }
+}
+
+/// Layout used when writing Matrix Market files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarketFormat {
+    /// Every entry is written in column-major order
+    Array,
+    /// Only nonzero entries are written as `row col value` triples
+    Coordinate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Symmetry {
+    General,
+    Symmetric,
+    SkewSymmetric,
+}
+
+/// Largest number of elements read from a coordinate file, which only lists nonzero entries. Also
+/// bounds the rows and columns of any file, one of them sizes an allocation when the other is zero
+const MAX_COORDINATE_ELEMENTS: usize = 1 << 28;
+
+fn parse_error(line: usize, column: usize, message: impl Into<String>) -> IoError {
+    IoError::Parse {
+        line,
+        column,
+        message: message.into(),
+    }
+}
+
+/// Splits line on whitespace, yielding tokens with their 1 based columns
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut start = None;
+    for (column, (offset, ch)) in line.char_indices().enumerate() {
+        match (ch.is_whitespace(), start) {
+            (false, None) => start = Some((column + 1, offset)),
+            (true, Some((token_column, token_offset))) => {
+                result.push((token_column, &line[token_offset..offset]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((token_column, token_offset)) = start {
+        result.push((token_column, &line[token_offset..]));
+    }
+    result
+}
+
+fn parse_token<V: FromStr>(line: usize, (column, token): (usize, &str)) -> Result<V, IoError> {
+    token
+        .parse::<V>()
+        .map_err(|_| parse_error(line, column, format!("invalid value `{}`", token)))
+}
+
+/// Reads matrix from Matrix Market `.mtx` text in array or coordinate format
+///
+/// Result is dense, so coordinate files may describe at most `2^28` elements and any file at most
+/// `2^28` rows or columns.
+/// ```
+/// use datas::io::read_matrix_market;
+/// use datas::matrix::Matrix;
+/// let text = "%%MatrixMarket matrix coordinate integer general\n2 2 2\n1 1 5\n2 2 7\n";
+/// let matrix: Matrix<i64> = read_matrix_market(text.as_bytes()).unwrap();
+/// assert_eq!(matrix, Matrix::<i64>::new(vec![vec![5, 0], vec![0, 7]]).unwrap());
+/// ```
+pub fn read_matrix_market<T: Element, R: BufRead>(reader: R) -> Result<Matrix<T>, IoError> {
+    let mut lines = Vec::new();
+    for line in reader.lines() {
+        lines.push(line?);
+    }
+    let header = lines
+        .first()
+        .ok_or_else(|| parse_error(1, 1, "missing `%%MatrixMarket` header"))?;
+    let banner = tokens(header);
+    if banner.len() != 5 || banner[0].1 != "%%MatrixMarket" {
+        return Err(parse_error(1, 1, "missing `%%MatrixMarket` header"));
+    }
+    let (column, object) = banner[1];
+    if !object.eq_ignore_ascii_case("matrix") {
+        return Err(parse_error(
+            1,
+            column,
+            format!("unsupported object `{}`", object),
+        ));
+    }
+    let (format_column, format) = banner[2];
+    let coordinate = match format.to_ascii_lowercase().as_str() {
+        "coordinate" => true,
+        "array" => false,
+        _ => {
+            return Err(parse_error(
+                1,
+                format_column,
+                format!("unsupported format `{}`", format),
+            ))
+        }
+    };
+    let (column, field) = banner[3];
+    let field = field.to_ascii_lowercase();
+    if !T::accepts_field(&field) || (field == "pattern" && !coordinate) {
+        return Err(parse_error(
+            1,
+            column,
+            format!("unsupported field `{}`", field),
+        ));
+    }
+    let (column, symmetry) = banner[4];
+    let symmetry = match symmetry.to_ascii_lowercase().as_str() {
+        "general" => Symmetry::General,
+        "symmetric" => Symmetry::Symmetric,
+        "skew-symmetric" => Symmetry::SkewSymmetric,
+        _ => {
+            return Err(parse_error(
+                1,
+                column,
+                format!("unsupported symmetry `{}`", symmetry),
+            ))
+        }
+    };
+
+    let mut data_lines = lines
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(index, line)| (index + 1, tokens(line)))
+        .filter(|(_, tokens)| !tokens.is_empty() && !tokens[0].1.starts_with('%'))
+        .collect::<Vec<_>>()
+        .into_iter();
+
+    let (size_line, size) = data_lines
+        .next()
+        .ok_or_else(|| parse_error(lines.len(), 1, "missing size line"))?;
+    let expected_sizes = if coordinate { 3 } else { 2 };
+    if size.len() != expected_sizes {
+        return Err(parse_error(
+            size_line,
+            1,
+            format!("expected {} sizes, found {}", expected_sizes, size.len()),
+        ));
+    }
+    let rows: usize = parse_token(size_line, size[0])?;
+    let cols: usize = parse_token(size_line, size[1])?;
+    if symmetry != Symmetry::General && rows != cols {
+        return Err(parse_error(size_line, 1, "symmetric matrix must be square"));
+    }
+    if rows.max(cols) > MAX_COORDINATE_ELEMENTS {
+        return Err(parse_error(size_line, 1, "matrix dimension too large"));
+    }
+    let elements = rows
+        .checked_mul(cols)
+        .ok_or_else(|| parse_error(size_line, 1, "matrix size overflows"))?;
+    if coordinate {
+        if elements > MAX_COORDINATE_ELEMENTS {
+            return Err(parse_error(
+                size_line,
+                1,
+                "matrix too large to read densely",
+            ));
+        }
+    } else {
+        // check the values are present before allocating for the declared size
+        let expected = match symmetry {
+            Symmetry::General => elements,
+            Symmetry::Symmetric => elements / 2 + (rows + 1) / 2,
+            Symmetry::SkewSymmetric => elements / 2 - rows / 2,
+        };
+        let found: usize = data_lines
+            .as_slice()
+            .iter()
+            .map(|(_, values)| values.len())
+            .sum();
+        if found < expected {
+            let line = data_lines
+                .as_slice()
+                .last()
+                .map_or(size_line, |(line, _)| *line);
+            return Err(parse_error(line, 1, "fewer entries than declared"));
+        }
+    }
+    let mut data = vec![vec![T::default(); cols]; rows];
+
+    if coordinate {
+        let mut last_line = size_line;
+        let entries: usize = parse_token(size_line, size[2])?;
+        let values = if field == "pattern" { 2 } else { 3 };
+        let mut count = 0;
+        for (line, entry) in data_lines {
+            last_line = line;
+            if count == entries {
+                return Err(parse_error(line, 1, "more entries than declared"));
+            }
+            if entry.len() != values {
+                return Err(parse_error(
+                    line,
+                    1,
+                    format!("expected {} values, found {}", values, entry.len()),
+                ));
+            }
+            let row: usize = parse_token(line, entry[0])?;
+            let col: usize = parse_token(line, entry[1])?;
+            if row == 0 || row > rows {
+                return Err(parse_error(line, entry[0].0, "row index out of bounds"));
+            }
+            if col == 0 || col > cols {
+                return Err(parse_error(line, entry[1].0, "column index out of bounds"));
+            }
+            if symmetry == Symmetry::SkewSymmetric && row == col {
+                return Err(parse_error(
+                    line,
+                    entry[0].0,
+                    "diagonal entry in skew-symmetric matrix",
+                ));
+            }
+            let value = if values == 3 {
+                parse_token(line, entry[2])?
+            } else {
+                T::one()
+            };
+            data[row - 1][col - 1] = value;
+            match symmetry {
+                Symmetry::General => {}
+                Symmetry::Symmetric => data[col - 1][row - 1] = value,
+                Symmetry::SkewSymmetric => data[col - 1][row - 1] = -value,
+            }
+            count += 1;
+        }
+        if count < entries {
+            return Err(parse_error(
+                last_line,
+                1,
+                format!("expected {} entries, found {}", entries, count),
+            ));
+        }
+    } else {
+        // array format stores columns one after another, symmetric variants only the lower triangle
+        let mut positions = (0..cols).flat_map(|col| {
+            let first_row = match symmetry {
+                Symmetry::General => 0,
+                Symmetry::Symmetric => col,
+                Symmetry::SkewSymmetric => col + 1,
+            };
+            (first_row..rows).map(move |row| (row, col))
+        });
+        for (line, values) in data_lines {
+            for value in values {
+                let (row, col) = positions
+                    .next()
+                    .ok_or_else(|| parse_error(line, value.0, "more entries than declared"))?;
+                let value: T = parse_token(line, value)?;
+                data[row][col] = value;
+                match symmetry {
+                    Symmetry::General => {}
+                    Symmetry::Symmetric => data[col][row] = value,
+                    Symmetry::SkewSymmetric => data[col][row] = -value,
+                }
+            }
+        }
+    }
+
+    if rows == 0 {
+        data.clear();
+    }
+    Ok(Matrix::from_rows(data)?)
+}
+
+/// Writes matrix as Matrix Market `.mtx` text with `general` symmetry
+/// ```
+/// use datas::io::{write_matrix_market, MarketFormat};
+/// use datas::matrix::Matrix;
+/// let matrix = Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap();
+/// let mut out = Vec::new();
+/// write_matrix_market(&matrix, &mut out, MarketFormat::Array).unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "%%MatrixMarket matrix array integer general\n2 2\n1\n3\n2\n4\n"
+/// );
+/// ```
+pub fn write_matrix_market<T: Element, W: Write>(
+    matrix: &Matrix<T>,
+    mut writer: W,
+    format: MarketFormat,
+) -> Result<(), IoError> {
+    let data = matrix.data();
+    let (rows, cols) = (matrix.rows(), matrix.cols());
+    match format {
+        MarketFormat::Array => {
+            writeln!(
+                writer,
+                "%%MatrixMarket matrix array {} general",
+                T::MARKET_FIELD
+            )?;
+            writeln!(writer, "{} {}", rows, cols)?;
+            for col in 0..cols as usize {
+                for row in data {
+                    writeln!(writer, "{}", row[col])?;
+                }
+            }
+        }
+        MarketFormat::Coordinate => {
+            let mut entries = Vec::new();
+            for col in 0..cols as usize {
+                for (row, values) in data.iter().enumerate() {
+                    if values[col] != T::default() {
+                        entries.push((row + 1, col + 1, values[col]));
+                    }
+                }
+            }
+            writeln!(
+                writer,
+                "%%MatrixMarket matrix coordinate {} general",
+                T::MARKET_FIELD
+            )?;
+            writeln!(writer, "{} {} {}", rows, cols, entries.len())?;
+            for (row, col, value) in entries {
+                writeln!(writer, "{} {} {}", row, col, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Options of delimited text files
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CsvOptions {
+    /// Character separating fields in a line
+    pub delimiter: char,
+    /// Whether first line holds column names and should be skipped when reading
+    pub has_header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            has_header: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Tab separated values without header
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: '\t',
+            has_header: false,
+        }
+    }
+
+    /// Returns copy of options with given header setting
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+}
+
+/// Splits line on delimiter, yielding trimmed and unquoted fields with their 1 based columns
+fn fields(line: &str, delimiter: char) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut column = 1;
+    for field in line.split(delimiter) {
+        let leading = field.chars().take_while(|c| c.is_whitespace()).count();
+        let trimmed = field.trim();
+        let unquoted = trimmed
+            .strip_prefix('"')
+            .and_then(|f| f.strip_suffix('"'))
+            .unwrap_or(trimmed);
+        result.push((column + leading, unquoted));
+        column += field.chars().count() + 1;
+    }
+    result
+}
+
+/// Reads matrix from delimited text, blank lines are skipped
+/// ```
+/// use datas::io::{read_csv, CsvOptions};
+/// use datas::matrix::Matrix;
+/// let text = "a,b\n1,2\n3,4\n";
+/// let matrix: Matrix<f64> =
+///     read_csv(text.as_bytes(), &CsvOptions::default().with_header(true)).unwrap();
+/// assert_eq!(matrix, Matrix::<f64>::new(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap());
+/// ```
+pub fn read_csv<T: Element, R: BufRead>(
+    reader: R,
+    options: &CsvOptions,
+) -> Result<Matrix<T>, IoError> {
+    let mut data: Vec<Vec<T>> = Vec::new();
+    let mut cols = None;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        if (index == 0 && options.has_header) || line.trim().is_empty() {
+            continue;
+        }
+        let fields = fields(&line, options.delimiter);
+        let expected = *cols.get_or_insert(fields.len());
+        if fields.len() != expected {
+            let column = fields
+                .get(expected)
+                .map(|&(column, _)| column)
+                .unwrap_or(line.chars().count() + 1);
+            return Err(parse_error(
+                line_number,
+                column,
+                format!("expected {} fields, found {}", expected, fields.len()),
+            ));
+        }
+        let row = fields
+            .into_iter()
+            .map(|field| parse_token(line_number, field))
+            .collect::<Result<Vec<T>, IoError>>()?;
+        data.push(row);
+    }
+    Ok(Matrix::from_rows(data)?)
+}
+
+/// Writes matrix as delimited text, optionally preceded by a header line
+/// ```
+/// use datas::io::{write_csv, CsvOptions};
+/// use datas::matrix::Matrix;
+/// let matrix = Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap();
+/// let mut out = Vec::new();
+/// write_csv(&matrix, &mut out, &CsvOptions::tsv(), Some(&["a", "b"])).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "a\tb\n1\t2\n3\t4\n");
+/// ```
+pub fn write_csv<T: Element, W: Write>(
+    matrix: &Matrix<T>,
+    mut writer: W,
+    options: &CsvOptions,
+    header: Option<&[&str]>,
+) -> Result<(), IoError> {
+    let delimiter = options.delimiter.to_string();
+    if let Some(header) = header {
+        if header.len() as u64 != matrix.cols() {
+            return Err(IoError::Format(format!(
+                "header has {} names but matrix has {} columns",
+                header.len(),
+                matrix.cols()
+            )));
+        }
+        writeln!(writer, "{}", header.join(&delimiter))?;
+    }
+    for row in matrix.data() {
+        let values: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+        writeln!(writer, "{}", values.join(&delimiter))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_position<T>(result: Result<T, IoError>) -> (usize, usize) {
+        match result {
+            Err(IoError::Parse { line, column, .. }) => (line, column),
+            _ => panic!("expected parse error"),
+        }
+    }
+
+    #[test]
+    fn matrix_market_roundtrip() {
+        let matrix = Matrix::<f64>::new(vec![vec![1.5, 0.0, 2.0], vec![0.0, -3.25, 0.0]]).unwrap();
+        for format in [MarketFormat::Array, MarketFormat::Coordinate] {
+            let mut out = Vec::new();
+            write_matrix_market(&matrix, &mut out, format).unwrap();
+            let read: Matrix<f64> = read_matrix_market(out.as_slice()).unwrap();
+            assert_eq!(read, matrix);
+        }
+    }
+
+    #[test]
+    fn matrix_market_symmetric() {
+        let text =
+            "%%MatrixMarket matrix coordinate integer symmetric\n% comment\n2 2 2\n1 1 4\n2 1 7\n";
+        let matrix: Matrix<i64> = read_matrix_market(text.as_bytes()).unwrap();
+        assert_eq!(
+            matrix,
+            Matrix::<i64>::new(vec![vec![4, 7], vec![7, 0]]).unwrap()
+        );
+
+        let text = "%%MatrixMarket matrix array real skew-symmetric\n2 2\n3\n";
+        let matrix: Matrix<f64> = read_matrix_market(text.as_bytes()).unwrap();
+        assert_eq!(
+            matrix,
+            Matrix::<f64>::new(vec![vec![0.0, -3.0], vec![3.0, 0.0]]).unwrap()
+        );
+    }
+
+    #[test]
+    fn matrix_market_pattern() {
+        let text = "%%MatrixMarket matrix coordinate pattern general\n2 2 1\n2 1\n";
+        let matrix: Matrix<i64> = read_matrix_market(text.as_bytes()).unwrap();
+        assert_eq!(
+            matrix,
+            Matrix::<i64>::new(vec![vec![0, 0], vec![1, 0]]).unwrap()
+        );
+    }
+
+    #[test]
+    fn matrix_market_errors() {
+        let text = "%%MatrixMarket matrix coordinate real general\n2 2 1\n1 1 1.5\n";
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+            (1, 34)
+        );
+
+        let text = "%%MatrixMarket matrix coordinate integer general\n2 2 2\n1 1 4\n1 3 4\n";
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+            (4, 3)
+        );
+
+        let text = "%%MatrixMarket matrix array integer general\n1 2\n1\n  x\n";
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+            (4, 3)
+        );
+
+        let text = "%%MatrixMarket matrix array integer general\n2 2\n1 2 3\n";
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+            (3, 1)
+        );
+
+        let text = "%%MatrixMarket matrix coordinate integer skew-symmetric\n2 2 1\n2 2 4\n";
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+            (3, 1)
+        );
+
+        // declared size is checked before any allocation
+        let text = "%%MatrixMarket matrix array integer general\n4611686018427387904 4\n1\n";
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+            (2, 1)
+        );
+        let text = "%%MatrixMarket matrix array integer general\n1000000 1000000\n1\n";
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+            (3, 1)
+        );
+        let text = "%%MatrixMarket matrix coordinate integer general\n1000000 1000000 1\n1 1 1\n";
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+            (2, 1)
+        );
+        // a zero dimension leaves no elements, the other one is still bounded
+        for text in [
+            "%%MatrixMarket matrix array integer general\n1000000000000 0\n",
+            "%%MatrixMarket matrix array integer general\n0 1000000000000\n",
+            "%%MatrixMarket matrix coordinate integer general\n1000000000000 0 0\n",
+        ] {
+            assert_eq!(
+                parse_position(read_matrix_market::<i64, _>(text.as_bytes())),
+                (2, 1)
+            );
+        }
+
+        assert_eq!(
+            parse_position(read_matrix_market::<i64, _>("1 1\n".as_bytes())),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn csv_roundtrip() {
+        let matrix = Matrix::<i64>::new(vec![vec![1, -2], vec![30, 4]]).unwrap();
+        let options = CsvOptions::default().with_header(true);
+        let mut out = Vec::new();
+        write_csv(&matrix, &mut out, &options, Some(&["x", "y"])).unwrap();
+        let read: Matrix<i64> = read_csv(out.as_slice(), &options).unwrap();
+        assert_eq!(read, matrix);
+    }
+
+    #[test]
+    fn csv_quoted_and_tsv() {
+        let text = "\"1\"\t 2\n\n3\t4\n";
+        let matrix: Matrix<i64> = read_csv(text.as_bytes(), &CsvOptions::tsv()).unwrap();
+        assert_eq!(
+            matrix,
+            Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap()
+        );
+    }
+
+    #[test]
+    fn csv_errors() {
+        let text = "1,2\n3,4,5\n";
+        let result = read_csv::<i64, _>(text.as_bytes(), &CsvOptions::default());
+        assert_eq!(parse_position(result), (2, 5));
+
+        let text = "1,2\n3, 4x\n";
+        let result = read_csv::<i64, _>(text.as_bytes(), &CsvOptions::default());
+        assert_eq!(parse_position(result), (2, 4));
+
+        let matrix = Matrix::<i64>::new(vec![vec![1, 2]]).unwrap();
+        let result = write_csv(&matrix, Vec::new(), &CsvOptions::default(), Some(&["a"]));
+        assert!(matches!(result, Err(IoError::Format(_))));
+    }
+}
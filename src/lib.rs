@@ -1,8 +1,190 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::hash::Hash;
 
+pub mod basis;
+pub mod distance;
+pub mod error;
+pub mod fixed;
+pub mod format;
+pub mod geometry;
+pub mod hnsw;
+pub mod io;
 pub mod matrix;
+mod means;
+mod moments;
+pub mod neighbors;
+pub mod norm;
+pub mod npy;
+pub mod online;
+pub mod quantile;
+mod robust;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod simd;
+pub mod sketch;
+pub mod sparse;
 pub mod vector;
+mod weighted;
+
+pub use error::DatasError;
+pub use weighted::WeightKind;
+
+/// This enum represents invalid input of the statistical functions.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StatsError {
+    /// Statistic is undefined for no data
+    Empty,
+    /// `weights` has a different length than `data`
+    LengthMismatch { data: u64, weights: u64 },
+    /// Value at `index` is NaN
+    ContainsNaN { index: u64 },
+    /// Weight at `index` is NaN
+    NaNWeight { index: u64 },
+    /// Weights sum to zero, so the weighted statistic is undefined
+    ZeroWeightSum,
+    /// Statistic needs at least `required` values but got `actual`
+    InsufficientData { required: u64, actual: u64 },
+    /// Quantile was requested at `probability` outside of `[0, 1]`
+    InvalidProbability { probability: f64 },
+    /// Trimmed or winsorized statistic was requested with `proportion` outside of `[0, 0.5)`
+    InvalidProportion { proportion: f64 },
+    /// Tuning constant of an M-estimator is not positive and finite
+    InvalidTuningConstant { constant: f64 },
+    /// Value at `index` is negative where only non-negative values have a meaning
+    NegativeValue { index: u64 },
+    /// Weight at `index` is negative
+    NegativeWeight { index: u64 },
+    /// Merged summaries together stand for more than `u64::MAX` values
+    CountOverflow,
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatsError::Empty => f.write_str("statistic of empty data"),
+            StatsError::LengthMismatch { data, weights } => {
+                write!(f, "{} weights given for {} values", weights, data)
+            }
+            StatsError::ContainsNaN { index } => write!(f, "value at index {} is NaN", index),
+            StatsError::NaNWeight { index } => write!(f, "weight at index {} is NaN", index),
+            StatsError::ZeroWeightSum => f.write_str("weights sum to zero"),
+            StatsError::InsufficientData { required, actual } => write!(
+                f,
+                "statistic needs at least {} values, found {}",
+                required, actual
+            ),
+            StatsError::InvalidProbability { probability } => {
+                write!(f, "probability {} is outside of [0, 1]", probability)
+            }
+            StatsError::InvalidProportion { proportion } => {
+                write!(f, "proportion {} is outside of [0, 0.5)", proportion)
+            }
+            StatsError::InvalidTuningConstant { constant } => {
+                write!(f, "tuning constant {} is not positive", constant)
+            }
+            StatsError::NegativeValue { index } => {
+                write!(f, "value at index {} is negative", index)
+            }
+            StatsError::NegativeWeight { index } => {
+                write!(f, "weight at index {} is negative", index)
+            }
+            StatsError::CountOverflow => f.write_str("merged count overflows u64"),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+/// Fails with `Empty` for no data
+fn check_empty<T>(data: &[T]) -> Result<(), StatsError> {
+    if data.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    Ok(())
+}
+
+/// Fails with `Empty` for no data and with `ContainsNaN` at the first NaN
+fn check_f(data: &[f64]) -> Result<(), StatsError> {
+    check_empty(data)?;
+    match data.iter().position(|x| x.is_nan()) {
+        Some(index) => Err(StatsError::ContainsNaN {
+            index: index as u64,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Fails with `NaNWeight` at the first NaN weight
+fn check_weights_f(weights: &[f64]) -> Result<(), StatsError> {
+    match weights.iter().position(|w| w.is_nan()) {
+        Some(index) => Err(StatsError::NaNWeight {
+            index: index as u64,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Fails with `InsufficientData` unless there are at least `required` values
+fn check_len<T>(data: &[T], required: u64) -> Result<(), StatsError> {
+    if (data.len() as u64) < required {
+        return Err(StatsError::InsufficientData {
+            required,
+            actual: data.len() as u64,
+        });
+    }
+    Ok(())
+}
+
+/// Fails with `LengthMismatch` unless every value has a weight
+fn check_weights<T, W>(data: &[T], weights: &[W]) -> Result<(), StatsError> {
+    if data.len() != weights.len() {
+        return Err(StatsError::LengthMismatch {
+            data: data.len() as u64,
+            weights: weights.len() as u64,
+        });
+    }
+    Ok(())
+}
+
+/// Checks of `try_weighted_average_f` that also fail with `NegativeWeight` at the first negative
+/// weight
+fn check_weighted(data: &[f64], weights: &[f64]) -> Result<(), StatsError> {
+    check_weights(data, weights)?;
+    check_empty(data)?;
+    check_weights_f(weights)?;
+    check_f(data)?;
+    if let Some(index) = weights.iter().position(|&w| w < 0.0) {
+        return Err(StatsError::NegativeWeight {
+            index: index as u64,
+        });
+    }
+    if weights.iter().sum::<f64>() == 0.0 {
+        return Err(StatsError::ZeroWeightSum);
+    }
+    Ok(())
+}
+
+/// Integers as `f64` for the `_i` statistics that share the implementation of the `_f` ones
+fn to_f(data: &[i64]) -> Vec<f64> {
+    data.iter().map(|&x| x as f64).collect()
+}
+
+/// Sum of integers in `i128`, which cannot overflow for fewer than 2^64 values
+fn sum_i(data: &[i64]) -> i128 {
+    data.iter().map(|&x| x as i128).sum()
+}
+
+/// `numerator / denominator` without truncation, the integer part is divided exactly so only the
+/// fractional part is rounded. Division by zero follows `f64` rules
+fn ratio(numerator: i128, denominator: i128) -> f64 {
+    if denominator == 0 {
+        return numerator as f64 / 0.0;
+    }
+    let (quotient, remainder) = (numerator / denominator, numerator % denominator);
+    quotient as f64 + remainder as f64 / denominator as f64
+}
 
 pub struct Mean;
 
@@ -15,12 +197,14 @@ impl Mean {
         sum / (data.len() as f64)
     }
 
+    /// Mean of integers, summed in `i128` so it neither overflows nor truncates
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::mean_i(&[1, 2]), 1.5);
+    /// assert_eq!(Mean::mean_i(&[i64::MAX, i64::MAX]), i64::MAX as f64);
+    /// ```
     pub fn mean_i(data: &[i64]) -> f64 {
-        let mut sum = 0;
-        for x in data {
-            sum += x;
-        }
-        (sum / (data.len() as i64)) as f64
+        ratio(sum_i(data), data.len() as i128)
     }
 
     pub fn weighted_average_f(data: &[f64], weights: &[f64]) -> f64 {
@@ -36,17 +220,33 @@ impl Mean {
         sum / sum_weights
     }
 
+    /// Weighted average of integers, products and sums are accumulated in `i128`. Products are
+    /// below 2^126, so only a sum of several extreme ones overflows, then they are summed in `f64`
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::weighted_average_i(&[1, 2], &[3, 1]), 1.25);
+    /// assert_eq!(Mean::weighted_average_i(&[i64::MIN; 2], &[i64::MIN; 2]), i64::MIN as f64);
+    /// ```
     pub fn weighted_average_i(data: &[i64], weights: &[i64]) -> f64 {
-        let mut sum = 0;
-        let mut sum_weights = 0;
+        let mut sum = Some(0i128);
         if data.len() != weights.len() {
             panic!("data and weights must have the same length");
         }
-        for (x, w) in data.iter().zip(weights) {
-            sum += x * w;
-            sum_weights += w;
+        for (&x, &w) in data.iter().zip(weights) {
+            sum = sum.and_then(|sum| sum.checked_add(x as i128 * w as i128));
+        }
+        let sum_weights = sum_i(weights);
+        match sum {
+            Some(sum) => ratio(sum, sum_weights),
+            None => {
+                let sum: f64 = data
+                    .iter()
+                    .zip(weights)
+                    .map(|(&x, &w)| x as f64 * w as f64)
+                    .sum();
+                sum / sum_weights as f64
+            }
         }
-        (sum / sum_weights) as f64
     }
 
     pub fn median_f(data: &[f64]) -> f64 {
@@ -65,7 +265,7 @@ impl Mean {
         data.sort();
         let mid = data.len() / 2;
         if data.len() % 2 == 0 {
-            ((data[mid] + data[mid - 1]) / 2) as f64
+            ratio(data[mid] as i128 + data[mid - 1] as i128, 2)
         } else {
             data[mid] as f64
         }
@@ -96,21 +296,65 @@ impl Mean {
     }
 
     pub fn variance_f(data: &[f64]) -> f64 {
+        Mean::variance_ddof_f(data, 0)
+    }
+
+    /// Variance with `ddof` delta degrees of freedom, squared deviations are divided by `n - ddof`
+    /// as in NumPy. NaN unless there are more than `ddof` values
+    /// ```
+    /// use datas::Mean;
+    /// let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+    /// assert_eq!(Mean::variance_ddof_f(&data, 0), 4.0);
+    /// assert_eq!(Mean::variance_ddof_f(&data, 1), 32.0 / 7.0);
+    /// ```
+    pub fn variance_ddof_f(data: &[f64], ddof: u64) -> f64 {
+        let len = data.len() as u64;
+        if len <= ddof {
+            return f64::NAN;
+        }
         let mean = Mean::mean_f(data);
         let mut sum = 0.0;
         for x in data {
             sum += (x - mean).powi(2);
         }
-        sum / (data.len() as f64)
+        sum / ((len - ddof) as f64)
     }
 
+    /// Population variance of integers
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::variance_i(&[1, 2]), 0.25);
+    /// ```
     pub fn variance_i(data: &[i64]) -> f64 {
-        let mean = Mean::mean_i(data);
-        let mut sum = 0;
-        for x in data {
-            sum += (x - mean as i64).pow(2);
+        Mean::variance_ddof_i(data, 0)
+    }
+
+    /// Variance of integers with `ddof` delta degrees of freedom. Deviations are taken from the
+    /// exact integer part of the mean, so they are small integers even when the values themselves
+    /// are not representable in `f64`
+    pub fn variance_ddof_i(data: &[i64], ddof: u64) -> f64 {
+        let len = data.len() as i128;
+        if len <= ddof as i128 {
+            return f64::NAN;
+        }
+        let sum = sum_i(data);
+        let (quotient, remainder) = (sum / len, sum % len);
+        let fraction = remainder as f64 / len as f64;
+        let mut squares = 0.0;
+        for &x in data {
+            squares += ((x as i128 - quotient) as f64 - fraction).powi(2);
         }
-        (sum / (data.len() as i64)) as f64
+        squares / (len - ddof as i128) as f64
+    }
+
+    /// Unbiased sample variance, divides by `n - 1` like `var` in R and pandas
+    pub fn sample_variance_f(data: &[f64]) -> f64 {
+        Mean::variance_ddof_f(data, 1)
+    }
+
+    /// Unbiased sample variance of integers, divides by `n - 1`
+    pub fn sample_variance_i(data: &[i64]) -> f64 {
+        Mean::variance_ddof_i(data, 1)
     }
 
     pub fn standard_deviation_f(data: &[f64]) -> f64 {
@@ -120,6 +364,207 @@ impl Mean {
     pub fn standard_deviation_i(data: &[i64]) -> f64 {
         Mean::variance_i(data).sqrt()
     }
+
+    /// Square root of `variance_ddof_f`
+    pub fn standard_deviation_ddof_f(data: &[f64], ddof: u64) -> f64 {
+        Mean::variance_ddof_f(data, ddof).sqrt()
+    }
+
+    /// Square root of `variance_ddof_i`
+    pub fn standard_deviation_ddof_i(data: &[i64], ddof: u64) -> f64 {
+        Mean::variance_ddof_i(data, ddof).sqrt()
+    }
+
+    /// Sample standard deviation, `sd` in R
+    pub fn sample_standard_deviation_f(data: &[f64]) -> f64 {
+        Mean::sample_variance_f(data).sqrt()
+    }
+
+    pub fn sample_standard_deviation_i(data: &[i64]) -> f64 {
+        Mean::sample_variance_i(data).sqrt()
+    }
+
+    /// Standard error of the mean, sample standard deviation over `sqrt(n)` like `scipy.stats.sem`
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::standard_error_f(&[1.0, 2.0, 3.0, 4.0]), (5.0f64 / 12.0).sqrt());
+    /// ```
+    pub fn standard_error_f(data: &[f64]) -> f64 {
+        Mean::sample_standard_deviation_f(data) / (data.len() as f64).sqrt()
+    }
+
+    pub fn standard_error_i(data: &[i64]) -> f64 {
+        Mean::sample_standard_deviation_i(data) / (data.len() as f64).sqrt()
+    }
+
+    /// Coefficient of variation, sample standard deviation relative to the mean.
+    /// Infinite or NaN when the mean is zero
+    pub fn coefficient_of_variation_f(data: &[f64]) -> f64 {
+        Mean::sample_standard_deviation_f(data) / Mean::mean_f(data)
+    }
+
+    pub fn coefficient_of_variation_i(data: &[i64]) -> f64 {
+        Mean::sample_standard_deviation_i(data) / Mean::mean_i(data)
+    }
+
+    /// `mean_f` that fails on empty data or NaN instead of returning NaN
+    /// ```
+    /// use datas::{Mean, StatsError};
+    /// assert_eq!(Mean::try_mean_f(&[1.0, 2.0]), Ok(1.5));
+    /// assert_eq!(Mean::try_mean_f(&[]), Err(StatsError::Empty));
+    /// assert_eq!(Mean::try_mean_f(&[1.0, f64::NAN]), Err(StatsError::ContainsNaN { index: 1 }));
+    /// ```
+    pub fn try_mean_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::mean_f(data))
+    }
+
+    /// `mean_i` that fails on empty data instead of returning NaN
+    pub fn try_mean_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::mean_i(data))
+    }
+
+    /// `weighted_average_f` that fails on mismatched lengths, empty data, NaN or weights summing to zero.
+    /// A NaN value fails with `ContainsNaN`, a NaN weight with `NaNWeight`
+    pub fn try_weighted_average_f(data: &[f64], weights: &[f64]) -> Result<f64, StatsError> {
+        check_weights(data, weights)?;
+        check_f(data)?;
+        check_weights_f(weights)?;
+        if weights.iter().sum::<f64>() == 0.0 {
+            return Err(StatsError::ZeroWeightSum);
+        }
+        Ok(Mean::weighted_average_f(data, weights))
+    }
+
+    /// `weighted_average_i` that fails on mismatched lengths, empty data or weights summing to zero
+    /// ```
+    /// use datas::{Mean, StatsError};
+    /// assert_eq!(
+    ///     Mean::try_weighted_average_i(&[1, 2], &[1]),
+    ///     Err(StatsError::LengthMismatch { data: 2, weights: 1 })
+    /// );
+    /// assert_eq!(Mean::try_weighted_average_i(&[1, 2], &[1, -1]), Err(StatsError::ZeroWeightSum));
+    /// ```
+    pub fn try_weighted_average_i(data: &[i64], weights: &[i64]) -> Result<f64, StatsError> {
+        check_weights(data, weights)?;
+        check_empty(data)?;
+        if sum_i(weights) == 0 {
+            return Err(StatsError::ZeroWeightSum);
+        }
+        Ok(Mean::weighted_average_i(data, weights))
+    }
+
+    /// `median_f` that fails on empty data or NaN instead of panicking
+    pub fn try_median_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::median_f(data))
+    }
+
+    /// `median_i` that fails on empty data instead of panicking
+    pub fn try_median_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::median_i(data))
+    }
+
+    /// `mode` that fails on empty data instead of returning `None`
+    pub fn try_mode<T: Eq + Ord + Clone + Hash>(data: &[T]) -> Result<T, StatsError> {
+        Mean::mode(data).ok_or(StatsError::Empty)
+    }
+
+    /// `mode_f64` that fails on empty data or NaN
+    pub fn try_mode_f64(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Mean::mode_f64(data).ok_or(StatsError::Empty)
+    }
+
+    /// `variance_f` that fails on empty data or NaN instead of returning NaN
+    pub fn try_variance_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::variance_f(data))
+    }
+
+    /// `variance_i` that fails on empty data instead of returning NaN
+    pub fn try_variance_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::variance_i(data))
+    }
+
+    /// `standard_deviation_f` that fails on empty data or NaN instead of returning NaN
+    pub fn try_standard_deviation_f(data: &[f64]) -> Result<f64, StatsError> {
+        Mean::try_variance_f(data).map(f64::sqrt)
+    }
+
+    /// `standard_deviation_i` that fails on empty data instead of returning NaN
+    pub fn try_standard_deviation_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_variance_i(data).map(f64::sqrt)
+    }
+
+    /// `variance_ddof_f` that fails on NaN or when there are not more than `ddof` values
+    /// ```
+    /// use datas::{Mean, StatsError};
+    /// assert_eq!(
+    ///     Mean::try_variance_ddof_f(&[1.0], 1),
+    ///     Err(StatsError::InsufficientData { required: 2, actual: 1 })
+    /// );
+    /// ```
+    pub fn try_variance_ddof_f(data: &[f64], ddof: u64) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_len(data, ddof.saturating_add(1))?;
+        Ok(Mean::variance_ddof_f(data, ddof))
+    }
+
+    /// `variance_ddof_i` that fails when there are not more than `ddof` values
+    pub fn try_variance_ddof_i(data: &[i64], ddof: u64) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        check_len(data, ddof.saturating_add(1))?;
+        Ok(Mean::variance_ddof_i(data, ddof))
+    }
+
+    pub fn try_sample_variance_f(data: &[f64]) -> Result<f64, StatsError> {
+        Mean::try_variance_ddof_f(data, 1)
+    }
+
+    pub fn try_sample_variance_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_variance_ddof_i(data, 1)
+    }
+
+    pub fn try_standard_deviation_ddof_f(data: &[f64], ddof: u64) -> Result<f64, StatsError> {
+        Mean::try_variance_ddof_f(data, ddof).map(f64::sqrt)
+    }
+
+    pub fn try_standard_deviation_ddof_i(data: &[i64], ddof: u64) -> Result<f64, StatsError> {
+        Mean::try_variance_ddof_i(data, ddof).map(f64::sqrt)
+    }
+
+    pub fn try_sample_standard_deviation_f(data: &[f64]) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_f(data).map(f64::sqrt)
+    }
+
+    pub fn try_sample_standard_deviation_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_i(data).map(f64::sqrt)
+    }
+
+    pub fn try_standard_error_f(data: &[f64]) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_f(data)?;
+        Ok(Mean::standard_error_f(data))
+    }
+
+    pub fn try_standard_error_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_i(data)?;
+        Ok(Mean::standard_error_i(data))
+    }
+
+    /// `coefficient_of_variation_f` that fails on invalid data, a zero mean still gives an infinite or NaN result
+    pub fn try_coefficient_of_variation_f(data: &[f64]) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_f(data)?;
+        Ok(Mean::coefficient_of_variation_f(data))
+    }
+
+    pub fn try_coefficient_of_variation_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_i(data)?;
+        Ok(Mean::coefficient_of_variation_i(data))
+    }
 }
 
 pub struct CalculusError;
@@ -135,10 +580,36 @@ impl CalculusError {
         (x - y).abs() / x.abs()
     }
     pub fn relative_error_i(x: i64, y: i64) -> f64 {
-        ((x - y).abs() / x.abs()) as f64
+        ratio((x as i128 - y as i128).abs(), (x as i128).abs())
     }
 }
 
+/// Relative comparison of `actual` with `expected` for the tests, absolute below 1
+#[cfg(test)]
+fn close(actual: f64, expected: f64) -> bool {
+    close_within(actual, expected, 1e-12)
+}
+
+/// `close` with a `tolerance` other than `1e-12`
+#[cfg(test)]
+fn close_within(actual: f64, expected: f64, tolerance: f64) -> bool {
+    (actual - expected).abs() <= tolerance * expected.abs().max(1.0)
+}
+
+/// Deterministic values in `[0, 1)` of a linear congruential generator started at `seed`
+#[cfg(test)]
+fn uniform(count: usize, seed: u64) -> Vec<f64> {
+    let mut state = seed;
+    (0..count)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,6 +692,185 @@ mod tests {
     }
 
     #[test]
+    fn try_functions_reject_bad_input() {
+        let empty_f: [f64; 0] = [];
+        let empty_i: [i64; 0] = [];
+        assert_eq!(Mean::try_mean_f(&empty_f), Err(StatsError::Empty));
+        assert_eq!(Mean::try_mean_i(&empty_i), Err(StatsError::Empty));
+        assert_eq!(Mean::try_median_f(&empty_f), Err(StatsError::Empty));
+        assert_eq!(Mean::try_median_i(&empty_i), Err(StatsError::Empty));
+        assert_eq!(Mean::try_mode(&empty_i), Err(StatsError::Empty));
+        assert_eq!(Mean::try_mode_f64(&empty_f), Err(StatsError::Empty));
+        assert_eq!(Mean::try_variance_f(&empty_f), Err(StatsError::Empty));
+        assert_eq!(Mean::try_variance_i(&empty_i), Err(StatsError::Empty));
+        assert_eq!(
+            Mean::try_standard_deviation_f(&empty_f),
+            Err(StatsError::Empty)
+        );
+        assert_eq!(
+            Mean::try_standard_deviation_i(&empty_i),
+            Err(StatsError::Empty)
+        );
+        assert_eq!(
+            Mean::try_weighted_average_i(&empty_i, &empty_i),
+            Err(StatsError::Empty)
+        );
+
+        let nan = [3.0, 1.0, f64::NAN];
+        let contains_nan = Err(StatsError::ContainsNaN { index: 2 });
+        assert_eq!(Mean::try_median_f(&nan), contains_nan);
+        assert_eq!(Mean::try_mode_f64(&nan), contains_nan);
+        assert_eq!(Mean::try_variance_f(&nan), contains_nan);
+        assert_eq!(
+            Mean::try_weighted_average_f(&[1.0, 2.0, 3.0], &nan),
+            Err(StatsError::NaNWeight { index: 2 })
+        );
+        assert_eq!(
+            Mean::try_weighted_average_f(&nan, &[1.0, 2.0, 3.0]),
+            contains_nan
+        );
+        // weights sum in i128, so this neither overflows nor counts as a zero sum
+        assert_eq!(
+            Mean::try_weighted_average_i(&[1, 1], &[i64::MAX, 1]),
+            Ok(1.0)
+        );
+
+        assert_eq!(
+            Mean::try_weighted_average_f(&[1.0, 2.0], &[1.0, 2.0, 3.0]),
+            Err(StatsError::LengthMismatch {
+                data: 2,
+                weights: 3
+            })
+        );
+        assert_eq!(
+            Mean::try_weighted_average_f(&[1.0, 2.0], &[0.0, 0.0]),
+            Err(StatsError::ZeroWeightSum)
+        );
+    }
+
+    #[test]
+    fn try_functions_match_infallible() {
+        let data_f = [4.0, 1.0, 2.0, 2.0];
+        let data_i = [5, 1, 3, 3, 2];
+        assert_eq!(Mean::try_mean_f(&data_f), Ok(Mean::mean_f(&data_f)));
+        assert_eq!(Mean::try_mean_i(&data_i), Ok(Mean::mean_i(&data_i)));
+        assert_eq!(Mean::try_median_f(&data_f), Ok(2.0));
+        assert_eq!(Mean::try_median_i(&data_i), Ok(3.0));
+        assert_eq!(Mean::try_mode(&data_i), Ok(3));
+        assert_eq!(Mean::try_mode_f64(&data_f), Ok(2.0));
+        assert_eq!(Mean::try_variance_f(&data_f), Ok(Mean::variance_f(&data_f)));
+        assert_eq!(
+            Mean::try_standard_deviation_i(&data_i),
+            Ok(Mean::standard_deviation_i(&data_i))
+        );
+        assert_eq!(
+            Mean::try_weighted_average_f(&data_f, &[1.0, 0.0, 0.0, 1.0]),
+            Ok(3.0)
+        );
+        assert_eq!(
+            Mean::try_weighted_average_i(&[2, 4], &[3, 1]),
+            Ok(Mean::weighted_average_i(&[2, 4], &[3, 1]))
+        );
+    }
+
+    #[test]
+    fn integer_statistics_do_not_truncate() {
+        assert_eq!(Mean::mean_i(&[1, 2]), 1.5);
+        assert_eq!(Mean::mean_i(&[-1, -2]), -1.5);
+        assert_eq!(Mean::median_i(&[1, 2, 3, 4]), 2.5);
+        assert_eq!(Mean::median_i(&[-3, 0]), -1.5);
+        assert_eq!(Mean::weighted_average_i(&[1, 2], &[1, 1]), 1.5);
+        assert_eq!(Mean::weighted_average_i(&[1, 2], &[2, 1]), 4.0 / 3.0);
+        assert_eq!(Mean::variance_i(&[1, 2]), 0.25);
+        assert_eq!(Mean::variance_i(&[1, 2, 4]), 14.0 / 9.0);
+        assert_eq!(Mean::standard_deviation_i(&[1, 2]), 0.5);
+        assert_eq!(CalculusError::relative_error_i(3, 4), 1.0 / 3.0);
+        assert_eq!(CalculusError::relative_error_i(-4, 2), 1.5);
+    }
+
+    #[test]
+    fn integer_statistics_do_not_overflow() {
+        let (max, min) = (i64::MAX, i64::MIN);
+        assert_eq!(Mean::mean_i(&[max, max, max]), max as f64);
+        assert_eq!(Mean::mean_i(&[max, min]), -0.5);
+        assert_eq!(Mean::median_i(&[max, max - 1]), max as f64 - 0.5);
+        assert_eq!(Mean::weighted_average_i(&[max, max], &[max, 1]), max as f64);
+        assert_eq!(
+            Mean::weighted_average_i(&[min, min], &[min, min]),
+            min as f64
+        );
+        assert_eq!(
+            Mean::weighted_average_i(&[min, min, max], &[min, min, 0]),
+            min as f64
+        );
+        // deviations of half of 2^64 - 1 from the mean
+        let half = (u64::MAX as f64) / 2.0;
+        assert_eq!(Mean::variance_i(&[max, min]), half * half);
+        // values beyond the precision of f64 keep their small spread
+        let large = [max - 2, max - 1, max];
+        assert_eq!(Mean::variance_i(&large), 2.0 / 3.0);
+        assert_eq!(
+            CalculusError::relative_error_i(min, max),
+            (u64::MAX as f64) / 2f64.powi(63)
+        );
+        assert!(Mean::mean_i(&[]).is_nan());
+        assert!(Mean::variance_i(&[]).is_nan());
+    }
+
+    #[test]
+    fn degrees_of_freedom() {
+        // reference values from numpy.var(ddof=...) and scipy.stats.sem
+        let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let integers = [2, 4, 4, 4, 5, 5, 7, 9];
+        assert_eq!(Mean::variance_ddof_f(&data, 0), Mean::variance_f(&data));
+        assert_eq!(Mean::sample_variance_f(&data), 32.0 / 7.0);
+        assert_eq!(Mean::sample_variance_i(&integers), 32.0 / 7.0);
+        assert_eq!(Mean::variance_ddof_i(&integers, 2), 32.0 / 6.0);
+        assert_eq!(
+            Mean::sample_standard_deviation_f(&data),
+            (32.0f64 / 7.0).sqrt()
+        );
+        assert_eq!(
+            Mean::standard_deviation_ddof_i(&integers, 1),
+            Mean::sample_standard_deviation_i(&integers)
+        );
+        let sem = (32.0f64 / 7.0).sqrt() / 8f64.sqrt();
+        assert!((Mean::standard_error_f(&data) - sem).abs() < 1e-15);
+        assert!((Mean::standard_error_i(&integers) - sem).abs() < 1e-15);
+        let cv = (32.0f64 / 7.0).sqrt() / 5.0;
+        assert!((Mean::coefficient_of_variation_f(&data) - cv).abs() < 1e-15);
+        assert!((Mean::coefficient_of_variation_i(&integers) - cv).abs() < 1e-15);
+
+        assert!(Mean::sample_variance_f(&[1.0]).is_nan());
+        assert!(Mean::variance_ddof_i(&[1, 2], 2).is_nan());
+        assert_eq!(
+            Mean::try_sample_variance_i(&[3]),
+            Err(StatsError::InsufficientData {
+                required: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(Mean::try_standard_error_f(&[]), Err(StatsError::Empty));
+        assert_eq!(
+            Mean::try_coefficient_of_variation_f(&[1.0, f64::NAN]),
+            Err(StatsError::ContainsNaN { index: 1 })
+        );
+        assert_eq!(
+            Mean::try_coefficient_of_variation_i(&[-1, 1]),
+            Ok(f64::INFINITY)
+        );
+        assert_eq!(Mean::try_variance_ddof_f(&data, 1), Ok(32.0 / 7.0));
+        let too_many = Err(StatsError::InsufficientData {
+            required: u64::MAX,
+            actual: 1,
+        });
+        assert_eq!(Mean::try_variance_ddof_f(&[1.0], u64::MAX), too_many);
+        assert_eq!(Mean::try_variance_ddof_i(&[1], u64::MAX), too_many);
+        assert!(Mean::variance_ddof_i(&[1], u64::MAX).is_nan());
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
     fn variance_i_test() {
         let data = [1, 2, 3, 4, 5];
         assert_eq!(Mean::variance_i(&data), 2.0);
@@ -1,20 +1,73 @@
+use std::fmt;
+
+use crate::format::{self, Cell, PrintOptions};
+use crate::vector::Vector;
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MatrixError {
-    InconsistentColumnSizes,
-    MultiplicationDimensionMismatch,
-    DimensionMismatch,
-    RowOutOfBound,
+    /// Row `row` has `actual` columns while the first row has `expected`
+    InconsistentColumnSizes {
+        row: u64,
+        expected: u64,
+        actual: u64,
+    },
+    /// Columns of the `left` matrix differ from rows of the `right` one, shapes are `(rows, cols)`
+    MultiplicationDimensionMismatch { left: (u64, u64), right: (u64, u64) },
+    /// Matrices of different shapes were combined, shapes are `(rows, cols)`
+    DimensionMismatch {
+        expected: (u64, u64),
+        actual: (u64, u64),
+    },
+    /// Row index `row` is not smaller than `rows`
+    RowOutOfBound { row: u64, rows: u64 },
+    /// Matrix has no inverse
+    Singular,
 }
 
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MatrixError::InconsistentColumnSizes {
+                row,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "row {} has {} columns, expected {}",
+                row, actual, expected
+            ),
+            MatrixError::MultiplicationDimensionMismatch { left, right } => write!(
+                f,
+                "cannot multiply {}x{} matrix by {}x{} matrix",
+                left.0, left.1, right.0, right.1
+            ),
+            MatrixError::DimensionMismatch { expected, actual } => write!(
+                f,
+                "expected {}x{} matrix, found {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            MatrixError::RowOutOfBound { row, rows } => {
+                write!(f, "row {} is out of bounds for {} rows", row, rows)
+            }
+            MatrixError::Singular => f.write_str("matrix is singular"),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Matrix<T> {
     data: Vec<Vec<T>>,
     rows: u64,
     cols: u64,
 }
 
-impl Matrix<i64> {
-    pub fn new(data: Vec<Vec<i64>>) -> Result<Self, MatrixError> {
+impl<T> Matrix<T> {
+    /// Builds matrix from rows, shared by the typed constructors
+    pub(crate) fn from_rows(data: Vec<Vec<T>>) -> Result<Self, MatrixError> {
         let rows = data.len() as u64;
         if rows == 0 {
             return Ok(Self {
@@ -24,16 +77,55 @@ impl Matrix<i64> {
             });
         }
         let cols = data[0].len() as u64;
-        if !data.iter().all(|row| row.len() as u64 == cols) {
-            return Err(MatrixError::InconsistentColumnSizes);
+        if let Some(row) = data.iter().position(|row| row.len() as u64 != cols) {
+            return Err(MatrixError::InconsistentColumnSizes {
+                row: row as u64,
+                expected: cols,
+                actual: data[row].len() as u64,
+            });
         }
 
         Ok(Self { data, rows, cols })
     }
 
+    /// Number of rows of the matrix
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Number of columns of the matrix
+    pub fn cols(&self) -> u64 {
+        self.cols
+    }
+
+    /// Returns reference to the element at given 0 based position
+    /// ```
+    /// use datas::matrix::Matrix;
+    /// let matrix = Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    /// assert_eq!(matrix.get(1, 0), Some(&3));
+    /// assert_eq!(matrix.get(2, 0), None);
+    /// ```
+    pub fn get(&self, row: u64, col: u64) -> Option<&T> {
+        self.data.get(row as usize)?.get(col as usize)
+    }
+
+    /// Rows of the matrix as stored
+    pub fn data(&self) -> &[Vec<T>] {
+        &self.data
+    }
+}
+
+impl Matrix<i64> {
+    pub fn new(data: Vec<Vec<i64>>) -> Result<Self, MatrixError> {
+        Self::from_rows(data)
+    }
+
     pub fn swap_row(&mut self, f_row: u64, s_row: u64) -> Result<(), MatrixError> {
-        if f_row >= self.rows || s_row >= self.rows {
-            return Err(MatrixError::RowOutOfBound);
+        if let Some(&row) = [f_row, s_row].iter().find(|&&row| row >= self.rows) {
+            return Err(MatrixError::RowOutOfBound {
+                row,
+                rows: self.rows,
+            });
         }
         let temp: Vec<i64> = self.data[f_row as usize].clone();
         self.data[f_row as usize] = self.data[s_row as usize].clone();
@@ -43,7 +135,10 @@ impl Matrix<i64> {
 
     pub fn add(&mut self, matrix: &Matrix<i64>) -> Result<(), MatrixError> {
         if self.cols != matrix.cols || self.rows != matrix.rows {
-            return Err(MatrixError::DimensionMismatch);
+            return Err(MatrixError::DimensionMismatch {
+                expected: (self.rows, self.cols),
+                actual: (matrix.rows, matrix.cols),
+            });
         }
 
         self.data
@@ -65,7 +160,10 @@ impl Matrix<i64> {
 
     pub fn matrix_multiplication(self, matrix: &Matrix<i64>) -> Result<Matrix<i64>, MatrixError> {
         if self.cols != matrix.rows {
-            return Err(MatrixError::MultiplicationDimensionMismatch);
+            return Err(MatrixError::MultiplicationDimensionMismatch {
+                left: (self.rows, self.cols),
+                right: (matrix.rows, matrix.cols),
+            });
         }
 
         let mut data = Vec::with_capacity(self.rows as usize);
@@ -83,10 +181,236 @@ impl Matrix<i64> {
 
             data.push(new_row);
         }
-        return Ok(match Matrix::<i64>::new(data) {
+        Ok(match Matrix::<i64>::new(data) {
             Ok(m) => m,
             Err(e) => panic!("{:?}", e),
-        });
+        })
+    }
+}
+
+impl Matrix<f64> {
+    pub fn new(data: Vec<Vec<f64>>) -> Result<Self, MatrixError> {
+        Self::from_rows(data)
+    }
+
+    /// Multiplies two matrices, the number of columns has to match the number of rows of `matrix`
+    pub fn matrix_multiplication_f(
+        &self,
+        matrix: &Matrix<f64>,
+    ) -> Result<Matrix<f64>, MatrixError> {
+        if self.cols != matrix.rows {
+            return Err(MatrixError::MultiplicationDimensionMismatch {
+                left: (self.rows, self.cols),
+                right: (matrix.rows, matrix.cols),
+            });
+        }
+        let data = self
+            .data
+            .iter()
+            .map(|row| {
+                (0..matrix.cols as usize)
+                    .map(|col| {
+                        row.iter()
+                            .zip(&matrix.data)
+                            .map(|(value, other)| value * other[col])
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+        Self::from_rows(data)
+    }
+
+    /// Multiplies the matrix by a column vector
+    /// ```
+    /// use datas::matrix::Matrix;
+    /// use datas::vector::Vector;
+    /// let matrix = Matrix::<f64>::new(vec![vec![0.0, -1.0], vec![1.0, 0.0]]).unwrap();
+    /// let rotated = matrix.vector_multiplication_f(&Vector::<f64>::new(vec![1.0, 2.0])).unwrap();
+    /// assert_eq!(rotated.components(), &[-2.0, 1.0]);
+    /// ```
+    pub fn vector_multiplication_f(
+        &self,
+        vector: &Vector<f64>,
+    ) -> Result<Vector<f64>, MatrixError> {
+        if self.cols != vector.dimensions() {
+            return Err(MatrixError::MultiplicationDimensionMismatch {
+                left: (self.rows, self.cols),
+                right: (vector.dimensions(), 1),
+            });
+        }
+        Ok(Vector::<f64>::new(
+            self.data
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(vector.components())
+                        .map(|(a, b)| a * b)
+                        .sum()
+                })
+                .collect(),
+        ))
+    }
+
+    /// Solves `self * x = rhs` for a square matrix with Gaussian elimination and partial pivoting,
+    /// `None` if the matrix is singular
+    pub(crate) fn solve(&self, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+        let n = rhs.len();
+        let mut a: Vec<Vec<f64>> = self.data.clone();
+        let scale = a
+            .iter()
+            .flatten()
+            .fold(0.0_f64, |max, value| max.max(value.abs()));
+        let tolerance = scale * n as f64 * f64::EPSILON;
+        for col in 0..n {
+            let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+            if a[pivot][col].abs() <= tolerance {
+                return None;
+            }
+            a.swap(col, pivot);
+            rhs.swap(col, pivot);
+            let (upper, lower) = a.split_at_mut(col + 1);
+            let pivot_row = &upper[col];
+            for (offset, row) in lower.iter_mut().enumerate() {
+                let factor = row[col] / pivot_row[col];
+                for (value, pivot_value) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *value -= factor * pivot_value;
+                }
+                rhs[col + 1 + offset] -= factor * rhs[col];
+            }
+        }
+        for col in (0..n).rev() {
+            let known: f64 = (col + 1..n).map(|k| a[col][k] * rhs[k]).sum();
+            rhs[col] = (rhs[col] - known) / a[col][col];
+        }
+        Some(rhs)
+    }
+}
+
+impl<T: fmt::Display> Matrix<T> {
+    /// Renders matrix NumPy style with aligned columns
+    /// ```
+    /// use datas::format::PrintOptions;
+    /// use datas::matrix::Matrix;
+    /// let matrix = Matrix::<i64>::new(vec![vec![1, 20], vec![300, 4]]).unwrap();
+    /// assert_eq!(
+    ///     matrix.to_string_with(&PrintOptions::default()),
+    ///     "[[  1  20]\n [300   4]]"
+    /// );
+    /// ```
+    pub fn to_string_with(&self, options: &PrintOptions) -> String {
+        if self.data.is_empty() {
+            return String::from("[]");
+        }
+        let grid = format::render_grid(&self.data, options);
+        let width = format::cell_width(grid.iter().flatten().flatten());
+        let lines: Vec<String> = grid
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let prefix = if i == 0 { "[" } else { " " };
+                match row {
+                    Some(cells) => format!("{}[{}]", prefix, format::join_cells(cells, width)),
+                    None => format!("{}...", prefix),
+                }
+            })
+            .collect();
+        format!("{}]", lines.join("\n"))
+    }
+
+    /// Renders matrix as LaTeX `bmatrix` environment
+    /// ```
+    /// use datas::format::PrintOptions;
+    /// use datas::matrix::Matrix;
+    /// let matrix = Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    /// assert_eq!(
+    ///     matrix.to_latex(&PrintOptions::default()),
+    ///     "\\begin{bmatrix}\n1 & 2 \\\\\n3 & 4\n\\end{bmatrix}"
+    /// );
+    /// ```
+    pub fn to_latex(&self, options: &PrintOptions) -> String {
+        let grid = format::render_grid(&self.data, options);
+        let layout: Vec<bool> = grid
+            .iter()
+            .flatten()
+            .next()
+            .map(|cells| cells.iter().map(|c| *c == Cell::Ellipsis).collect())
+            .unwrap_or_default();
+        let lines: Vec<String> = grid
+            .iter()
+            .map(|row| {
+                let cells: Vec<String> = match row {
+                    Some(cells) => cells
+                        .iter()
+                        .map(|cell| match cell {
+                            Cell::Value(s) => s.clone(),
+                            Cell::Ellipsis => String::from("\\cdots"),
+                        })
+                        .collect(),
+                    None => layout
+                        .iter()
+                        .map(|&elided| String::from(if elided { "\\ddots" } else { "\\vdots" }))
+                        .collect(),
+                };
+                cells.join(" & ")
+            })
+            .collect();
+        let mut body = lines.join(" \\\\\n");
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        format!("\\begin{{bmatrix}}\n{}\\end{{bmatrix}}", body)
+    }
+
+    /// Renders matrix as Markdown table with column indices in the header
+    /// ```
+    /// use datas::format::PrintOptions;
+    /// use datas::matrix::Matrix;
+    /// let matrix = Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    /// assert_eq!(
+    ///     matrix.to_markdown(&PrintOptions::default()),
+    ///     "| 0 | 1 |\n|---|---|\n| 1 | 2 |\n| 3 | 4 |"
+    /// );
+    /// ```
+    pub fn to_markdown(&self, options: &PrintOptions) -> String {
+        let elide = (self.rows * self.cols) as usize > options.threshold;
+        let columns = format::visible_indices(self.cols as usize, elide, options.edge_items);
+        let header: Vec<String> = columns
+            .iter()
+            .map(|index| match index {
+                Some(i) => i.to_string(),
+                None => String::from("..."),
+            })
+            .collect();
+        let mut lines = vec![
+            format!("| {} |", header.join(" | ")),
+            format!("|{}|", vec!["---"; header.len()].join("|")),
+        ];
+        for row in format::render_grid(&self.data, options) {
+            let cells: Vec<String> = match row {
+                Some(cells) => cells
+                    .into_iter()
+                    .map(|cell| match cell {
+                        Cell::Value(s) => s,
+                        Cell::Ellipsis => String::from("..."),
+                    })
+                    .collect(),
+                None => vec![String::from("..."); header.len()],
+            };
+            lines.push(format!("| {} |", cells.join(" | ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Formats matrix NumPy style, precision can be given as `{:.2}`
+impl<T: fmt::Display> fmt::Display for Matrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = PrintOptions {
+            precision: f.precision(),
+            ..PrintOptions::default()
+        };
+        f.write_str(&self.to_string_with(&options))
     }
 }
 
@@ -268,4 +592,92 @@ mod tests {
             expected_result
         );
     }
+
+    #[test]
+    fn fmatrix_multiplication() {
+        let matrix =
+            Matrix::<f64>::new(vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![0.5, 0.0]]).unwrap();
+        let matrix2 = Matrix::<f64>::new(vec![vec![2.0, 0.0, 1.0], vec![1.0, 2.0, 0.0]]).unwrap();
+        let expected = Matrix::<f64>::new(vec![
+            vec![4.0, 4.0, 1.0],
+            vec![10.0, 8.0, 3.0],
+            vec![1.0, 0.0, 0.5],
+        ])
+        .unwrap();
+        assert_eq!(matrix.matrix_multiplication_f(&matrix2), Ok(expected));
+        assert_eq!(
+            matrix.matrix_multiplication_f(&matrix),
+            Err(MatrixError::MultiplicationDimensionMismatch {
+                left: (3, 2),
+                right: (3, 2)
+            })
+        );
+        let vector = Vector::<f64>::new(vec![1.0, -1.0]);
+        assert_eq!(
+            matrix
+                .vector_multiplication_f(&vector)
+                .unwrap()
+                .components(),
+            &[-1.0, -1.0, 0.5]
+        );
+        assert_eq!(
+            matrix2.vector_multiplication_f(&vector),
+            Err(MatrixError::MultiplicationDimensionMismatch {
+                left: (2, 3),
+                right: (2, 1)
+            })
+        );
+    }
+
+    #[test]
+    fn imatrix_display() {
+        let matrix = Matrix::<i64>::new(vec![vec![1, -2], vec![30, 4]]).unwrap();
+        assert_eq!(matrix.to_string(), "[[ 1 -2]\n [30  4]]");
+        assert_eq!(Matrix::<i64>::new(vec![]).unwrap().to_string(), "[]");
+    }
+
+    #[test]
+    fn matrix_display_precision() {
+        let matrix = Matrix::<f64> {
+            data: vec![vec![1.0, 2.5], vec![3.26, 4.14]],
+            rows: 2,
+            cols: 2,
+        };
+        assert_eq!(format!("{:.1}", matrix), "[[1.0 2.5]\n [3.3 4.1]]");
+    }
+
+    #[test]
+    fn imatrix_display_elision() {
+        let data: Vec<Vec<i64>> = (0..40)
+            .map(|r| (0..40).map(|c| r * 40 + c).collect())
+            .collect();
+        let matrix = Matrix::<i64>::new(data).unwrap();
+        let options = PrintOptions::default();
+        let text = matrix.to_string_with(&options);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "[[   0    1    2 ...   37   38   39]");
+        assert_eq!(lines[3], " ...");
+        assert_eq!(lines[6], " [1560 1561 1562 ... 1597 1598 1599]]");
+        assert_eq!(matrix.to_string_with(&options.full()).lines().count(), 40);
+    }
+
+    #[test]
+    fn imatrix_latex_elision() {
+        let data: Vec<Vec<i64>> = (0..3).map(|r| (0..4).map(|c| r + c).collect()).collect();
+        let matrix = Matrix::<i64>::new(data).unwrap();
+        let options = PrintOptions {
+            threshold: 4,
+            edge_items: 1,
+            ..PrintOptions::default()
+        };
+        assert_eq!(
+            matrix.to_latex(&options),
+            "\\begin{bmatrix}\n0 & \\cdots & 3 \\\\\n\\vdots & \\ddots & \\vdots \\\\\n2 & \\cdots & 5\n\\end{bmatrix}"
+        );
+        assert_eq!(
+            matrix.to_markdown(&options),
+            "| 0 | ... | 3 |\n|---|---|---|\n| 0 | ... | 3 |\n| ... | ... | ... |\n| 2 | ... | 5 |"
+        );
+    }
 }
@@ -0,0 +1,430 @@
+//! Generalized means of the `Mean` API and log-sum-exp.
+//!
+//! The power mean `M_p = (Σ w x^p / Σ w)^(1/p)` covers the harmonic (`p = -1`), geometric (the
+//! limit `p = 0`), arithmetic (`p = 1`) and quadratic (`p = 2`) means. Values are scaled by their
+//! maximum before taking positive powers and by their smallest nonzero value before taking
+//! negative ones, and the geometric mean is computed from logarithms, so none of them overflows.
+//! They are defined for non-negative values and NaN otherwise.
+
+use crate::{check_f, check_weighted, to_f, Mean, StatsError};
+
+/// Weighted power mean of order `p`, unweighted without `weights`. Terms of zero weight are left
+/// out, so a zero weight cancels an infinite or zero value
+fn power_mean(data: &[f64], weights: Option<&[f64]>, p: f64) -> f64 {
+    if let Some(weights) = weights {
+        if data.len() != weights.len() {
+            panic!("data and weights must have the same length");
+        }
+    }
+    if data.is_empty() || p.is_nan() || data.iter().any(|&x| x.is_nan() || x < 0.0) {
+        return f64::NAN;
+    }
+    let terms = || {
+        data.iter()
+            .enumerate()
+            .map(move |(i, &x)| (x, weights.map_or(1.0, |weights| weights[i])))
+            .filter(|&(_, w)| w != 0.0)
+    };
+    let total: f64 = terms().map(|(_, w)| w).sum();
+    if p == f64::INFINITY {
+        return terms().map(|(x, _)| x).fold(f64::NAN, f64::max);
+    }
+    if p == f64::NEG_INFINITY {
+        return terms().map(|(x, _)| x).fold(f64::NAN, f64::min);
+    }
+    if p == 0.0 {
+        return (terms().map(|(x, w)| w * x.ln()).sum::<f64>() / total).exp();
+    }
+    // the value the others are divided by must give the largest term, so that no power overflows
+    let scale = if p > 0.0 {
+        terms().map(|(x, _)| x).fold(0.0, f64::max)
+    } else {
+        terms()
+            .map(|(x, _)| x)
+            .filter(|&x| x > 0.0)
+            .fold(f64::INFINITY, f64::min)
+    };
+    let scale = if scale > 0.0 && scale.is_finite() {
+        scale
+    } else {
+        1.0
+    };
+    let sum: f64 = terms().map(|(x, w)| w * (x / scale).powf(p)).sum();
+    scale * (sum / total).powf(1.0 / p)
+}
+
+/// Fails with `NegativeValue` at the first negative value
+fn check_non_negative(data: &[f64]) -> Result<(), StatsError> {
+    match data.iter().position(|&x| x < 0.0) {
+        Some(index) => Err(StatsError::NegativeValue {
+            index: index as u64,
+        }),
+        None => Ok(()),
+    }
+}
+
+impl Mean {
+    /// Geometric mean, the `n`-th root of the product computed as the exponential of the mean
+    /// logarithm. Zero if any value is zero, NaN for empty data or negative values
+    /// ```
+    /// use datas::Mean;
+    /// assert!((Mean::geometric_mean_f(&[1.0, 4.0, 16.0]) - 4.0).abs() < 1e-12);
+    /// // the product 1e600 would overflow
+    /// assert!((Mean::geometric_mean_f(&[1e300, 1e300]) / 1e300 - 1.0).abs() < 1e-12);
+    /// ```
+    pub fn geometric_mean_f(data: &[f64]) -> f64 {
+        power_mean(data, None, 0.0)
+    }
+
+    pub fn geometric_mean_i(data: &[i64]) -> f64 {
+        Mean::geometric_mean_f(&to_f(data))
+    }
+
+    /// Harmonic mean, the reciprocal of the mean reciprocal. Zero if any value is zero, NaN for
+    /// empty data or negative values
+    /// ```
+    /// use datas::Mean;
+    /// // average speed over equal distances at 60 and 30 km/h
+    /// assert_eq!(Mean::harmonic_mean_f(&[60.0, 30.0]), 40.0);
+    /// ```
+    pub fn harmonic_mean_f(data: &[f64]) -> f64 {
+        power_mean(data, None, -1.0)
+    }
+
+    pub fn harmonic_mean_i(data: &[i64]) -> f64 {
+        Mean::harmonic_mean_f(&to_f(data))
+    }
+
+    /// Generalized (Hölder) mean of order `p`, the geometric mean for `p = 0`, the maximum for
+    /// infinite `p` and the minimum for negative infinite `p`. NaN for empty data or negative
+    /// values
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::power_mean_f(&[1.0, 2.0, 3.0], 1.0), 2.0);
+    /// assert!((Mean::power_mean_f(&[1.0, 7.0], 3.0) - 172f64.cbrt()).abs() < 1e-12);
+    /// ```
+    pub fn power_mean_f(data: &[f64], p: f64) -> f64 {
+        power_mean(data, None, p)
+    }
+
+    pub fn power_mean_i(data: &[i64], p: f64) -> f64 {
+        Mean::power_mean_f(&to_f(data), p)
+    }
+
+    /// Quadratic mean or root mean square, also defined for negative values. NaN for empty data
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::quadratic_mean_f(&[3.0, -4.0, 3.0, 4.0]), 12.5f64.sqrt());
+    /// ```
+    pub fn quadratic_mean_f(data: &[f64]) -> f64 {
+        let magnitudes: Vec<f64> = data.iter().map(|x| x.abs()).collect();
+        power_mean(&magnitudes, None, 2.0)
+    }
+
+    pub fn quadratic_mean_i(data: &[i64]) -> f64 {
+        Mean::quadratic_mean_f(&to_f(data))
+    }
+
+    /// Weighted geometric mean `exp(Σ w ln x / Σ w)`, panics when the lengths differ like
+    /// `weighted_average_f`
+    pub fn weighted_geometric_mean_f(data: &[f64], weights: &[f64]) -> f64 {
+        power_mean(data, Some(weights), 0.0)
+    }
+
+    pub fn weighted_geometric_mean_i(data: &[i64], weights: &[i64]) -> f64 {
+        Mean::weighted_geometric_mean_f(&to_f(data), &to_f(weights))
+    }
+
+    /// Weighted harmonic mean `Σ w / Σ (w / x)`, panics when the lengths differ
+    /// ```
+    /// use datas::Mean;
+    /// // average speed over 1 km at 60 km/h and 2 km at 40 km/h
+    /// assert_eq!(Mean::weighted_harmonic_mean_f(&[60.0, 40.0], &[1.0, 2.0]), 45.0);
+    /// ```
+    pub fn weighted_harmonic_mean_f(data: &[f64], weights: &[f64]) -> f64 {
+        power_mean(data, Some(weights), -1.0)
+    }
+
+    pub fn weighted_harmonic_mean_i(data: &[i64], weights: &[i64]) -> f64 {
+        Mean::weighted_harmonic_mean_f(&to_f(data), &to_f(weights))
+    }
+
+    /// Weighted power mean of order `p`, `p = 1` is `weighted_average_f` and `p = 2` the weighted
+    /// quadratic mean. Panics when the lengths differ
+    pub fn weighted_power_mean_f(data: &[f64], weights: &[f64], p: f64) -> f64 {
+        power_mean(data, Some(weights), p)
+    }
+
+    pub fn weighted_power_mean_i(data: &[i64], weights: &[i64], p: f64) -> f64 {
+        Mean::weighted_power_mean_f(&to_f(data), &to_f(weights), p)
+    }
+
+    /// `ln(Σ exp(x))` without overflow or underflow, the exponentials are taken relative to the
+    /// maximum. Negative infinity for empty data, like the logarithm of an empty sum
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::log_sum_exp_f(&[1000.0, 1000.0]), 1000.0 + 2f64.ln());
+    /// ```
+    pub fn log_sum_exp_f(data: &[f64]) -> f64 {
+        if data.iter().any(|x| x.is_nan()) {
+            return f64::NAN;
+        }
+        let max = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        if max.is_infinite() {
+            return max;
+        }
+        max + data.iter().map(|x| (x - max).exp()).sum::<f64>().ln()
+    }
+
+    /// `ln(Σ exp(x) / n)`, the logarithm of the mean of values given by their logarithms.
+    /// NaN for empty data
+    pub fn log_mean_exp_f(data: &[f64]) -> f64 {
+        Mean::log_sum_exp_f(data) - (data.len() as f64).ln()
+    }
+
+    /// `geometric_mean_f` that fails on empty data, NaN or negative values
+    /// ```
+    /// use datas::{Mean, StatsError};
+    /// assert_eq!(
+    ///     Mean::try_geometric_mean_f(&[1.0, -2.0]),
+    ///     Err(StatsError::NegativeValue { index: 1 })
+    /// );
+    /// ```
+    pub fn try_geometric_mean_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_non_negative(data)?;
+        Ok(Mean::geometric_mean_f(data))
+    }
+
+    pub fn try_geometric_mean_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_geometric_mean_f(&to_f(data))
+    }
+
+    pub fn try_harmonic_mean_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_non_negative(data)?;
+        Ok(Mean::harmonic_mean_f(data))
+    }
+
+    pub fn try_harmonic_mean_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_harmonic_mean_f(&to_f(data))
+    }
+
+    /// `power_mean_f` that fails on empty data, NaN or negative values, a NaN `p` gives NaN
+    pub fn try_power_mean_f(data: &[f64], p: f64) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_non_negative(data)?;
+        Ok(Mean::power_mean_f(data, p))
+    }
+
+    pub fn try_power_mean_i(data: &[i64], p: f64) -> Result<f64, StatsError> {
+        Mean::try_power_mean_f(&to_f(data), p)
+    }
+
+    pub fn try_quadratic_mean_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::quadratic_mean_f(data))
+    }
+
+    pub fn try_quadratic_mean_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_quadratic_mean_f(&to_f(data))
+    }
+
+    /// `weighted_geometric_mean_f` that fails on mismatched lengths, empty data, NaN, negative
+    /// values, negative weights or weights summing to zero
+    pub fn try_weighted_geometric_mean_f(data: &[f64], weights: &[f64]) -> Result<f64, StatsError> {
+        check_weighted(data, weights)?;
+        check_non_negative(data)?;
+        Ok(Mean::weighted_geometric_mean_f(data, weights))
+    }
+
+    pub fn try_weighted_geometric_mean_i(data: &[i64], weights: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_weighted_geometric_mean_f(&to_f(data), &to_f(weights))
+    }
+
+    pub fn try_weighted_harmonic_mean_f(data: &[f64], weights: &[f64]) -> Result<f64, StatsError> {
+        check_weighted(data, weights)?;
+        check_non_negative(data)?;
+        Ok(Mean::weighted_harmonic_mean_f(data, weights))
+    }
+
+    pub fn try_weighted_harmonic_mean_i(data: &[i64], weights: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_weighted_harmonic_mean_f(&to_f(data), &to_f(weights))
+    }
+
+    pub fn try_weighted_power_mean_f(
+        data: &[f64],
+        weights: &[f64],
+        p: f64,
+    ) -> Result<f64, StatsError> {
+        check_weighted(data, weights)?;
+        check_non_negative(data)?;
+        Ok(Mean::weighted_power_mean_f(data, weights, p))
+    }
+
+    pub fn try_weighted_power_mean_i(
+        data: &[i64],
+        weights: &[i64],
+        p: f64,
+    ) -> Result<f64, StatsError> {
+        Mean::try_weighted_power_mean_f(&to_f(data), &to_f(weights), p)
+    }
+
+    /// `log_sum_exp_f` that fails on empty data or NaN
+    pub fn try_log_sum_exp_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::log_sum_exp_f(data))
+    }
+
+    /// `log_mean_exp_f` that fails on empty data or NaN
+    pub fn try_log_mean_exp_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::log_mean_exp_f(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{close, Mean, StatsError};
+
+    #[test]
+    fn means_are_ordered() {
+        let data = [1.0, 2.0, 4.0, 8.0, 16.0];
+        let harmonic = Mean::harmonic_mean_f(&data);
+        let geometric = Mean::geometric_mean_f(&data);
+        let arithmetic = Mean::mean_f(&data);
+        let quadratic = Mean::quadratic_mean_f(&data);
+        assert!(close(harmonic, 5.0 / (31.0 / 16.0)));
+        assert!(close(geometric, 4.0));
+        assert!(close(Mean::power_mean_f(&data, 1.0), arithmetic));
+        assert!(close(quadratic, (341.0f64 / 5.0).sqrt()));
+        assert!(harmonic < geometric && geometric < arithmetic && arithmetic < quadratic);
+        // the power mean approaches the geometric mean, minimum and maximum in the limits
+        assert!((Mean::power_mean_f(&data, 1e-9) - geometric).abs() < 1e-6);
+        assert_eq!(Mean::power_mean_f(&data, f64::INFINITY), 16.0);
+        assert_eq!(Mean::power_mean_f(&data, f64::NEG_INFINITY), 1.0);
+        assert_eq!(Mean::power_mean_i(&[1, 2, 4, 8, 16], -1.0), harmonic);
+        assert!(close(Mean::geometric_mean_i(&[2, 8]), 4.0));
+        assert_eq!(Mean::quadratic_mean_i(&[-3, 4, 3, -4]), 12.5f64.sqrt());
+    }
+
+    #[test]
+    fn large_and_special_values() {
+        // products and powers overflow f64 without scaling
+        let large = [1e200, 1e250, 1e300];
+        assert!(close(Mean::geometric_mean_f(&large), 1e250));
+        assert!(close(Mean::quadratic_mean_f(&large), 1e300 / 3f64.sqrt()));
+        // negative powers of tiny values overflow unless they are scaled by the smallest one,
+        // `close` cannot tell tiny values from zero
+        let harmonic = Mean::harmonic_mean_f(&[1e-300, 1e-300]);
+        assert!((harmonic / 1e-300 - 1.0).abs() < 1e-12);
+        let harmonic = Mean::harmonic_mean_f(&[1e-300, 1e300]);
+        assert!((harmonic / 2e-300 - 1.0).abs() < 1e-12);
+        let cubic = Mean::power_mean_f(&[1e-200, 2e-200, 1e300], -3.0);
+        assert!((cubic / (1e-200 * (8.0f64 / 3.0).cbrt()) - 1.0).abs() < 1e-12);
+        assert_eq!(Mean::geometric_mean_f(&[0.0, 5.0]), 0.0);
+        assert_eq!(Mean::harmonic_mean_f(&[0.0, 5.0]), 0.0);
+        assert!(Mean::geometric_mean_f(&[-1.0, 5.0]).is_nan());
+        assert!(Mean::power_mean_f(&[], 2.0).is_nan());
+        assert!(Mean::power_mean_f(&[1.0, f64::NAN], f64::INFINITY).is_nan());
+        assert_eq!(Mean::power_mean_f(&[1.0, f64::INFINITY], -1.0), 2.0);
+    }
+
+    #[test]
+    fn weighted_means() {
+        let data = [2.0, 8.0, 4.0];
+        let weights = [1.0, 2.0, 0.0];
+        // zero weight leaves a value out, integer weights repeat it
+        let repeated = [2.0, 8.0, 8.0];
+        for p in [-2.0, -1.0, 0.0, 0.5, 1.0, 3.0] {
+            assert!(close(
+                Mean::weighted_power_mean_f(&data, &weights, p),
+                Mean::power_mean_f(&repeated, p)
+            ));
+        }
+        assert_eq!(
+            Mean::weighted_power_mean_f(&data, &weights, 1.0),
+            Mean::weighted_average_f(&data, &weights)
+        );
+        assert!(close(
+            Mean::weighted_geometric_mean_f(&data, &weights),
+            128f64.cbrt()
+        ));
+        assert!(close(
+            Mean::weighted_harmonic_mean_i(&[2, 8, 4], &[1, 2, 0]),
+            3.0 / 0.75
+        ));
+        assert!(close(
+            Mean::weighted_geometric_mean_i(&[2, 8, 4], &[1, 2, 0]),
+            128f64.cbrt()
+        ));
+        // a zero weight also cancels a zero value
+        assert!(close(
+            Mean::weighted_geometric_mean_f(&[0.0, 9.0], &[0.0, 1.0]),
+            9.0
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "data and weights must have the same length")]
+    fn weighted_mean_length_mismatch() {
+        Mean::weighted_geometric_mean_f(&[1.0, 2.0], &[1.0]);
+    }
+
+    #[test]
+    fn log_sum_exp() {
+        let data = [-1000.0, -1000.0, -1000.0 + 3f64.ln()];
+        assert!(close(Mean::log_sum_exp_f(&data), -1000.0 + 5f64.ln()));
+        assert!(close(
+            Mean::log_mean_exp_f(&data),
+            -1000.0 + (5.0f64 / 3.0).ln()
+        ));
+        assert!(close(
+            Mean::log_sum_exp_f(&[1.0, 2.0]),
+            (1f64.exp() + 2f64.exp()).ln()
+        ));
+        assert_eq!(Mean::log_sum_exp_f(&[]), f64::NEG_INFINITY);
+        assert_eq!(Mean::log_sum_exp_f(&[f64::NEG_INFINITY, 0.0]), 0.0);
+        assert_eq!(Mean::log_sum_exp_f(&[f64::INFINITY, 0.0]), f64::INFINITY);
+        assert!(Mean::log_sum_exp_f(&[f64::NAN, 0.0]).is_nan());
+        assert!(Mean::log_mean_exp_f(&[]).is_nan());
+    }
+
+    #[test]
+    fn try_functions() {
+        assert_eq!(Mean::try_harmonic_mean_f(&[]), Err(StatsError::Empty));
+        assert_eq!(
+            Mean::try_power_mean_i(&[1, -2], 2.0),
+            Err(StatsError::NegativeValue { index: 1 })
+        );
+        assert_eq!(Mean::try_quadratic_mean_i(&[-2, 2]), Ok(2.0));
+        assert_eq!(
+            Mean::try_weighted_harmonic_mean_f(&[1.0, 2.0], &[1.0]),
+            Err(StatsError::LengthMismatch {
+                data: 2,
+                weights: 1
+            })
+        );
+        assert_eq!(
+            Mean::try_weighted_power_mean_i(&[1, 2], &[1, -1], 2.0),
+            Err(StatsError::NegativeWeight { index: 1 })
+        );
+        assert_eq!(
+            Mean::try_weighted_geometric_mean_f(&[1.0, 100.0], &[2.0, -1.0]),
+            Err(StatsError::NegativeWeight { index: 1 })
+        );
+        assert_eq!(
+            Mean::try_weighted_harmonic_mean_f(&[1.0, 2.0], &[0.0, 0.0]),
+            Err(StatsError::ZeroWeightSum)
+        );
+        assert_eq!(
+            Mean::try_weighted_geometric_mean_f(&[1.0, 4.0], &[f64::NAN, 1.0]),
+            Err(StatsError::NaNWeight { index: 0 })
+        );
+        assert_eq!(
+            Mean::try_log_mean_exp_f(&[0.0, f64::NAN]),
+            Err(StatsError::ContainsNaN { index: 1 })
+        );
+        assert_eq!(Mean::try_log_sum_exp_f(&[0.0]), Ok(0.0));
+    }
+}
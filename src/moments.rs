@@ -0,0 +1,406 @@
+//! Moments and shape statistics of the `Mean` API.
+//!
+//! Central moments, skewness and kurtosis follow the conventions of `scipy.stats`: the biased
+//! estimators divide by `n`, the corrected ones are the adjusted Fisher–Pearson estimators. Sample
+//! L-moments are computed from probability weighted moments as defined by Hosking.
+
+use crate::{check_empty, check_f, check_len, sum_i, Mean, StatsError};
+
+/// Deviations from the mean
+fn deviations_f(data: &[f64]) -> impl Iterator<Item = f64> + '_ {
+    let mean = Mean::mean_f(data);
+    data.iter().map(move |x| x - mean)
+}
+
+/// Deviations from the exact integer mean, see `variance_ddof_i`
+fn deviations_i(data: &[i64]) -> impl Iterator<Item = f64> + '_ {
+    let len = data.len().max(1) as i128;
+    let sum = sum_i(data);
+    let (quotient, remainder) = (sum / len, sum % len);
+    let fraction = remainder as f64 / len as f64;
+    data.iter()
+        .map(move |&x| (x as i128 - quotient) as f64 - fraction)
+}
+
+/// Mean of the `order` powers of `values`, NaN for no values
+fn mean_power(values: impl Iterator<Item = f64>, order: u32) -> f64 {
+    let power = |value: f64| match i32::try_from(order) {
+        Ok(order) => value.powi(order),
+        Err(_) => value.powf(order as f64),
+    };
+    let (mut sum, mut len) = (0.0, 0);
+    for value in values {
+        sum += power(value);
+        len += 1;
+    }
+    sum / len as f64
+}
+
+/// Skewness `g1` from the second and third central moments
+fn skewness(m2: f64, m3: f64) -> f64 {
+    m3 / m2.powf(1.5)
+}
+
+/// Excess kurtosis `g2` from the second and fourth central moments
+fn kurtosis(m2: f64, m4: f64) -> f64 {
+    m4 / (m2 * m2) - 3.0
+}
+
+/// Adjusted Fisher–Pearson skewness `G1` from `g1` of `len` values
+fn sample_skewness(g1: f64, len: usize) -> f64 {
+    let n = len as f64;
+    if len < 3 {
+        return f64::NAN;
+    }
+    g1 * (n * (n - 1.0)).sqrt() / (n - 2.0)
+}
+
+/// Bias corrected excess kurtosis `G2` from `g2` of `len` values
+fn sample_kurtosis(g2: f64, len: usize) -> f64 {
+    let n = len as f64;
+    if len < 4 {
+        return f64::NAN;
+    }
+    ((n + 1.0) * g2 + 6.0) * (n - 1.0) / ((n - 2.0) * (n - 3.0))
+}
+
+/// First `count` L-moments of sorted values, at most one per value
+fn l_moments(sorted: &[f64], count: usize) -> Vec<f64> {
+    let n = sorted.len();
+    let count = count.min(n);
+    // probability weighted moments b_r, the weight of the j-th smallest value is
+    // C(j, r) / C(n - 1, r) which is updated from the weight for r - 1
+    let mut weights = vec![1.0; n];
+    let mut pwm = Vec::with_capacity(count);
+    for r in 0..count {
+        if r > 0 {
+            for (j, weight) in weights.iter_mut().enumerate() {
+                *weight *= j.saturating_sub(r - 1) as f64 / (n - r) as f64;
+            }
+        }
+        let sum: f64 = weights.iter().zip(sorted).map(|(w, x)| w * x).sum();
+        pwm.push(sum / n as f64);
+    }
+    (0..count)
+        .map(|r| {
+            // λ_{r+1} = Σ_k (-1)^(r-k) C(r, k) C(r+k, k) b_k
+            let mut coefficient = if r % 2 == 0 { 1.0 } else { -1.0 };
+            let mut lambda = 0.0;
+            for (k, b) in pwm.iter().enumerate().take(r + 1) {
+                lambda += coefficient * b;
+                coefficient *= -(((r - k) * (r + k + 1)) as f64) / ((k + 1) * (k + 1)) as f64;
+            }
+            lambda
+        })
+        .collect()
+}
+
+fn sorted_f(data: &[f64]) -> Vec<f64> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+fn sorted_i(data: &[i64]) -> Vec<f64> {
+    let mut sorted = data.to_vec();
+    sorted.sort_unstable();
+    sorted.into_iter().map(|x| x as f64).collect()
+}
+
+impl Mean {
+    /// Raw moment of `order`, the mean of `x^order`. NaN for empty data
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::raw_moment_f(&[1.0, 2.0, 3.0], 2), 14.0 / 3.0);
+    /// ```
+    pub fn raw_moment_f(data: &[f64], order: u32) -> f64 {
+        mean_power(data.iter().copied(), order)
+    }
+
+    /// Raw moment of integers, the powers are computed in `f64`
+    pub fn raw_moment_i(data: &[i64], order: u32) -> f64 {
+        mean_power(data.iter().map(|&x| x as f64), order)
+    }
+
+    /// Central moment of `order`, the mean of `(x - mean)^order`. NaN for empty data
+    /// ```
+    /// use datas::Mean;
+    /// let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+    /// assert_eq!(Mean::central_moment_f(&data, 2), Mean::variance_f(&data));
+    /// ```
+    pub fn central_moment_f(data: &[f64], order: u32) -> f64 {
+        mean_power(deviations_f(data), order)
+    }
+
+    /// Central moment of integers, deviations are taken from the exact integer mean
+    pub fn central_moment_i(data: &[i64], order: u32) -> f64 {
+        mean_power(deviations_i(data), order)
+    }
+
+    /// Population (biased) skewness `g1 = m3 / m2^(3/2)`, `scipy.stats.skew` with `bias=True`.
+    /// NaN for empty data or when all values are equal
+    /// ```
+    /// use datas::Mean;
+    /// let data = [2.0, 8.0, 0.0, 4.0, 1.0, 9.0, 9.0, 0.0];
+    /// assert!((Mean::skewness_f(&data) - 0.2650554122698573).abs() < 1e-15);
+    /// ```
+    pub fn skewness_f(data: &[f64]) -> f64 {
+        skewness(
+            Mean::central_moment_f(data, 2),
+            Mean::central_moment_f(data, 3),
+        )
+    }
+
+    pub fn skewness_i(data: &[i64]) -> f64 {
+        skewness(
+            Mean::central_moment_i(data, 2),
+            Mean::central_moment_i(data, 3),
+        )
+    }
+
+    /// Adjusted Fisher–Pearson skewness `G1`, unbiased for normal data and the skewness reported by
+    /// Excel and SAS. NaN for fewer than 3 values
+    pub fn sample_skewness_f(data: &[f64]) -> f64 {
+        sample_skewness(Mean::skewness_f(data), data.len())
+    }
+
+    pub fn sample_skewness_i(data: &[i64]) -> f64 {
+        sample_skewness(Mean::skewness_i(data), data.len())
+    }
+
+    /// Population (biased) excess kurtosis `g2 = m4 / m2^2 - 3`, zero for normal data.
+    /// NaN for empty data or when all values are equal
+    pub fn kurtosis_f(data: &[f64]) -> f64 {
+        kurtosis(
+            Mean::central_moment_f(data, 2),
+            Mean::central_moment_f(data, 4),
+        )
+    }
+
+    pub fn kurtosis_i(data: &[i64]) -> f64 {
+        kurtosis(
+            Mean::central_moment_i(data, 2),
+            Mean::central_moment_i(data, 4),
+        )
+    }
+
+    /// Bias corrected excess kurtosis `G2`, `scipy.stats.kurtosis` with `bias=False`.
+    /// NaN for fewer than 4 values
+    pub fn sample_kurtosis_f(data: &[f64]) -> f64 {
+        sample_kurtosis(Mean::kurtosis_f(data), data.len())
+    }
+
+    pub fn sample_kurtosis_i(data: &[i64]) -> f64 {
+        sample_kurtosis(Mean::kurtosis_i(data), data.len())
+    }
+
+    /// First `count` sample L-moments `λ1, λ2, ...`, unbiased estimates that exist whenever the
+    /// mean does and are less sensitive to outliers than moments. `λ1` is the mean and `λ2` half
+    /// the mean absolute difference, the L-skewness and L-kurtosis are `λ3 / λ2` and `λ4 / λ2`.
+    /// `λr` needs at least `r` values, so no more than `data.len()` L-moments are returned
+    /// ```
+    /// use datas::Mean;
+    /// let l = Mean::l_moments_f(&[1.0, 2.0, 3.0, 4.0, 5.0], 3);
+    /// assert_eq!(l, vec![3.0, 1.0, 0.0]);
+    /// ```
+    pub fn l_moments_f(data: &[f64], count: usize) -> Vec<f64> {
+        l_moments(&sorted_f(data), count)
+    }
+
+    pub fn l_moments_i(data: &[i64], count: usize) -> Vec<f64> {
+        l_moments(&sorted_i(data), count)
+    }
+
+    /// `raw_moment_f` that fails on empty data or NaN instead of returning NaN
+    pub fn try_raw_moment_f(data: &[f64], order: u32) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::raw_moment_f(data, order))
+    }
+
+    /// `raw_moment_i` that fails on empty data instead of returning NaN
+    pub fn try_raw_moment_i(data: &[i64], order: u32) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::raw_moment_i(data, order))
+    }
+
+    /// `central_moment_f` that fails on empty data or NaN instead of returning NaN
+    pub fn try_central_moment_f(data: &[f64], order: u32) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::central_moment_f(data, order))
+    }
+
+    /// `central_moment_i` that fails on empty data instead of returning NaN
+    pub fn try_central_moment_i(data: &[i64], order: u32) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::central_moment_i(data, order))
+    }
+
+    /// `skewness_f` that fails on empty data or NaN, equal values still give NaN
+    pub fn try_skewness_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::skewness_f(data))
+    }
+
+    pub fn try_skewness_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::skewness_i(data))
+    }
+
+    /// `sample_skewness_f` that fails on NaN or fewer than 3 values
+    /// ```
+    /// use datas::{Mean, StatsError};
+    /// assert_eq!(
+    ///     Mean::try_sample_skewness_f(&[1.0, 2.0]),
+    ///     Err(StatsError::InsufficientData { required: 3, actual: 2 })
+    /// );
+    /// ```
+    pub fn try_sample_skewness_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_len(data, 3)?;
+        Ok(Mean::sample_skewness_f(data))
+    }
+
+    pub fn try_sample_skewness_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        check_len(data, 3)?;
+        Ok(Mean::sample_skewness_i(data))
+    }
+
+    /// `kurtosis_f` that fails on empty data or NaN, equal values still give NaN
+    pub fn try_kurtosis_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::kurtosis_f(data))
+    }
+
+    pub fn try_kurtosis_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::kurtosis_i(data))
+    }
+
+    /// `sample_kurtosis_f` that fails on NaN or fewer than 4 values
+    pub fn try_sample_kurtosis_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_len(data, 4)?;
+        Ok(Mean::sample_kurtosis_f(data))
+    }
+
+    pub fn try_sample_kurtosis_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        check_len(data, 4)?;
+        Ok(Mean::sample_kurtosis_i(data))
+    }
+
+    /// `l_moments_f` that fails on NaN or fewer than `count` values
+    pub fn try_l_moments_f(data: &[f64], count: usize) -> Result<Vec<f64>, StatsError> {
+        check_f(data)?;
+        check_len(data, count as u64)?;
+        Ok(Mean::l_moments_f(data, count))
+    }
+
+    pub fn try_l_moments_i(data: &[i64], count: usize) -> Result<Vec<f64>, StatsError> {
+        check_empty(data)?;
+        check_len(data, count as u64)?;
+        Ok(Mean::l_moments_i(data, count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{close, Mean, StatsError};
+
+    #[test]
+    fn shape_matches_scipy() {
+        // scipy.stats.skew and scipy.stats.kurtosis of the same data
+        let data = [2.0, 8.0, 0.0, 4.0, 1.0, 9.0, 9.0, 0.0];
+        let integers = [2, 8, 0, 4, 1, 9, 9, 0];
+        for (skewness, sample_skewness, kurtosis, sample_kurtosis) in [
+            (
+                Mean::skewness_f(&data),
+                Mean::sample_skewness_f(&data),
+                Mean::kurtosis_f(&data),
+                Mean::sample_kurtosis_f(&data),
+            ),
+            (
+                Mean::skewness_i(&integers),
+                Mean::sample_skewness_i(&integers),
+                Mean::kurtosis_i(&integers),
+                Mean::sample_kurtosis_i(&integers),
+            ),
+        ] {
+            assert!(close(skewness, 0.2650554122698573));
+            assert!(close(sample_skewness, 0.33058218040797466));
+            assert!(close(kurtosis, -1.6660010752838508));
+            assert!(close(sample_kurtosis, -2.098602258096087));
+        }
+        assert!(Mean::skewness_f(&[3.0, 3.0]).is_nan());
+        assert!(Mean::sample_kurtosis_f(&[1.0, 2.0, 3.0]).is_nan());
+    }
+
+    #[test]
+    fn moments() {
+        let data = [1.0, 2.0, 3.0, 6.0];
+        assert_eq!(Mean::raw_moment_f(&data, 0), 1.0);
+        assert_eq!(Mean::raw_moment_f(&data, 1), 3.0);
+        assert_eq!(Mean::raw_moment_i(&[1, 2, 3, 6], 3), 63.0);
+        assert_eq!(Mean::central_moment_f(&data, 1), 0.0);
+        assert_eq!(Mean::central_moment_f(&data, 3), 4.5);
+        assert_eq!(Mean::central_moment_i(&[1, 2, 3, 6], 2), 3.5);
+        // deviations from the exact mean stay small for values beyond f64 precision
+        let large = [i64::MAX, i64::MAX - 1, i64::MAX - 2];
+        assert_eq!(Mean::central_moment_i(&large, 2), 2.0 / 3.0);
+        assert_eq!(Mean::skewness_i(&large), 0.0);
+        assert!(Mean::central_moment_f(&[], 2).is_nan());
+    }
+
+    #[test]
+    fn l_moments() {
+        // reference values from the probability weighted moment definition
+        let data = [2.0, 8.0, 0.0, 4.0, 1.0, 9.0, 9.0, 0.0, 5.0, 3.5];
+        let expected = [4.15, 2.094444444444444, 0.2, -0.21904761904762];
+        let l = Mean::l_moments_f(&data, 4);
+        for (actual, expected) in l.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
+        // λ2 is half of the mean absolute difference between pairs
+        let pairs: f64 = data
+            .iter()
+            .flat_map(|x| data.iter().map(move |y| (x - y).abs()))
+            .sum();
+        assert!(close(l[1], pairs / (10.0 * 9.0) / 2.0));
+        assert_eq!(Mean::l_moments_i(&[5, 1, 4, 2, 3], 4)[..3], [3.0, 1.0, 0.0]);
+        assert_eq!(Mean::l_moments_f(&[1.0, 2.0], 3), vec![1.5, 0.5]);
+        assert_eq!(Mean::l_moments_f(&[1.0], usize::MAX), vec![1.0]);
+    }
+
+    #[test]
+    fn try_functions() {
+        assert_eq!(Mean::try_skewness_f(&[]), Err(StatsError::Empty));
+        assert_eq!(
+            Mean::try_kurtosis_f(&[1.0, f64::NAN]),
+            Err(StatsError::ContainsNaN { index: 1 })
+        );
+        assert_eq!(
+            Mean::try_sample_kurtosis_i(&[1, 2, 3]),
+            Err(StatsError::InsufficientData {
+                required: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            Mean::try_l_moments_i(&[1, 2], 3),
+            Err(StatsError::InsufficientData {
+                required: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(Mean::try_central_moment_i(&[1, 3], 2), Ok(1.0));
+        assert_eq!(Mean::try_raw_moment_f(&[2.0], 10), Ok(1024.0));
+        // orders beyond i32 do not wrap to negative exponents
+        assert_eq!(Mean::raw_moment_f(&[0.5], u32::MAX), 0.0);
+        assert_eq!(Mean::raw_moment_f(&[-1.0], u32::MAX), -1.0);
+        assert_eq!(
+            Mean::try_sample_skewness_i(&[1, 2, 6]),
+            Ok(Mean::sample_skewness_i(&[1, 2, 6]))
+        );
+    }
+}
@@ -0,0 +1,799 @@
+//! Nearest neighbor search over collections of `Vector<f64>`.
+//!
+//! `KdTree` and `BallTree` answer the same queries as the linear scan of `BruteForce`, but skip
+//! the parts of the collection that cannot contain a result. The KD-tree works best in low
+//! dimensions, the ball tree keeps pruning in higher dimensions and with clustered data.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::norm::nan_max;
+use crate::vector::{Vector, VectorError};
+
+/// Number of points below which tree nodes are scanned linearly
+const LEAF_SIZE: usize = 16;
+
+/// Relative slack on pruning bounds covering the rounding of computed distances
+const PRUNE_TOLERANCE: f64 = 1e-12;
+
+/// Distance used to compare points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+    /// Minkowski distance with the given `p`, indexes reject `p` that is NaN or below 1
+    Minkowski(f64),
+    /// `1 - cos` of the angle between vectors, zero vectors have no direction and are rejected
+    Cosine,
+}
+
+impl Metric {
+    /// Metric applied to stored points, cosine distance is searched as the Euclidean distance
+    /// of normalized vectors which orders points the same way
+    fn searched(self) -> Metric {
+        match self {
+            Metric::Cosine => Metric::Euclidean,
+            metric => metric,
+        }
+    }
+
+    fn between(self, a: &[f64], b: &[f64]) -> f64 {
+        let differences = a.iter().zip(b).map(|(x, y)| (x - y).abs());
+        match self {
+            Metric::Euclidean | Metric::Cosine => differences.map(|d| d * d).sum::<f64>().sqrt(),
+            Metric::Manhattan => differences.sum(),
+            Metric::Chebyshev => differences.fold(0.0, nan_max),
+            Metric::Minkowski(p) if p.is_infinite() => differences.fold(0.0, nan_max),
+            Metric::Minkowski(p) => differences.map(|d| d.powf(p)).sum::<f64>().powf(1.0 / p),
+        }
+    }
+
+    /// Distance reported to the caller from the searched one
+    fn reported(self, distance: f64) -> f64 {
+        match self {
+            // |a - b|^2 = 2 - 2 cos for unit vectors
+            Metric::Cosine => distance * distance / 2.0,
+            _ => distance,
+        }
+    }
+
+    /// Searched distance matching a radius given by the caller
+    fn search_radius(self, radius: f64) -> f64 {
+        match self {
+            Metric::Cosine => (2.0 * radius.max(0.0)).sqrt(),
+            _ => radius,
+        }
+    }
+}
+
+/// Point found by a query
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor {
+    /// Position of the point in the indexed collection
+    pub index: usize,
+    pub distance: f64,
+}
+
+/// Queries answered by every index
+pub trait NearestNeighbors {
+    /// `k` closest points sorted by distance, ties are broken by the smaller index
+    fn knn(&self, query: &Vector<f64>, k: usize) -> Result<Vec<Neighbor>, VectorError>;
+
+    /// Points at most `radius` away sorted by distance, ties are broken by the smaller index
+    fn within_radius(&self, query: &Vector<f64>, radius: f64)
+        -> Result<Vec<Neighbor>, VectorError>;
+
+    /// Number of indexed points
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Points stored contiguously, normalized for the cosine metric
+#[derive(Debug, Clone)]
+struct Points {
+    data: Vec<f64>,
+    dimensions: Option<usize>,
+    len: usize,
+    metric: Metric,
+}
+
+impl Points {
+    fn new(points: &[Vector<f64>], metric: Metric) -> Result<Self, VectorError> {
+        if let Metric::Minkowski(p) = metric {
+            if p.is_nan() || p < 1.0 {
+                return Err(VectorError::InvalidExponent { p });
+            }
+        }
+        let dimensions = points.first().map(|point| point.dimensions());
+        let mut data = Vec::with_capacity(points.len() * dimensions.unwrap_or(0) as usize);
+        for point in points {
+            points[0].check_dimensions(point)?;
+            data.extend_from_slice(Self::prepared(point, metric)?.components());
+        }
+        Ok(Self {
+            data,
+            dimensions: dimensions.map(|dimensions| dimensions as usize),
+            len: points.len(),
+            metric,
+        })
+    }
+
+    fn prepared(point: &Vector<f64>, metric: Metric) -> Result<Vector<f64>, VectorError> {
+        match metric {
+            Metric::Cosine => point.normalize_f(),
+            _ => Ok(point.clone()),
+        }
+    }
+
+    /// Query in the form of stored points, fails unless it has the dimensions of the points
+    fn query(&self, query: &Vector<f64>) -> Result<Vector<f64>, VectorError> {
+        if let Some(dimensions) = self.dimensions {
+            if query.dimensions() != dimensions as u64 {
+                return Err(VectorError::DimensionMismatch {
+                    expected: dimensions as u64,
+                    actual: query.dimensions(),
+                });
+            }
+        }
+        Self::prepared(query, self.metric)
+    }
+
+    fn point(&self, index: usize) -> &[f64] {
+        let dimensions = self.dimensions.unwrap_or(0);
+        &self.data[index * dimensions..(index + 1) * dimensions]
+    }
+
+    fn distance(&self, query: &[f64], index: usize) -> f64 {
+        self.metric.searched().between(query, self.point(index))
+    }
+
+    /// Dimension along which the points in `order` are spread the most and that spread
+    fn widest_dimension(&self, order: &[usize]) -> (usize, f64) {
+        (0..self.dimensions.unwrap_or(0))
+            .map(|dimension| {
+                let (min, max) =
+                    order
+                        .iter()
+                        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &index| {
+                            let value = self.point(index)[dimension];
+                            (min.min(value), max.max(value))
+                        });
+                (dimension, max - min)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, 0.0))
+    }
+
+    /// Reorders `order` around its median along the widest dimension, `None` when it should stay a leaf
+    fn split(&self, order: &mut [usize]) -> Option<(usize, f64, usize)> {
+        if order.len() <= LEAF_SIZE {
+            return None;
+        }
+        let (dimension, spread) = self.widest_dimension(order);
+        if spread.is_nan() || spread <= 0.0 {
+            return None;
+        }
+        let middle = order.len() / 2;
+        order.select_nth_unstable_by(middle, |&a, &b| {
+            self.point(a)[dimension].total_cmp(&self.point(b)[dimension])
+        });
+        Some((dimension, self.point(order[middle])[dimension], middle))
+    }
+}
+
+/// Candidate ordered by distance and then by index
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Candidate {
+    pub(crate) distance: f64,
+    pub(crate) index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Collects the results of a query, the `k` closest candidates or all of them within a radius
+struct Results {
+    k: usize,
+    radius: f64,
+    heap: BinaryHeap<Candidate>,
+}
+
+impl Results {
+    fn nearest(k: usize) -> Self {
+        Self {
+            k,
+            radius: f64::INFINITY,
+            heap: BinaryHeap::with_capacity(k.min(1024) + 1),
+        }
+    }
+
+    fn within(radius: f64) -> Self {
+        Self {
+            k: usize::MAX,
+            radius,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    /// Candidates farther than this cannot change the results
+    fn bound(&self) -> f64 {
+        match self.heap.peek() {
+            Some(worst) if self.heap.len() >= self.k => worst.distance,
+            _ => self.radius,
+        }
+    }
+
+    /// Whether a region whose points are at least `lower_bound` away can change the results.
+    /// Distances are rounded differently than the bounds, so regions just past the bound are kept
+    fn reaches(&self, lower_bound: f64) -> bool {
+        lower_bound <= self.bound() * (1.0 + PRUNE_TOLERANCE)
+    }
+
+    fn offer(&mut self, distance: f64, index: usize) {
+        if self.k == 0 || distance > self.radius {
+            return;
+        }
+        let candidate = Candidate { distance, index };
+        if self.heap.len() < self.k {
+            self.heap.push(candidate);
+        } else if self.heap.peek().is_some_and(|worst| candidate < *worst) {
+            self.heap.pop();
+            self.heap.push(candidate);
+        }
+    }
+
+    fn into_neighbors(self, metric: Metric) -> Vec<Neighbor> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|candidate| Neighbor {
+                index: candidate.index,
+                distance: metric.reported(candidate.distance),
+            })
+            .collect()
+    }
+}
+
+/// Linear scan over every point, the baseline the trees are verified against
+/// ```
+/// use datas::neighbors::{BruteForce, Metric, NearestNeighbors};
+/// use datas::vector::Vector;
+/// let points = vec![
+///     Vector::<f64>::new(vec![0.0, 0.0]),
+///     Vector::<f64>::new(vec![5.0, 5.0]),
+///     Vector::<f64>::new(vec![1.0, 1.0]),
+/// ];
+/// let index = BruteForce::new(&points, Metric::Manhattan).unwrap();
+/// let nearest = index.knn(&Vector::<f64>::new(vec![0.9, 0.8]), 1).unwrap();
+/// assert_eq!(nearest[0].index, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BruteForce {
+    points: Points,
+}
+
+impl BruteForce {
+    pub fn new(points: &[Vector<f64>], metric: Metric) -> Result<Self, VectorError> {
+        Ok(Self {
+            points: Points::new(points, metric)?,
+        })
+    }
+
+    fn scan(
+        &self,
+        query: &Vector<f64>,
+        mut results: Results,
+    ) -> Result<Vec<Neighbor>, VectorError> {
+        let query = self.points.query(query)?;
+        for index in 0..self.points.len {
+            results.offer(self.points.distance(query.components(), index), index);
+        }
+        Ok(results.into_neighbors(self.points.metric))
+    }
+}
+
+impl NearestNeighbors for BruteForce {
+    fn knn(&self, query: &Vector<f64>, k: usize) -> Result<Vec<Neighbor>, VectorError> {
+        self.scan(query, Results::nearest(k))
+    }
+
+    fn within_radius(
+        &self,
+        query: &Vector<f64>,
+        radius: f64,
+    ) -> Result<Vec<Neighbor>, VectorError> {
+        let radius = self.points.metric.search_radius(radius);
+        self.scan(query, Results::within(radius))
+    }
+
+    fn len(&self) -> usize {
+        self.points.len
+    }
+}
+
+#[derive(Debug, Clone)]
+enum KdNode {
+    Leaf {
+        start: usize,
+        end: usize,
+    },
+    /// Points with a coordinate below `value` along `dimension` are on the left, above it on the right
+    Split {
+        dimension: usize,
+        value: f64,
+        left: usize,
+        right: usize,
+    },
+}
+
+/// KD-tree, splits space with axis aligned planes at the median of the widest dimension
+/// ```
+/// use datas::neighbors::{KdTree, Metric, NearestNeighbors};
+/// use datas::vector::Vector;
+/// let points: Vec<Vector<f64>> = (0..100)
+///     .map(|i| Vector::<f64>::new(vec![(i % 10) as f64, (i / 10) as f64]))
+///     .collect();
+/// let tree = KdTree::new(&points, Metric::Euclidean).unwrap();
+/// let found = tree.within_radius(&Vector::<f64>::new(vec![4.0, 4.0]), 1.0).unwrap();
+/// assert_eq!(found.len(), 5);
+/// assert_eq!(found[0].index, 44);
+/// ```
+#[derive(Debug, Clone)]
+pub struct KdTree {
+    points: Points,
+    order: Vec<usize>,
+    nodes: Vec<KdNode>,
+}
+
+impl KdTree {
+    pub fn new(points: &[Vector<f64>], metric: Metric) -> Result<Self, VectorError> {
+        let mut tree = Self {
+            points: Points::new(points, metric)?,
+            order: (0..points.len()).collect(),
+            nodes: Vec::new(),
+        };
+        let mut order = std::mem::take(&mut tree.order);
+        tree.build(&mut order, 0);
+        tree.order = order;
+        Ok(tree)
+    }
+
+    fn build(&mut self, order: &mut [usize], start: usize) -> usize {
+        let node = self.nodes.len();
+        self.nodes.push(KdNode::Leaf {
+            start,
+            end: start + order.len(),
+        });
+        if let Some((dimension, value, middle)) = self.points.split(order) {
+            let (low, high) = order.split_at_mut(middle);
+            let left = self.build(low, start);
+            let right = self.build(high, start + middle);
+            self.nodes[node] = KdNode::Split {
+                dimension,
+                value,
+                left,
+                right,
+            };
+        }
+        node
+    }
+
+    fn search(&self, node: usize, query: &[f64], results: &mut Results) {
+        match self.nodes[node] {
+            KdNode::Leaf { start, end } => {
+                for &index in &self.order[start..end] {
+                    results.offer(self.points.distance(query, index), index);
+                }
+            }
+            KdNode::Split {
+                dimension,
+                value,
+                left,
+                right,
+            } => {
+                let offset = query[dimension] - value;
+                let (near, far) = if offset <= 0.0 {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+                self.search(near, query, results);
+                // every metric is at least the difference along a single axis
+                if results.reaches(offset.abs()) {
+                    self.search(far, query, results);
+                }
+            }
+        }
+    }
+
+    fn query(
+        &self,
+        query: &Vector<f64>,
+        mut results: Results,
+    ) -> Result<Vec<Neighbor>, VectorError> {
+        let query = self.points.query(query)?;
+        if !self.nodes.is_empty() {
+            self.search(0, query.components(), &mut results);
+        }
+        Ok(results.into_neighbors(self.points.metric))
+    }
+}
+
+impl NearestNeighbors for KdTree {
+    fn knn(&self, query: &Vector<f64>, k: usize) -> Result<Vec<Neighbor>, VectorError> {
+        self.query(query, Results::nearest(k))
+    }
+
+    fn within_radius(
+        &self,
+        query: &Vector<f64>,
+        radius: f64,
+    ) -> Result<Vec<Neighbor>, VectorError> {
+        let radius = self.points.metric.search_radius(radius);
+        self.query(query, Results::within(radius))
+    }
+
+    fn len(&self) -> usize {
+        self.points.len
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Ball {
+    center: Vec<f64>,
+    /// Largest distance from the center to a point of the ball
+    radius: f64,
+    start: usize,
+    end: usize,
+    children: Option<(usize, usize)>,
+}
+
+/// Ball tree, nests balls around the centroids of their points.
+/// Pruning relies on the triangle inequality, which is why Minkowski `p` has to be at least 1
+#[derive(Debug, Clone)]
+pub struct BallTree {
+    points: Points,
+    order: Vec<usize>,
+    balls: Vec<Ball>,
+}
+
+impl BallTree {
+    pub fn new(points: &[Vector<f64>], metric: Metric) -> Result<Self, VectorError> {
+        let mut tree = Self {
+            points: Points::new(points, metric)?,
+            order: (0..points.len()).collect(),
+            balls: Vec::new(),
+        };
+        let mut order = std::mem::take(&mut tree.order);
+        if !order.is_empty() {
+            tree.build(&mut order, 0);
+        }
+        tree.order = order;
+        Ok(tree)
+    }
+
+    fn build(&mut self, order: &mut [usize], start: usize) -> usize {
+        let dimensions = self.points.dimensions.unwrap_or(0);
+        // points with NaN or infinite coordinates would move the center to NaN, they only widen
+        // the radius so that the ball is never pruned
+        let mut center = vec![0.0; dimensions];
+        let mut finite = 0;
+        for &index in order.iter() {
+            let point = self.points.point(index);
+            if point.iter().all(|value| value.is_finite()) {
+                center
+                    .iter_mut()
+                    .zip(point)
+                    .for_each(|(sum, value)| *sum += value);
+                finite += 1;
+            }
+        }
+        if finite > 0 {
+            center.iter_mut().for_each(|sum| *sum /= finite as f64);
+        }
+        let radius = order
+            .iter()
+            .map(|&index| self.points.distance(&center, index))
+            .fold(0.0, nan_max);
+        let ball = self.balls.len();
+        self.balls.push(Ball {
+            center,
+            radius,
+            start,
+            end: start + order.len(),
+            children: None,
+        });
+        if let Some((_, _, middle)) = self.points.split(order) {
+            let (low, high) = order.split_at_mut(middle);
+            let left = self.build(low, start);
+            let right = self.build(high, start + middle);
+            self.balls[ball].children = Some((left, right));
+        }
+        ball
+    }
+
+    /// `distance` is the distance from the query to the center of the ball
+    fn search(&self, ball: usize, distance: f64, query: &[f64], results: &mut Results) {
+        let node = &self.balls[ball];
+        let lower_bound = distance - node.radius;
+        if !lower_bound.is_nan() && !results.reaches(lower_bound) {
+            return;
+        }
+        match node.children {
+            None => {
+                for &index in &self.order[node.start..node.end] {
+                    results.offer(self.points.distance(query, index), index);
+                }
+            }
+            Some((left, right)) => {
+                let metric = self.points.metric.searched();
+                let left_distance = metric.between(query, &self.balls[left].center);
+                let right_distance = metric.between(query, &self.balls[right].center);
+                if left_distance <= right_distance {
+                    self.search(left, left_distance, query, results);
+                    self.search(right, right_distance, query, results);
+                } else {
+                    self.search(right, right_distance, query, results);
+                    self.search(left, left_distance, query, results);
+                }
+            }
+        }
+    }
+
+    fn query(
+        &self,
+        query: &Vector<f64>,
+        mut results: Results,
+    ) -> Result<Vec<Neighbor>, VectorError> {
+        let query = self.points.query(query)?;
+        if let Some(root) = self.balls.first() {
+            let distance = self
+                .points
+                .metric
+                .searched()
+                .between(query.components(), &root.center);
+            self.search(0, distance, query.components(), &mut results);
+        }
+        Ok(results.into_neighbors(self.points.metric))
+    }
+}
+
+impl NearestNeighbors for BallTree {
+    fn knn(&self, query: &Vector<f64>, k: usize) -> Result<Vec<Neighbor>, VectorError> {
+        self.query(query, Results::nearest(k))
+    }
+
+    fn within_radius(
+        &self,
+        query: &Vector<f64>,
+        radius: f64,
+    ) -> Result<Vec<Neighbor>, VectorError> {
+        let radius = self.points.metric.search_radius(radius);
+        self.query(query, Results::within(radius))
+    }
+
+    fn len(&self) -> usize {
+        self.points.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uniform;
+
+    /// Deterministic points with coordinates in `[0, 1)`, every third one shifted to form a cluster
+    fn points(count: usize, dimensions: usize, seed: u64) -> Vec<Vector<f64>> {
+        let values = uniform(count * dimensions, seed);
+        (0..count)
+            .map(|i| {
+                let components = values[i * dimensions..(i + 1) * dimensions]
+                    .iter()
+                    .map(|&value| {
+                        if i % 3 == 0 {
+                            value / 10.0 + 0.5
+                        } else {
+                            value
+                        }
+                    })
+                    .collect();
+                Vector::<f64>::new(components)
+            })
+            .collect()
+    }
+
+    const METRICS: [Metric; 5] = [
+        Metric::Euclidean,
+        Metric::Manhattan,
+        Metric::Chebyshev,
+        Metric::Minkowski(3.0),
+        Metric::Cosine,
+    ];
+
+    fn indices(neighbors: &[Neighbor]) -> Vec<usize> {
+        neighbors.iter().map(|neighbor| neighbor.index).collect()
+    }
+
+    #[test]
+    fn non_finite_points() {
+        let mut data = points(40, 2, 7);
+        data[5] = Vector::<f64>::new(vec![f64::NAN, 0.5]);
+        data[9] = Vector::<f64>::new(vec![f64::INFINITY, 0.5]);
+        let query = Vector::<f64>::new(vec![0.5, 0.5]);
+        for metric in METRICS {
+            let brute = BruteForce::new(&data, metric).unwrap();
+            let kd = KdTree::new(&data, metric).unwrap();
+            let ball = BallTree::new(&data, metric).unwrap();
+            for k in [3, 40] {
+                let expected = indices(&brute.knn(&query, k).unwrap());
+                assert_eq!(expected.len(), k);
+                assert_eq!(indices(&kd.knn(&query, k).unwrap()), expected);
+                assert_eq!(indices(&ball.knn(&query, k).unwrap()), expected);
+            }
+        }
+        let a = Vector::<f64>::new(vec![f64::NAN, 1.0]);
+        let chebyshev = BruteForce::new(&[a], Metric::Chebyshev).unwrap();
+        let origin = Vector::<f64>::new(vec![0.0, 1.0]);
+        assert!(chebyshev.knn(&origin, 1).unwrap()[0].distance.is_nan());
+    }
+
+    #[test]
+    fn trees_match_brute_force() {
+        for dimensions in [1, 3, 8] {
+            let data = points(700, dimensions, dimensions as u64);
+            let queries = points(20, dimensions, 99);
+            for metric in METRICS {
+                let brute = BruteForce::new(&data, metric).unwrap();
+                let kd = KdTree::new(&data, metric).unwrap();
+                let ball = BallTree::new(&data, metric).unwrap();
+                for query in &queries {
+                    for k in [1, 5, 40] {
+                        let expected = brute.knn(query, k).unwrap();
+                        assert_eq!(expected.len(), k);
+                        assert_eq!(indices(&kd.knn(query, k).unwrap()), indices(&expected));
+                        assert_eq!(indices(&ball.knn(query, k).unwrap()), indices(&expected));
+                    }
+                    let radius = brute.knn(query, 25).unwrap()[24].distance;
+                    let expected = brute.within_radius(query, radius).unwrap();
+                    assert!(expected.len() >= 25);
+                    assert_eq!(
+                        indices(&kd.within_radius(query, radius).unwrap()),
+                        indices(&expected)
+                    );
+                    assert_eq!(
+                        indices(&ball.within_radius(query, radius).unwrap()),
+                        indices(&expected)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn distances_and_ties() {
+        let data: Vec<Vector<f64>> = [[1.0, 0.0], [0.0, 1.0], [-1.0, 0.0], [0.0, 0.0], [3.0, 4.0]]
+            .iter()
+            .map(|point| Vector::<f64>::new(point.to_vec()))
+            .collect();
+        let origin = Vector::<f64>::new(vec![0.0, 0.0]);
+        let tree = KdTree::new(&data, Metric::Euclidean).unwrap();
+        let nearest = tree.knn(&origin, 3).unwrap();
+        assert_eq!(
+            nearest,
+            vec![
+                Neighbor {
+                    index: 3,
+                    distance: 0.0
+                },
+                Neighbor {
+                    index: 0,
+                    distance: 1.0
+                },
+                Neighbor {
+                    index: 1,
+                    distance: 1.0
+                },
+            ]
+        );
+        let all = BallTree::new(&data, Metric::Chebyshev)
+            .unwrap()
+            .knn(&origin, 10)
+            .unwrap();
+        assert_eq!(indices(&all), vec![3, 0, 1, 2, 4]);
+        assert_eq!(all[4].distance, 4.0);
+        assert!(tree.knn(&origin, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn cosine_distances() {
+        let data: Vec<Vector<f64>> = [[2.0, 0.0], [1.0, 1.0], [0.0, 3.0], [-1.0, 0.0]]
+            .iter()
+            .map(|point| Vector::<f64>::new(point.to_vec()))
+            .collect();
+        let query = Vector::<f64>::new(vec![5.0, 0.0]);
+        for neighbors in [
+            BruteForce::new(&data, Metric::Cosine)
+                .unwrap()
+                .knn(&query, 4)
+                .unwrap(),
+            KdTree::new(&data, Metric::Cosine)
+                .unwrap()
+                .knn(&query, 4)
+                .unwrap(),
+            BallTree::new(&data, Metric::Cosine)
+                .unwrap()
+                .knn(&query, 4)
+                .unwrap(),
+        ] {
+            assert_eq!(indices(&neighbors), vec![0, 1, 2, 3]);
+            let expected = [0.0, 1.0 - std::f64::consts::FRAC_1_SQRT_2, 1.0, 2.0];
+            for (neighbor, expected) in neighbors.iter().zip(expected) {
+                assert!((neighbor.distance - expected).abs() < 1e-12);
+            }
+        }
+        let zero = Vector::<f64>::new(vec![0.0, 0.0]);
+        assert!(matches!(
+            KdTree::new(std::slice::from_ref(&zero), Metric::Cosine),
+            Err(VectorError::ZeroVector)
+        ));
+        let tree = BallTree::new(&data, Metric::Cosine).unwrap();
+        assert_eq!(tree.knn(&zero, 1), Err(VectorError::ZeroVector));
+    }
+
+    #[test]
+    fn invalid_input() {
+        let data = vec![
+            Vector::<f64>::new(vec![1.0, 2.0]),
+            Vector::<f64>::new(vec![1.0]),
+        ];
+        let mismatch = VectorError::DimensionMismatch {
+            expected: 2,
+            actual: 1,
+        };
+        assert!(
+            matches!(BruteForce::new(&data, Metric::Euclidean), Err(ref error) if *error == mismatch)
+        );
+        let tree = KdTree::new(&data[..1], Metric::Euclidean).unwrap();
+        assert_eq!(tree.knn(&data[1], 1), Err(mismatch));
+
+        let empty = BallTree::new(&[], Metric::Euclidean).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.knn(&data[0], 3), Ok(vec![]));
+        let empty = KdTree::new(&[], Metric::Manhattan).unwrap();
+        assert_eq!(empty.within_radius(&data[0], 1.0), Ok(vec![]));
+
+        for p in [0.5, f64::NAN] {
+            assert!(matches!(
+                BallTree::new(&data[..1], Metric::Minkowski(p)),
+                Err(VectorError::InvalidExponent { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn duplicate_points() {
+        let data = vec![Vector::<f64>::new(vec![1.0, 1.0]); 100];
+        let tree = KdTree::new(&data, Metric::Euclidean).unwrap();
+        let query = Vector::<f64>::new(vec![0.0, 0.0]);
+        assert_eq!(indices(&tree.knn(&query, 3).unwrap()), vec![0, 1, 2]);
+        let ball = BallTree::new(&data, Metric::Euclidean).unwrap();
+        assert_eq!(ball.within_radius(&query, 2.0).unwrap().len(), 100);
+    }
+}
@@ -0,0 +1,281 @@
+//! Norms, sums and dot products that stay accurate on inputs where naive formulas fail.
+//!
+//! Norms are computed on components scaled by the largest magnitude, so squares can neither
+//! overflow nor underflow, and the scaled terms are summed pairwise which bounds the rounding error
+//! by `O(log n)` instead of `O(n)` ulps.
+
+use crate::vector::{Component, Vector, VectorError};
+
+/// Below this length pairwise summation adds sequentially, larger blocks are split in half
+const PAIRWISE_BLOCK: usize = 64;
+
+/// Sum with pairwise (cascade) summation
+/// ```
+/// use datas::norm;
+/// let values = vec![0.1; 1_000_000];
+/// let sequential: f64 = values.iter().sum();
+/// let exact = 100_000.000_000_005_55;
+/// assert!((norm::pairwise_sum(&values) - exact).abs() < (sequential - exact).abs());
+/// ```
+pub fn pairwise_sum(values: &[f64]) -> f64 {
+    if values.len() <= PAIRWISE_BLOCK {
+        return values.iter().sum();
+    }
+    let (left, right) = values.split_at(values.len() / 2);
+    pairwise_sum(left) + pairwise_sum(right)
+}
+
+/// Compensated sum (Kahan–Babuška–Neumaier), the rounding error of every addition is carried along
+/// ```
+/// use datas::norm;
+/// assert_eq!(norm::kahan_sum(&[1.0, 1e100, 1.0, -1e100]), 2.0);
+/// ```
+pub fn kahan_sum(values: &[f64]) -> f64 {
+    let (sum, compensation) = values
+        .iter()
+        .fold((0.0, 0.0), |(sum, compensation), &value| {
+            let (total, error) = two_sum(sum, value);
+            (total, compensation + error)
+        });
+    sum + compensation
+}
+
+/// `a + b` and its exact rounding error
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let virtual_b = sum - a;
+    let error = (a - (sum - virtual_b)) + (b - virtual_b);
+    (sum, error)
+}
+
+/// Dot product as accurate as if computed with twice the precision of `f64` (Ogita, Rump and Oishi),
+/// the rounding errors of products and sums are accumulated separately
+/// ```
+/// use datas::norm;
+/// use datas::vector::Vector;
+/// let a = Vector::<f64>::new(vec![1e16, 1.0, -1e16]);
+/// let b = Vector::<i64>::new(vec![1, 1, 1]);
+/// assert_eq!(norm::dot_compensated(&a, &b), Ok(1.0));
+/// ```
+pub fn dot_compensated<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    a.check_dimensions(b)?;
+    let (sum, compensation) = a.components().iter().zip(b.components()).fold(
+        (0.0, 0.0),
+        |(sum, compensation), (&x, &y)| {
+            let (x, y) = (x.as_f64(), y.as_f64());
+            let product = x * y;
+            let product_error = x.mul_add(y, -product);
+            let (total, sum_error) = two_sum(sum, product);
+            (total, compensation + (product_error + sum_error))
+        },
+    );
+    Ok(sum + compensation)
+}
+
+/// Dot product with pairwise summation of the products
+pub fn dot_pairwise<A: Component, B: Component>(
+    a: &Vector<A>,
+    b: &Vector<B>,
+) -> Result<f64, VectorError> {
+    a.check_dimensions(b)?;
+    let products: Vec<f64> = a
+        .components()
+        .iter()
+        .zip(b.components())
+        .map(|(&x, &y)| x.as_f64() * y.as_f64())
+        .collect();
+    Ok(pairwise_sum(&products))
+}
+
+/// Sum of absolute values of the components
+pub fn l1<T: Component>(vector: &Vector<T>) -> f64 {
+    let absolute: Vec<f64> = magnitudes(vector).collect();
+    pairwise_sum(&absolute)
+}
+
+/// Euclidean length, does not overflow or underflow unless the result itself does
+/// ```
+/// use datas::norm;
+/// use datas::vector::Vector;
+/// let huge = Vector::<f64>::new(vec![3e200, 4e200]);
+/// assert!((norm::l2(&huge) / 5e200 - 1.0).abs() < 1e-15);
+/// let tiny = Vector::<f64>::new(vec![3e-200, 4e-200]);
+/// assert!((norm::l2(&tiny) / 5e-200 - 1.0).abs() < 1e-15);
+/// ```
+pub fn l2<T: Component>(vector: &Vector<T>) -> f64 {
+    lp(vector, 2.0)
+}
+
+/// Largest absolute value of the components, NaN if any component is NaN
+pub fn linf<T: Component>(vector: &Vector<T>) -> f64 {
+    magnitudes(vector).fold(0.0, nan_max)
+}
+
+/// Larger of `max` and `value` that keeps NaN, unlike `f64::max` which drops it
+pub(crate) fn nan_max(max: f64, value: f64) -> f64 {
+    if value > max || value.is_nan() {
+        value
+    } else {
+        max
+    }
+}
+
+/// `p`-th root of the sum of `p`-th powers of absolute values, NaN unless `p` is at least 1.
+/// `p = f64::INFINITY` gives `linf`
+pub fn lp<T: Component>(vector: &Vector<T>, p: f64) -> f64 {
+    if p.is_nan() || p < 1.0 {
+        return f64::NAN;
+    }
+    let max = linf(vector);
+    if p.is_infinite() || max == 0.0 || !max.is_finite() {
+        return max;
+    }
+    if p == 1.0 {
+        return l1(vector);
+    }
+    let powers: Vec<f64> = magnitudes(vector)
+        .map(|value| {
+            let scaled = value / max;
+            if p == 2.0 {
+                scaled * scaled
+            } else {
+                scaled.powf(p)
+            }
+        })
+        .collect();
+    let sum = pairwise_sum(&powers);
+    max * if p == 2.0 {
+        sum.sqrt()
+    } else {
+        sum.powf(1.0 / p)
+    }
+}
+
+fn magnitudes<T: Component>(vector: &Vector<T>) -> impl Iterator<Item = f64> + '_ {
+    vector
+        .components()
+        .iter()
+        .map(|&component| component.as_f64().abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::close_within;
+
+    #[test]
+    fn l2_extreme_magnitudes() {
+        // comparing ratios with 1 keeps the tolerance relative for tiny values
+        let huge = Vector::<f64>::new(vec![1e200, 1e200]);
+        assert!(close_within(
+            l2(&huge) / (std::f64::consts::SQRT_2 * 1e200),
+            1.0,
+            1e-15
+        ));
+        assert!(close_within(
+            huge.magnitude() / (std::f64::consts::SQRT_2 * 1e200),
+            1.0,
+            1e-15
+        ));
+        // subnormal components whose squares are zero in f64
+        let subnormal = Vector::<f64>::new(vec![3e-320, 4e-320]);
+        assert!(close_within(l2(&subnormal) / 5e-320, 1.0, 1e-3));
+        assert!(close_within(subnormal.magnitude() / 5e-320, 1.0, 1e-3));
+        let half = f64::MAX / 2.0;
+        let mixed = Vector::<f64>::new(vec![half, 1.0, -half]);
+        assert!(close_within(
+            l2(&mixed) / (std::f64::consts::SQRT_2 * half),
+            1.0,
+            1e-15
+        ));
+
+        assert_eq!(l2(&Vector::<f64>::new(vec![0.0, 0.0])), 0.0);
+        assert_eq!(l2(&Vector::<f64>::new(vec![])), 0.0);
+        assert_eq!(
+            l2(&Vector::<f64>::new(vec![1.0, f64::INFINITY])),
+            f64::INFINITY
+        );
+        assert!(l2(&Vector::<f64>::new(vec![f64::NAN, 1.0])).is_nan());
+        assert!(Vector::<f64>::new(vec![1.0, f64::NAN]).magnitude().is_nan());
+        let huge = Vector::<f32>::new(vec![3e30, 4e30]);
+        assert!(close_within(huge.magnitude() / 5e30, 1.0, 1e-6));
+        let tiny = Vector::<f32>::new(vec![3e-30, 4e-30]);
+        assert!(close_within(tiny.magnitude() / 5e-30, 1.0, 1e-6));
+        assert_eq!(
+            l2(&Vector::<i64>::new(vec![i64::MAX, i64::MAX])),
+            i64::MAX as f64 * std::f64::consts::SQRT_2
+        );
+        assert_eq!(
+            Vector::<i64>::new(vec![i64::MAX, i64::MAX]).magnitude(),
+            i64::MAX as f64 * std::f64::consts::SQRT_2
+        );
+    }
+
+    #[test]
+    fn long_vector_precision() {
+        let n = 1_000_000;
+        let vector = Vector::<f64>::new(vec![0.1; n]);
+        // sqrt(n * 0.1^2) with 0.1 rounded to f64 is 0.1 * 1000 up to one rounding
+        let expected = 0.1 * 1000.0;
+        assert!(close_within(l2(&vector) / expected, 1.0, f64::EPSILON));
+        assert!(close_within(l1(&vector) / (0.1 * n as f64), 1.0, 1e-15));
+    }
+
+    #[test]
+    fn other_norms() {
+        let vector = Vector::<i64>::new(vec![3, -4, 0, 12]);
+        assert_eq!(l1(&vector), 19.0);
+        assert_eq!(l2(&vector), 13.0);
+        assert_eq!(linf(&vector), 12.0);
+        assert_eq!(lp(&vector, 1.0), 19.0);
+        assert_eq!(lp(&vector, f64::INFINITY), 12.0);
+        let cube = 3f64.powi(3) + 4f64.powi(3) + 12f64.powi(3);
+        assert!(close_within(lp(&vector, 3.0) / cube.cbrt(), 1.0, 1e-15));
+        let huge = Vector::<f64>::new(vec![1e300, 1e300]);
+        assert!(close_within(
+            lp(&huge, 3.0) / (2f64.cbrt() * 1e300),
+            1.0,
+            1e-15
+        ));
+        assert!(lp(&vector, 0.5).is_nan());
+        assert!(lp(&vector, f64::NAN).is_nan());
+        assert!(linf(&Vector::<f64>::new(vec![1.0, f64::NAN, 2.0])).is_nan());
+    }
+
+    #[test]
+    fn compensated_summation() {
+        assert_eq!(kahan_sum(&[]), 0.0);
+        assert_eq!(pairwise_sum(&[]), 0.0);
+        let values = [1e100, 1.0, -1e100, 1.0];
+        assert_eq!(kahan_sum(&values), 2.0);
+        assert_eq!(values.iter().sum::<f64>(), 1.0);
+        let small: Vec<f64> = (1..=10_000).map(|i| 1.0 / i as f64).collect();
+        let reference = kahan_sum(&small);
+        assert!(close_within(pairwise_sum(&small) / reference, 1.0, 1e-15));
+    }
+
+    #[test]
+    fn accurate_dot_products() {
+        // condition number around 1e32, naive summation returns 0
+        let a = Vector::<f64>::new(vec![1e16, 1.0, -1e16, 0.5]);
+        let b = Vector::<f64>::new(vec![1.0, 1.0, 1.0, 3.0]);
+        assert_eq!(dot_compensated(&a, &b), Ok(2.5));
+        // products with rounding errors that cancel only when kept exactly
+        let x = 1.0 + f64::EPSILON;
+        let a = Vector::<f64>::new(vec![x, -1.0]);
+        let b = Vector::<f64>::new(vec![x, 1.0 + 2.0 * f64::EPSILON]);
+        assert_eq!(dot_compensated(&a, &b), Ok(f64::EPSILON * f64::EPSILON));
+        let vector = Vector::<i64>::new(vec![1, 2, 3]);
+        assert_eq!(dot_pairwise(&vector, &vector), Ok(14.0));
+        let short = Vector::<i64>::new(vec![1, 2]);
+        let mismatch = Err(VectorError::DimensionMismatch {
+            expected: 3,
+            actual: 2,
+        });
+        assert_eq!(dot_compensated(&vector, &short), mismatch);
+        assert_eq!(dot_pairwise(&vector, &short), mismatch);
+    }
+}
@@ -0,0 +1,778 @@
+use std::io::{Read, Write};
+
+use crate::io::IoError;
+use crate::matrix::Matrix;
+use crate::vector::Vector;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Largest number of rows of a matrix without columns, whose empty payload cannot bound them
+const MAX_EMPTY_ROWS: usize = 1 << 28;
+
+/// Element types that can be stored in `.npy` files
+pub trait NpyElement: Copy {
+    /// Little endian NumPy dtype descriptor written for this type
+    const DESCR: &'static str;
+
+    /// Little endian bytes of the value
+    fn to_le_bytes(self) -> [u8; 8];
+
+    /// Converts value read from an integer dtype, `None` if it does not fit
+    fn from_int(value: i128) -> Option<Self>;
+
+    /// Converts value read from a floating point dtype, `None` if not representable
+    fn from_float(value: f64) -> Option<Self>;
+}
+
+impl NpyElement for i64 {
+    const DESCR: &'static str = "<i8";
+
+    fn to_le_bytes(self) -> [u8; 8] {
+        i64::to_le_bytes(self)
+    }
+
+    fn from_int(value: i128) -> Option<Self> {
+        i64::try_from(value).ok()
+    }
+
+    fn from_float(_: f64) -> Option<Self> {
+        None
+    }
+}
+
+impl NpyElement for f64 {
+    const DESCR: &'static str = "<f8";
+
+    fn to_le_bytes(self) -> [u8; 8] {
+        f64::to_le_bytes(self)
+    }
+
+    fn from_int(value: i128) -> Option<Self> {
+        Some(value as f64)
+    }
+
+    fn from_float(value: f64) -> Option<Self> {
+        Some(value)
+    }
+}
+
+/// Structures that can be stored as NumPy arrays
+pub trait NpyArray: Sized {
+    type Element: NpyElement;
+
+    /// Shape written to the header
+    fn shape(&self) -> Vec<usize>;
+
+    /// Elements in C (row-major) order
+    fn values(&self) -> Vec<Self::Element>;
+
+    /// Builds structure from shape and elements in C order
+    fn from_values(shape: &[usize], values: Vec<Self::Element>) -> Result<Self, IoError>;
+}
+
+impl<T: NpyElement> NpyArray for Matrix<T> {
+    type Element = T;
+
+    fn shape(&self) -> Vec<usize> {
+        vec![self.rows() as usize, self.cols() as usize]
+    }
+
+    fn values(&self) -> Vec<T> {
+        self.data().iter().flatten().copied().collect()
+    }
+
+    fn from_values(shape: &[usize], values: Vec<T>) -> Result<Self, IoError> {
+        let (rows, cols) = match *shape {
+            [rows, cols] => (rows, cols),
+            _ => return Err(shape_error(shape, 2)),
+        };
+        let data = if cols == 0 {
+            if rows > MAX_EMPTY_ROWS {
+                return Err(format_error("too many rows without columns"));
+            }
+            vec![Vec::new(); rows]
+        } else {
+            values.chunks(cols).map(|row| row.to_vec()).collect()
+        };
+        Ok(Matrix::from_rows(data)?)
+    }
+}
+
+impl<T: NpyElement> NpyArray for Vector<T> {
+    type Element = T;
+
+    fn shape(&self) -> Vec<usize> {
+        vec![self.dimensions() as usize]
+    }
+
+    fn values(&self) -> Vec<T> {
+        self.components().to_vec()
+    }
+
+    fn from_values(shape: &[usize], values: Vec<T>) -> Result<Self, IoError> {
+        if shape.len() != 1 {
+            return Err(shape_error(shape, 1));
+        }
+        Ok(Vector::from_components(values))
+    }
+}
+
+fn format_error(message: impl Into<String>) -> IoError {
+    IoError::Format(message.into())
+}
+
+fn shape_error(shape: &[usize], expected: usize) -> IoError {
+    format_error(format!(
+        "expected {} dimensional array, found shape {:?}",
+        expected, shape
+    ))
+}
+
+/// Decoded `descr` of the header
+struct DType {
+    big_endian: bool,
+    kind: char,
+    size: usize,
+}
+
+impl DType {
+    fn parse(descr: &str) -> Result<Self, IoError> {
+        let unsupported = || format_error(format!("unsupported dtype `{}`", descr));
+        let mut chars = descr.chars();
+        let big_endian = match chars.next().ok_or_else(unsupported)? {
+            '<' | '|' => false,
+            '>' => true,
+            '=' => cfg!(target_endian = "big"),
+            _ => return Err(unsupported()),
+        };
+        let kind = chars.next().ok_or_else(unsupported)?;
+        let size: usize = chars.as_str().parse().map_err(|_| unsupported())?;
+        let supported = match kind {
+            'f' => matches!(size, 4 | 8),
+            'i' | 'u' => matches!(size, 1 | 2 | 4 | 8),
+            'b' => size == 1,
+            _ => false,
+        };
+        if !supported {
+            return Err(unsupported());
+        }
+        Ok(Self {
+            big_endian,
+            kind,
+            size,
+        })
+    }
+
+    fn decode<T: NpyElement>(&self, bytes: &[u8]) -> Option<T> {
+        let mut buffer = [0u8; 8];
+        if self.big_endian {
+            buffer[..self.size].copy_from_slice(bytes);
+            buffer[..self.size].reverse();
+        } else {
+            buffer[..self.size].copy_from_slice(bytes);
+        }
+        match (self.kind, self.size) {
+            ('f', 4) => {
+                T::from_float(
+                    f32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+                )
+            }
+            ('f', _) => T::from_float(f64::from_le_bytes(buffer)),
+            ('u', _) | ('b', _) => T::from_int(u64::from_le_bytes(buffer) as i128),
+            (_, size) => {
+                // sign extend the value read into the low bytes
+                let shift = 64 - 8 * size as u32;
+                T::from_int(((i64::from_le_bytes(buffer) << shift) >> shift) as i128)
+            }
+        }
+    }
+}
+
+/// Finds raw text of the value stored under `key` in the Python dict literal of the header
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, IoError> {
+    let missing = || format_error(format!("header is missing `{}`", key));
+    let start = header
+        .find(&format!("'{}'", key))
+        .or_else(|| header.find(&format!("\"{}\"", key)))
+        .ok_or_else(missing)?;
+    let rest = header[start + key.len() + 2..].trim_start();
+    let rest = rest.strip_prefix(':').ok_or_else(missing)?.trim_start();
+    let end = match rest.chars().next() {
+        Some(quote @ ('\'' | '"')) => rest[1..].find(quote).map(|i| i + 2),
+        Some('(') => rest.find(')').map(|i| i + 1),
+        Some(_) => rest.find([',', '}']),
+        None => None,
+    }
+    .ok_or_else(missing)?;
+    Ok(rest[..end].trim())
+}
+
+/// Reads `len` bytes, growing the buffer as data arrives rather than trusting `len` up front
+fn read_len<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>, IoError> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(bytes)
+}
+
+/// Reads a NumPy `.npy` array into a `Matrix` (2 dimensional) or `Vector` (1 dimensional)
+/// ```
+/// use datas::npy::{read_npy, write_npy};
+/// use datas::vector::Vector;
+/// let f_vector = Vector::<f64>::new(vec![1.5, 2.5]);
+/// let mut out = Vec::new();
+/// write_npy(&f_vector, &mut out).unwrap();
+/// let read: Vector<f64> = read_npy(out.as_slice()).unwrap();
+/// assert_eq!(read, f_vector);
+/// ```
+pub fn read_npy<A: NpyArray, R: Read>(mut reader: R) -> Result<A, IoError> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic[..6] != MAGIC {
+        return Err(format_error("missing NumPy magic string"));
+    }
+    let header_len = match magic[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        version => {
+            return Err(format_error(format!(
+                "unsupported format version {}",
+                version
+            )))
+        }
+    };
+    let header = read_len(&mut reader, header_len)?;
+    let header = String::from_utf8(header).map_err(|_| format_error("header is not UTF-8"))?;
+
+    let descr = header_value(&header, "descr")?;
+    let dtype = DType::parse(descr.trim_matches(|c| c == '\'' || c == '"'))?;
+    let fortran_order = match header_value(&header, "fortran_order")? {
+        "True" => true,
+        "False" => false,
+        other => return Err(format_error(format!("invalid fortran_order `{}`", other))),
+    };
+    let shape = header_value(&header, "shape")?;
+    let shape = shape
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| {
+            dim.trim_end_matches('L')
+                .parse::<usize>()
+                .map_err(|_| format_error(format!("invalid shape `{}`", shape)))
+        })
+        .collect::<Result<Vec<usize>, IoError>>()?;
+
+    let len = shape
+        .iter()
+        .try_fold(dtype.size, |len, &dim| len.checked_mul(dim))
+        .ok_or_else(|| format_error("array size overflows"))?;
+    let bytes = read_len(&mut reader, len)?;
+    let values = bytes
+        .chunks(dtype.size)
+        .map(|chunk| {
+            dtype
+                .decode(chunk)
+                .ok_or_else(|| format_error(format!("dtype `{}` does not fit element type", descr)))
+        })
+        .collect::<Result<Vec<A::Element>, IoError>>()?;
+
+    let values = if fortran_order && shape.len() == 2 {
+        let (rows, cols) = (shape[0], shape[1]);
+        (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| col * rows + row))
+            .map(|index| values[index])
+            .collect()
+    } else {
+        values
+    };
+    A::from_values(&shape, values)
+}
+
+/// Writes a `Matrix` or `Vector` as NumPy `.npy` array in C order
+/// ```
+/// use datas::matrix::Matrix;
+/// use datas::npy::write_npy;
+/// let matrix = Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap();
+/// let mut out = Vec::new();
+/// write_npy(&matrix, &mut out).unwrap();
+/// assert_eq!(out.len(), 128 + 4 * 8);
+/// ```
+pub fn write_npy<A: NpyArray, W: Write>(array: &A, mut writer: W) -> Result<(), IoError> {
+    let shape = array.shape();
+    let shape = match shape.as_slice() {
+        [len] => format!("({},)", len),
+        dims => format!(
+            "({})",
+            dims.iter()
+                .map(|dim| dim.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        A::Element::DESCR,
+        shape
+    );
+    // magic, version and length take 10 bytes, whole preamble is padded to 64 bytes
+    let padding = 63 - (10 + header.len()) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[1, 0])?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for value in array.values() {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+struct NpzEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Writer of uncompressed `.npz` archives, as produced by `numpy.savez`
+/// ```
+/// use datas::npy::{NpzArchive, NpzWriter};
+/// use datas::vector::Vector;
+/// let mut writer = NpzWriter::new(Vec::new());
+/// writer.add("x", &Vector::<i64>::new(vec![1, 2])).unwrap();
+/// let bytes = writer.finish().unwrap();
+/// let archive = NpzArchive::read(bytes.as_slice()).unwrap();
+/// assert_eq!(archive.names(), vec!["x"]);
+/// ```
+pub struct NpzWriter<W: Write> {
+    writer: W,
+    entries: Vec<NpzEntry>,
+    offset: u64,
+}
+
+impl<W: Write> NpzWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            entries: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Stores array under `name`, the `.npy` extension is appended like NumPy does
+    pub fn add<A: NpyArray>(&mut self, name: &str, array: &A) -> Result<(), IoError> {
+        let mut data = Vec::new();
+        write_npy(array, &mut data)?;
+        let name = format!("{}.npy", name);
+        let too_large = || format_error("archive exceeds 4 GiB");
+        let entry = NpzEntry {
+            crc: crc32(&data),
+            size: u32::try_from(data.len()).map_err(|_| too_large())?,
+            offset: u32::try_from(self.offset).map_err(|_| too_large())?,
+            name,
+        };
+
+        let mut header = Vec::with_capacity(30 + entry.name.len());
+        header.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        header.extend_from_slice(&0u16.to_le_bytes()); // flags
+        header.extend_from_slice(&0u16.to_le_bytes()); // stored, no compression
+        header.extend_from_slice(&0u16.to_le_bytes()); // time
+        header.extend_from_slice(&0x21u16.to_le_bytes()); // date, 1980-01-01
+        header.extend_from_slice(&entry.crc.to_le_bytes());
+        header.extend_from_slice(&entry.size.to_le_bytes());
+        header.extend_from_slice(&entry.size.to_le_bytes());
+        header.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        header.extend_from_slice(entry.name.as_bytes());
+
+        self.writer.write_all(&header)?;
+        self.writer.write_all(&data)?;
+        self.offset += (header.len() + data.len()) as u64;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Writes central directory and returns the underlying writer
+    pub fn finish(mut self) -> Result<W, IoError> {
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            directory.extend_from_slice(&20u16.to_le_bytes()); // version made by
+            directory.extend_from_slice(&20u16.to_le_bytes()); // version needed
+            directory.extend_from_slice(&0u16.to_le_bytes()); // flags
+            directory.extend_from_slice(&0u16.to_le_bytes()); // stored, no compression
+            directory.extend_from_slice(&0u16.to_le_bytes()); // time
+            directory.extend_from_slice(&0x21u16.to_le_bytes()); // date, 1980-01-01
+            directory.extend_from_slice(&entry.crc.to_le_bytes());
+            directory.extend_from_slice(&entry.size.to_le_bytes());
+            directory.extend_from_slice(&entry.size.to_le_bytes());
+            directory.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0u8; 12]); // extra, comment, disk, attributes
+            directory.extend_from_slice(&entry.offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+        let offset =
+            u32::try_from(self.offset).map_err(|_| format_error("archive exceeds 4 GiB"))?;
+        // 0xFFFF marks zip64 archives, larger counts would need a zip64 end record
+        let count = u16::try_from(self.entries.len())
+            .ok()
+            .filter(|&count| count != 0xFFFF)
+            .ok_or_else(|| format_error("archive exceeds 65534 entries"))?;
+
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // disk
+        end.extend_from_slice(&0u16.to_le_bytes()); // disk with directory
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        end.extend_from_slice(&offset.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+        self.writer.write_all(&directory)?;
+        self.writer.write_all(&end)?;
+        Ok(self.writer)
+    }
+}
+
+/// Uncompressed `.npz` archive loaded into memory
+#[derive(Debug)]
+pub struct NpzArchive {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+/// Adds offset or size read from the archive, rejecting values past the addressable range
+fn checked_offset(at: usize, delta: u64) -> Result<usize, IoError> {
+    usize::try_from(delta)
+        .ok()
+        .and_then(|delta| at.checked_add(delta))
+        .ok_or_else(|| format_error("offset out of bounds"))
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<u16, IoError> {
+    bytes
+        .get(at..checked_offset(at, 2)?)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| format_error("truncated archive"))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<u32, IoError> {
+    bytes
+        .get(at..checked_offset(at, 4)?)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format_error("truncated archive"))
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> Result<u64, IoError> {
+    Ok(read_u32(bytes, at)? as u64 | (read_u32(bytes, checked_offset(at, 4)?)? as u64) << 32)
+}
+
+impl NpzArchive {
+    /// Reads archive, entries must be stored without compression
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, IoError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let end = (0..bytes.len().saturating_sub(21))
+            .rev()
+            .find(|&at| read_u32(&bytes, at).ok() == Some(0x0605_4b50))
+            .ok_or_else(|| format_error("missing end of central directory"))?;
+        let mut count = read_u16(&bytes, end + 10)? as u64;
+        let mut directory = read_u32(&bytes, end + 16)? as u64;
+        if count == 0xFFFF || directory == 0xFFFF_FFFF {
+            // zip64 archives, e.g. written by numpy.savez, keep real values in a separate record
+            let locator = end
+                .checked_sub(20)
+                .filter(|&at| read_u32(&bytes, at).ok() == Some(0x0706_4b50))
+                .ok_or_else(|| format_error("missing zip64 locator"))?;
+            let record = checked_offset(0, read_u64(&bytes, locator + 8)?)?;
+            if read_u32(&bytes, record)? != 0x0606_4b50 {
+                return Err(format_error("missing zip64 end of central directory"));
+            }
+            count = read_u64(&bytes, checked_offset(record, 32)?)?;
+            directory = read_u64(&bytes, checked_offset(record, 48)?)?;
+        }
+
+        let mut entries = Vec::new();
+        let mut at = checked_offset(0, directory)?;
+        for _ in 0..count {
+            if read_u32(&bytes, at)? != 0x0201_4b50 {
+                return Err(format_error("invalid central directory entry"));
+            }
+            let method = read_u16(&bytes, at + 10)?;
+            let crc = read_u32(&bytes, at + 16)?;
+            let mut size = read_u32(&bytes, at + 20)? as u64;
+            let mut uncompressed = read_u32(&bytes, at + 24)? as u64;
+            let name_len = read_u16(&bytes, at + 28)? as usize;
+            let extra_len = read_u16(&bytes, at + 30)? as usize;
+            let comment_len = read_u16(&bytes, at + 32)? as usize;
+            let mut offset = read_u32(&bytes, at + 42)? as u64;
+            let name_start = checked_offset(at, 46)?;
+            let mut extra = checked_offset(name_start, name_len as u64)?;
+            let name = bytes
+                .get(name_start..extra)
+                .ok_or_else(|| format_error("truncated archive"))?;
+            let name = String::from_utf8_lossy(name).into_owned();
+
+            // zip64 extra field holds the values saturated in the fixed part, in this order
+            let extra_end = checked_offset(extra, extra_len as u64)?;
+            while extra + 4 <= extra_end {
+                let id = read_u16(&bytes, extra)?;
+                let len = read_u16(&bytes, extra + 2)? as usize;
+                if id == 0x0001 {
+                    let mut field = extra + 4;
+                    for value in [&mut uncompressed, &mut size, &mut offset] {
+                        if *value == 0xFFFF_FFFF {
+                            *value = read_u64(&bytes, field)?;
+                            field += 8;
+                        }
+                    }
+                }
+                extra += 4 + len;
+            }
+            at = checked_offset(extra_end, comment_len as u64)?;
+
+            if method != 0 || size != uncompressed {
+                return Err(format_error(format!("entry `{}` is compressed", name)));
+            }
+            let local = checked_offset(0, offset)?;
+            if read_u32(&bytes, local)? != 0x0403_4b50 {
+                return Err(format_error("invalid local file header"));
+            }
+            let start = checked_offset(local, 30)?;
+            let start = checked_offset(start, read_u16(&bytes, local + 26)? as u64)?;
+            let start = checked_offset(start, read_u16(&bytes, local + 28)? as u64)?;
+            let data = bytes
+                .get(start..checked_offset(start, size)?)
+                .ok_or_else(|| format_error("truncated archive"))?;
+            if crc32(data) != crc {
+                return Err(format_error(format!("checksum mismatch in `{}`", name)));
+            }
+            let name = name.strip_suffix(".npy").unwrap_or(&name).to_string();
+            entries.push((name, data.to_vec()));
+        }
+        Ok(Self { entries })
+    }
+
+    /// Names of stored arrays without the `.npy` extension
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Reads array stored under `name`
+    pub fn get<A: NpyArray>(&self, name: &str) -> Result<A, IoError> {
+        let (_, data) = self
+            .entries
+            .iter()
+            .find(|(entry, _)| entry == name)
+            .ok_or_else(|| format_error(format!("no array named `{}`", name)))?;
+        read_npy(data.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn npy_bytes(descr: &str, fortran_order: bool, shape: &str, data: &[u8]) -> Vec<u8> {
+        let order = if fortran_order { "True" } else { "False" };
+        let header = format!(
+            "{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}\n",
+            descr, order, shape
+        );
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn npy_matrix_roundtrip() {
+        let matrix = Matrix::<f64>::new(vec![vec![1.0, -2.5, 3.0], vec![4.0, 5.0, 6.25]]).unwrap();
+        let mut out = Vec::new();
+        write_npy(&matrix, &mut out).unwrap();
+        let preamble = out.len() - 6 * 8;
+        assert_eq!(preamble % 64, 0);
+        assert!(String::from_utf8_lossy(&out[10..preamble]).contains("'shape': (2, 3)"));
+        let read: Matrix<f64> = read_npy(out.as_slice()).unwrap();
+        assert_eq!(read, matrix);
+
+        let matrix = Matrix::<i64>::new(vec![vec![i64::MIN], vec![i64::MAX]]).unwrap();
+        let mut out = Vec::new();
+        write_npy(&matrix, &mut out).unwrap();
+        let read: Matrix<i64> = read_npy(out.as_slice()).unwrap();
+        assert_eq!(read, matrix);
+    }
+
+    #[test]
+    fn npy_fortran_order() {
+        let data: Vec<u8> = [1i64, 4, 2, 5, 3, 6]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let bytes = npy_bytes("<i8", true, "(2, 3)", &data);
+        let read: Matrix<i64> = read_npy(bytes.as_slice()).unwrap();
+        assert_eq!(
+            read,
+            Matrix::<i64>::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap()
+        );
+    }
+
+    #[test]
+    fn npy_dtype_conversion() {
+        let data: Vec<u8> = [1.5f64, -2.0]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let bytes = npy_bytes(">f8", false, "(2,)", &data);
+        let read: Vector<f64> = read_npy(bytes.as_slice()).unwrap();
+        assert_eq!(read, Vector::<f64>::new(vec![1.5, -2.0]));
+
+        let data: Vec<u8> = [-3i16, 7].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let bytes = npy_bytes("<i2", false, "(2,)", &data);
+        let read: Vector<i64> = read_npy(bytes.as_slice()).unwrap();
+        assert_eq!(read, Vector::<i64>::new(vec![-3, 7]));
+        let read: Vector<f64> = read_npy(bytes.as_slice()).unwrap();
+        assert_eq!(read, Vector::<f64>::new(vec![-3.0, 7.0]));
+
+        let bytes = npy_bytes("<f4", false, "(1,)", &0.5f32.to_le_bytes());
+        assert!(matches!(
+            read_npy::<Vector<i64>, _>(bytes.as_slice()),
+            Err(IoError::Format(_))
+        ));
+        let bytes = npy_bytes("<c16", false, "(1,)", &[0; 16]);
+        assert!(matches!(
+            read_npy::<Vector<f64>, _>(bytes.as_slice()),
+            Err(IoError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn npy_shape_mismatch() {
+        let mut out = Vec::new();
+        write_npy(&Vector::<i64>::new(vec![1, 2]), &mut out).unwrap();
+        assert!(matches!(
+            read_npy::<Matrix<i64>, _>(out.as_slice()),
+            Err(IoError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn npy_untrusted_shape() {
+        let bytes = npy_bytes("<i8", false, "(4611686018427387904, 4)", &[]);
+        assert!(matches!(
+            read_npy::<Matrix<i64>, _>(bytes.as_slice()),
+            Err(IoError::Format(_))
+        ));
+        let bytes = npy_bytes("<i8", false, "(1000000000000,)", &[0; 8]);
+        assert!(matches!(
+            read_npy::<Vector<i64>, _>(bytes.as_slice()),
+            Err(IoError::Io(_))
+        ));
+        // no columns leave nothing to read, the rows still have to be allocated
+        let bytes = npy_bytes("<i8", false, "(1152921504606846976, 0)", &[]);
+        assert!(matches!(
+            read_npy::<Matrix<i64>, _>(bytes.as_slice()),
+            Err(IoError::Format(_))
+        ));
+        let bytes = npy_bytes("<i8", false, "(3, 0)", &[]);
+        let read: Matrix<i64> = read_npy(bytes.as_slice()).unwrap();
+        assert_eq!((read.rows(), read.cols()), (3, 0));
+    }
+
+    #[test]
+    fn npz_roundtrip() {
+        let matrix = Matrix::<f64>::new(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap();
+        let i_vector = Vector::<i64>::new(vec![5, 6, 7]);
+        let mut writer = NpzWriter::new(Vec::new());
+        writer.add("m", &matrix).unwrap();
+        writer.add("v", &i_vector).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let archive = NpzArchive::read(bytes.as_slice()).unwrap();
+        assert_eq!(archive.names(), vec!["m", "v"]);
+        assert_eq!(archive.get::<Matrix<f64>>("m").unwrap(), matrix);
+        assert_eq!(archive.get::<Vector<i64>>("v").unwrap(), i_vector);
+        assert!(archive.get::<Vector<i64>>("x").is_err());
+
+        let mut corrupted = bytes.clone();
+        corrupted[100] ^= 0xFF;
+        assert!(NpzArchive::read(corrupted.as_slice()).is_err());
+
+        // zip64 locator pointing past the end of the address space
+        let (body, end) = bytes.split_at(bytes.len() - 22);
+        let mut corrupted = body.to_vec();
+        corrupted.extend_from_slice(&0x0706_4b50u32.to_le_bytes());
+        corrupted.extend_from_slice(&0u32.to_le_bytes());
+        corrupted.extend_from_slice(&u64::MAX.to_le_bytes());
+        corrupted.extend_from_slice(&1u32.to_le_bytes());
+        corrupted.extend_from_slice(end);
+        let count = corrupted.len() - 12;
+        corrupted[count..count + 2].copy_from_slice(&0xFFFFu16.to_le_bytes());
+        assert!(matches!(
+            NpzArchive::read(corrupted.as_slice()),
+            Err(IoError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn npz_entry_limit() {
+        let mut writer = NpzWriter::new(std::io::sink());
+        let i_vector = Vector::<i64>::new(vec![]);
+        for index in 0..0xFFFF {
+            writer.add(&index.to_string(), &i_vector).unwrap();
+        }
+        assert!(matches!(writer.finish(), Err(IoError::Format(_))));
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
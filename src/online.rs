@@ -0,0 +1,341 @@
+//! Statistics of data streams, updated one value at a time in constant memory.
+//!
+//! `OnlineStats` keeps the count, mean and central moment sums up to the fourth order, updated
+//! with Welford's algorithm and combined with the pairwise formulas of Pébay, so partial results
+//! computed in parallel merge into the statistics of the whole stream.
+
+use crate::sketch::widen;
+use crate::StatsError;
+
+/// Accumulator of count, mean, variance, skewness, kurtosis, minimum and maximum
+/// ```
+/// use datas::online::OnlineStats;
+/// let mut stats = OnlineStats::new();
+/// stats.extend([2.0, 4.0, 4.0, 4.0]);
+/// let rest: OnlineStats = [5.0, 5.0, 7.0, 9.0].into_iter().collect();
+/// stats.merge(&rest).unwrap();
+/// assert_eq!(stats.count(), 8);
+/// assert_eq!(stats.mean(), Ok(5.0));
+/// assert_eq!(stats.variance(), Ok(4.0));
+/// assert_eq!(stats.max(), Ok(9.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OnlineStats {
+    count: u64,
+    mean: f64,
+    /// Sums of the second, third and fourth powers of deviations from the mean
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    /// Smallest and largest value, `None` until a value is pushed
+    range: Option<(f64, f64)>,
+    /// Position of the first NaN pushed, statistics are undefined after it
+    nan_index: Option<u64>,
+}
+
+impl Default for OnlineStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OnlineStats {
+    /// Accumulator of no values
+    pub fn new() -> Self {
+        Self::from_parts(0, [0.0; 4], None, None)
+    }
+
+    /// Builds accumulator from validated parts, shared by the constructor and deserialization
+    pub(crate) fn from_parts(
+        count: u64,
+        moments: [f64; 4],
+        range: Option<(f64, f64)>,
+        nan_index: Option<u64>,
+    ) -> Self {
+        let [mean, m2, m3, m4] = moments;
+        Self {
+            count,
+            mean,
+            m2,
+            m3,
+            m4,
+            range,
+            nan_index,
+        }
+    }
+
+    /// Adds a value, a NaN makes every statistic fail with `ContainsNaN`
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            self.nan_index.get_or_insert(self.count);
+        }
+        let previous = self.count as f64;
+        self.count += 1;
+        let n = self.count as f64;
+        let delta = value - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term = delta * delta_n * previous;
+        self.mean += delta_n;
+        self.m4 += term * delta_n2 * (n * n - 3.0 * n + 3.0) + 6.0 * delta_n2 * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term;
+        self.range = widen(self.range, Some((value, value)));
+    }
+
+    /// Combines statistics of another part of the stream, as if its values were pushed after these.
+    /// Fails with `CountOverflow` and keeps these statistics when the counts add up to more than
+    /// `u64::MAX`, which only deserialized states can reach
+    pub fn merge(&mut self, other: &OnlineStats) -> Result<(), StatsError> {
+        let count = self
+            .count
+            .checked_add(other.count)
+            .ok_or(StatsError::CountOverflow)?;
+        if other.count == 0 {
+            return Ok(());
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return Ok(());
+        }
+        if let Some(index) = other.nan_index {
+            self.nan_index.get_or_insert(self.count + index);
+        }
+        let (a, b) = (self.count as f64, other.count as f64);
+        let n = a + b;
+        let delta = other.mean - self.mean;
+        let delta2 = delta * delta;
+        let m2 = self.m2 + other.m2 + delta2 * a * b / n;
+        let m3 = self.m3
+            + other.m3
+            + delta2 * delta * a * b * (a - b) / (n * n)
+            + 3.0 * delta * (a * other.m2 - b * self.m2) / n;
+        let m4 = self.m4
+            + other.m4
+            + delta2 * delta2 * a * b * (a * a - a * b + b * b) / (n * n * n)
+            + 6.0 * delta2 * (a * a * other.m2 + b * b * self.m2) / (n * n)
+            + 4.0 * delta * (a * other.m3 - b * self.m3) / n;
+        self.mean += delta * b / n;
+        self.m2 = m2;
+        self.m3 = m3;
+        self.m4 = m4;
+        self.count = count;
+        self.range = widen(self.range, other.range);
+        Ok(())
+    }
+
+    /// Number of values pushed
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Result<f64, StatsError> {
+        self.check(1)?;
+        Ok(self.mean)
+    }
+
+    /// Population variance, divides by `n`
+    pub fn variance(&self) -> Result<f64, StatsError> {
+        self.variance_ddof(0)
+    }
+
+    /// Unbiased sample variance, divides by `n - 1`
+    pub fn sample_variance(&self) -> Result<f64, StatsError> {
+        self.variance_ddof(1)
+    }
+
+    /// Variance with `ddof` delta degrees of freedom, divides by `n - ddof`
+    pub fn variance_ddof(&self, ddof: u64) -> Result<f64, StatsError> {
+        self.check(ddof.saturating_add(1))?;
+        Ok(self.m2 / (self.count - ddof) as f64)
+    }
+
+    /// Population standard deviation
+    pub fn standard_deviation(&self) -> Result<f64, StatsError> {
+        self.variance().map(f64::sqrt)
+    }
+
+    pub fn sample_standard_deviation(&self) -> Result<f64, StatsError> {
+        self.sample_variance().map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Result<f64, StatsError> {
+        self.check(1)?;
+        Ok(self.range.map_or(f64::NAN, |(min, _)| min))
+    }
+
+    pub fn max(&self) -> Result<f64, StatsError> {
+        self.check(1)?;
+        Ok(self.range.map_or(f64::NAN, |(_, max)| max))
+    }
+
+    /// Population skewness `m3 / m2^(3/2)` of the central moments, NaN when all values are equal
+    pub fn skewness(&self) -> Result<f64, StatsError> {
+        self.check(1)?;
+        let n = self.count as f64;
+        Ok(n.sqrt() * self.m3 / self.m2.powf(1.5))
+    }
+
+    /// Population excess kurtosis `m4 / m2^2 - 3`, zero for normally distributed values and NaN
+    /// when all values are equal
+    pub fn kurtosis(&self) -> Result<f64, StatsError> {
+        self.check(1)?;
+        let n = self.count as f64;
+        Ok(n * self.m4 / (self.m2 * self.m2) - 3.0)
+    }
+
+    /// Fails unless at least `required` values without NaN were pushed
+    fn check(&self, required: u64) -> Result<(), StatsError> {
+        if let Some(index) = self.nan_index {
+            return Err(StatsError::ContainsNaN { index });
+        }
+        if self.count == 0 {
+            return Err(StatsError::Empty);
+        }
+        if self.count < required {
+            return Err(StatsError::InsufficientData {
+                required,
+                actual: self.count,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Extend<f64> for OnlineStats {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for value in values {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a> Extend<&'a f64> for OnlineStats {
+    fn extend<I: IntoIterator<Item = &'a f64>>(&mut self, values: I) {
+        self.extend(values.into_iter().copied());
+    }
+}
+
+impl FromIterator<f64> for OnlineStats {
+    fn from_iter<I: IntoIterator<Item = f64>>(values: I) -> Self {
+        let mut stats = OnlineStats::new();
+        stats.extend(values);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{close, Mean};
+
+    /// Skewness and excess kurtosis computed from two passes over the data
+    fn moments(data: &[f64]) -> (f64, f64) {
+        let n = data.len() as f64;
+        let mean = Mean::mean_f(data);
+        let central = |power| data.iter().map(|x| (x - mean).powi(power)).sum::<f64>() / n;
+        let m2 = central(2);
+        (central(3) / m2.powf(1.5), central(4) / (m2 * m2) - 3.0)
+    }
+
+    fn data() -> Vec<f64> {
+        (0..1000)
+            .map(|i| ((i * 37 % 101) as f64).powf(1.3) - 20.0)
+            .collect()
+    }
+
+    #[test]
+    fn matches_batch_statistics() {
+        let data = data();
+        let stats: OnlineStats = data.iter().copied().collect();
+        assert_eq!(stats.count(), 1000);
+        assert!(close(stats.mean().unwrap(), Mean::mean_f(&data)));
+        assert!(close(stats.variance().unwrap(), Mean::variance_f(&data)));
+        assert!(close(
+            stats.sample_standard_deviation().unwrap(),
+            Mean::sample_standard_deviation_f(&data)
+        ));
+        let (skewness, kurtosis) = moments(&data);
+        assert!(close(stats.skewness().unwrap(), skewness));
+        assert!(close(stats.kurtosis().unwrap(), kurtosis));
+        assert_eq!(stats.min(), Ok(-20.0));
+        assert_eq!(stats.max(), Ok(100f64.powf(1.3) - 20.0));
+    }
+
+    #[test]
+    fn merge_equals_sequential() {
+        let data = data();
+        let sequential: OnlineStats = data.iter().copied().collect();
+        for split in [0, 1, 7, 500, 999, 1000] {
+            let mut left: OnlineStats = data[..split].iter().copied().collect();
+            let right: OnlineStats = data[split..].iter().copied().collect();
+            left.merge(&right).unwrap();
+            assert_eq!(left.count(), sequential.count());
+            assert_eq!(left.min(), sequential.min());
+            assert_eq!(left.max(), sequential.max());
+            for (merged, expected) in [
+                (left.mean(), sequential.mean()),
+                (left.variance(), sequential.variance()),
+                (left.skewness(), sequential.skewness()),
+                (left.kurtosis(), sequential.kurtosis()),
+            ] {
+                assert!(close(merged.unwrap(), expected.unwrap()));
+            }
+        }
+        // many small partial results, as produced by parallel workers
+        let mut total = OnlineStats::new();
+        for chunk in data.chunks(3) {
+            let mut part = OnlineStats::new();
+            part.extend(chunk);
+            total.merge(&part).unwrap();
+        }
+        assert!(close(
+            total.kurtosis().unwrap(),
+            sequential.kurtosis().unwrap()
+        ));
+    }
+
+    #[test]
+    fn stable_with_large_offset() {
+        let stats: OnlineStats = [4.0, 7.0, 13.0, 16.0].iter().map(|x| x + 1e9).collect();
+        assert_eq!(stats.variance(), Ok(22.5));
+        assert_eq!(stats.sample_variance(), Ok(30.0));
+        assert!(stats.skewness().unwrap().abs() < 1e-6);
+    }
+
+    #[test]
+    fn invalid_states() {
+        let mut stats = OnlineStats::default();
+        assert_eq!(stats.mean(), Err(StatsError::Empty));
+        assert_eq!(stats.min(), Err(StatsError::Empty));
+        stats.push(3.0);
+        assert_eq!(stats.variance(), Ok(0.0));
+        assert_eq!(
+            stats.sample_variance(),
+            Err(StatsError::InsufficientData {
+                required: 2,
+                actual: 1
+            })
+        );
+        assert!(stats.skewness().unwrap().is_nan());
+        assert_eq!(
+            stats.variance_ddof(u64::MAX),
+            Err(StatsError::InsufficientData {
+                required: u64::MAX,
+                actual: 1
+            })
+        );
+
+        let mut with_nan: OnlineStats = [1.0, 2.0].into_iter().collect();
+        with_nan.extend([f64::NAN, 4.0]);
+        assert_eq!(with_nan.mean(), Err(StatsError::ContainsNaN { index: 2 }));
+        stats.merge(&with_nan).unwrap();
+        assert_eq!(stats.max(), Err(StatsError::ContainsNaN { index: 3 }));
+
+        let huge = OnlineStats::from_parts(u64::MAX, [1.0, 0.0, 0.0, 0.0], Some((1.0, 1.0)), None);
+        assert_eq!(stats.merge(&huge), Err(StatsError::CountOverflow));
+        assert_eq!(stats.count(), 5);
+    }
+}
@@ -0,0 +1,361 @@
+//! Sample quantiles under the nine definitions of Hyndman and Fan, numbered as the `type`
+//! argument of `quantile` in R.
+//!
+//! Single quantiles select the neighboring order statistics with quickselect in `O(n)`, several
+//! quantiles of the same data share one sort.
+
+use crate::vector::Component;
+use crate::StatsError;
+
+/// Definition of the sample quantile, `Linear` (R type 7) is the default of R and NumPy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuantileMethod {
+    /// Type 1, inverse of the empirical distribution function
+    InvertedCdf,
+    /// Type 2, like type 1 with averaging at discontinuities
+    AveragedInvertedCdf,
+    /// Type 3, nearest even order statistic (SAS definition 2)
+    ClosestObservation,
+    /// Type 4, linear interpolation of the empirical distribution function
+    InterpolatedInvertedCdf,
+    /// Type 5, piecewise linear with knots at the midpoints of the steps
+    Hazen,
+    /// Type 6, `p`-th quantile at rank `p(n + 1)` (Excel `PERCENTILE.EXC`)
+    Weibull,
+    /// Type 7, `p`-th quantile at rank `1 + p(n - 1)` (Excel `PERCENTILE.INC`)
+    #[default]
+    Linear,
+    /// Type 8, approximately median unbiased regardless of the distribution
+    MedianUnbiased,
+    /// Type 9, approximately unbiased for normally distributed data
+    NormalUnbiased,
+}
+
+impl QuantileMethod {
+    /// Method of the given R `type`, `None` outside of 1 to 9
+    /// ```
+    /// use datas::quantile::QuantileMethod;
+    /// assert_eq!(QuantileMethod::from_r_type(7), Some(QuantileMethod::Linear));
+    /// assert_eq!(QuantileMethod::from_r_type(10), None);
+    /// ```
+    pub fn from_r_type(r_type: u8) -> Option<QuantileMethod> {
+        use QuantileMethod::*;
+        let methods = [
+            InvertedCdf,
+            AveragedInvertedCdf,
+            ClosestObservation,
+            InterpolatedInvertedCdf,
+            Hazen,
+            Weibull,
+            Linear,
+            MedianUnbiased,
+            NormalUnbiased,
+        ];
+        methods.get((r_type as usize).checked_sub(1)?).copied()
+    }
+
+    /// R `type` of the method
+    pub fn r_type(self) -> u8 {
+        self as u8 + 1
+    }
+
+    /// 0 based indices of the order statistics around the `p` quantile of `len` sorted values and
+    /// the weight of the upper one
+    fn position(self, len: usize, p: f64) -> (usize, usize, f64) {
+        use QuantileMethod::*;
+        // same tolerance as R, so ranks that are integers in exact arithmetic are treated as such
+        const FUZZ: f64 = 4.0 * f64::EPSILON;
+        let n = len as f64;
+        let rank = match self {
+            InvertedCdf | AveragedInvertedCdf | InterpolatedInvertedCdf => n * p,
+            ClosestObservation => n * p - 0.5,
+            Hazen => n * p + 0.5,
+            Weibull => (n + 1.0) * p,
+            Linear => 1.0 + (n - 1.0) * p,
+            MedianUnbiased => (n + 1.0 / 3.0) * p + 1.0 / 3.0,
+            NormalUnbiased => (n + 0.25) * p + 0.375,
+        };
+        let j = (rank + FUZZ).floor();
+        let mut fraction = rank - j;
+        if fraction.abs() < FUZZ {
+            fraction = 0.0;
+        }
+        let weight = match self {
+            InvertedCdf if fraction == 0.0 => 0.0,
+            AveragedInvertedCdf if fraction == 0.0 => 0.5,
+            ClosestObservation if fraction == 0.0 && j % 2.0 == 0.0 => 0.0,
+            InvertedCdf | AveragedInvertedCdf | ClosestObservation => 1.0,
+            _ => fraction,
+        };
+        // `j` is the 1 based rank of the lower order statistic, outside of the data the nearest
+        // extreme is used
+        if j < 1.0 {
+            (0, 0, 0.0)
+        } else if j >= n {
+            (len - 1, len - 1, 0.0)
+        } else {
+            let lower = j as usize - 1;
+            (lower, lower + 1, weight)
+        }
+    }
+}
+
+/// Minimum, quartiles and maximum
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FiveNumberSummary {
+    pub min: f64,
+    pub lower_quartile: f64,
+    pub median: f64,
+    pub upper_quartile: f64,
+    pub max: f64,
+}
+
+/// `p` quantile of the data, `p` is a probability between 0 and 1
+/// ```
+/// use datas::quantile::{quantile, QuantileMethod};
+/// let latencies = [12, 15, 11, 90, 14, 13, 16, 12, 15, 250];
+/// assert_eq!(quantile(&latencies, 0.5, QuantileMethod::Linear), Ok(14.5));
+/// assert_eq!(quantile(&latencies, 0.75, QuantileMethod::Linear), Ok(15.75));
+/// assert_eq!(quantile(&latencies, 0.95, QuantileMethod::InvertedCdf), Ok(250.0));
+/// ```
+pub fn quantile<T: Component>(
+    data: &[T],
+    p: f64,
+    method: QuantileMethod,
+) -> Result<f64, StatsError> {
+    check_probability(p)?;
+    let mut values = prepared(data)?;
+    let (lower, upper, weight) = method.position(values.len(), p);
+    let (_, &mut low, above) = values.select_nth_unstable_by(lower, f64::total_cmp);
+    if upper == lower || weight == 0.0 {
+        return Ok(low);
+    }
+    let high = above.iter().copied().fold(f64::INFINITY, f64::min);
+    Ok(interpolate(low, high, weight))
+}
+
+/// Quantiles at every probability in `ps`, sorting the data once
+/// ```
+/// use datas::quantile::{quantiles, QuantileMethod};
+/// let data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+/// assert_eq!(
+///     quantiles(&data, &[0.25, 0.5, 0.75], QuantileMethod::Weibull),
+///     Ok(vec![2.25, 4.5, 6.75])
+/// );
+/// ```
+pub fn quantiles<T: Component>(
+    data: &[T],
+    ps: &[f64],
+    method: QuantileMethod,
+) -> Result<Vec<f64>, StatsError> {
+    for &p in ps {
+        check_probability(p)?;
+    }
+    let mut values = prepared(data)?;
+    values.sort_unstable_by(f64::total_cmp);
+    Ok(ps
+        .iter()
+        .map(|&p| quantile_sorted(&values, p, method))
+        .collect())
+}
+
+/// Quantile at `percent` between 0 and 100, a failure reports the probability `percent / 100`
+pub fn percentile<T: Component>(
+    data: &[T],
+    percent: f64,
+    method: QuantileMethod,
+) -> Result<f64, StatsError> {
+    quantile(data, percent / 100.0, method)
+}
+
+/// Interquartile range, difference of the 0.75 and 0.25 quantiles
+pub fn iqr<T: Component>(data: &[T], method: QuantileMethod) -> Result<f64, StatsError> {
+    let quartiles = quantiles(data, &[0.25, 0.75], method)?;
+    Ok(quartiles[1] - quartiles[0])
+}
+
+/// Minimum, quartiles and maximum of the data
+/// ```
+/// use datas::quantile::{five_number_summary, QuantileMethod};
+/// let summary = five_number_summary(&[7, 1, 3, 5, 9], QuantileMethod::Linear).unwrap();
+/// assert_eq!((summary.min, summary.median, summary.max), (1.0, 5.0, 9.0));
+/// assert_eq!((summary.lower_quartile, summary.upper_quartile), (3.0, 7.0));
+/// ```
+pub fn five_number_summary<T: Component>(
+    data: &[T],
+    method: QuantileMethod,
+) -> Result<FiveNumberSummary, StatsError> {
+    let values = quantiles(data, &[0.0, 0.25, 0.5, 0.75, 1.0], method)?;
+    Ok(FiveNumberSummary {
+        min: values[0],
+        lower_quartile: values[1],
+        median: values[2],
+        upper_quartile: values[3],
+        max: values[4],
+    })
+}
+
+/// `k`-th smallest value (0 based) with quickselect, reordering `data` so smaller values come
+/// before it and larger ones after it. `None` when `k` is out of bound
+/// ```
+/// use datas::quantile::select_nth;
+/// let mut data = [5.0, 1.0, 4.0, 2.0, 3.0];
+/// assert_eq!(select_nth(&mut data, 1), Some(2.0));
+/// assert_eq!(select_nth(&mut data, 5), None);
+/// ```
+pub fn select_nth(data: &mut [f64], k: usize) -> Option<f64> {
+    if k >= data.len() {
+        return None;
+    }
+    let (_, value, _) = data.select_nth_unstable_by(k, f64::total_cmp);
+    Some(*value)
+}
+
+/// Quantile of values sorted in increasing order, which must not be empty
+pub(crate) fn quantile_sorted(sorted: &[f64], p: f64, method: QuantileMethod) -> f64 {
+    let (lower, upper, weight) = method.position(sorted.len(), p);
+    if weight == 0.0 {
+        return sorted[lower];
+    }
+    interpolate(sorted[lower], sorted[upper], weight)
+}
+
+fn interpolate(low: f64, high: f64, weight: f64) -> f64 {
+    if weight == 1.0 {
+        high
+    } else {
+        low + weight * (high - low)
+    }
+}
+
+pub(crate) fn check_probability(p: f64) -> Result<(), StatsError> {
+    if !(0.0..=1.0).contains(&p) {
+        return Err(StatsError::InvalidProbability { probability: p });
+    }
+    Ok(())
+}
+
+/// Data as `f64`, failing on empty data or NaN
+fn prepared<T: Component>(data: &[T]) -> Result<Vec<f64>, StatsError> {
+    let values: Vec<f64> = data.iter().map(|&x| x.as_f64()).collect();
+    crate::check_f(&values)?;
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mean;
+
+    const METHODS: [QuantileMethod; 9] = [
+        QuantileMethod::InvertedCdf,
+        QuantileMethod::AveragedInvertedCdf,
+        QuantileMethod::ClosestObservation,
+        QuantileMethod::InterpolatedInvertedCdf,
+        QuantileMethod::Hazen,
+        QuantileMethod::Weibull,
+        QuantileMethod::Linear,
+        QuantileMethod::MedianUnbiased,
+        QuantileMethod::NormalUnbiased,
+    ];
+
+    #[test]
+    fn matches_r() {
+        // quantile(1:10, c(0.1, 0.25, 0.5, 0.9), type = t) in R
+        let data: Vec<i64> = (1..=10).collect();
+        let expected: [[f64; 4]; 9] = [
+            [1.0, 3.0, 5.0, 9.0],
+            [1.5, 3.0, 5.5, 9.5],
+            [1.0, 2.0, 5.0, 9.0],
+            [1.0, 2.5, 5.0, 9.0],
+            [1.5, 3.0, 5.5, 9.5],
+            [1.1, 2.75, 5.5, 9.9],
+            [1.9, 3.25, 5.5, 9.1],
+            [
+                1.366_666_666_666_667,
+                2.916_666_666_666_667,
+                5.5,
+                9.633_333_333_333_333,
+            ],
+            [1.4, 2.9375, 5.5, 9.6],
+        ];
+        for (method, expected) in METHODS.iter().zip(expected) {
+            let found = quantiles(&data, &[0.1, 0.25, 0.5, 0.9], *method).unwrap();
+            for (found, expected) in found.iter().zip(expected) {
+                assert!(
+                    (found - expected).abs() < 1e-12,
+                    "type {}: {} != {}",
+                    method.r_type(),
+                    found,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn single_quantile_matches_sorted() {
+        let data: Vec<f64> = (0..257).map(|i| ((i * 89 % 257) as f64).sqrt()).collect();
+        let mut sorted = data.clone();
+        sorted.sort_by(f64::total_cmp);
+        for method in METHODS {
+            for p in [0.0, 0.01, 0.05, 0.25, 0.5, 0.5 + 1e-9, 0.9, 0.95, 0.99, 1.0] {
+                assert_eq!(
+                    quantile(&data, p, method),
+                    Ok(quantile_sorted(&sorted, p, method))
+                );
+            }
+            assert_eq!(quantile(&[3.5], 0.3, method), Ok(3.5));
+            assert_eq!(percentile(&data, 0.0, method), Ok(0.0));
+            assert_eq!(percentile(&data, 100.0, method), Ok(256f64.sqrt()));
+        }
+        assert_eq!(
+            quantile(&data, 0.5, QuantileMethod::Linear),
+            Ok(Mean::median_f(&data))
+        );
+    }
+
+    #[test]
+    fn summaries() {
+        let data = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 100];
+        assert_eq!(iqr(&data, QuantileMethod::Linear), Ok(5.5));
+        let summary = five_number_summary(&data, QuantileMethod::Hazen).unwrap();
+        assert_eq!(
+            summary,
+            FiveNumberSummary {
+                min: 1.0,
+                lower_quartile: 3.5,
+                median: 6.5,
+                upper_quartile: 9.5,
+                max: 100.0,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_input() {
+        let empty: [f64; 0] = [];
+        assert_eq!(
+            quantile(&empty, 0.5, QuantileMethod::Linear),
+            Err(StatsError::Empty)
+        );
+        assert_eq!(
+            quantiles(&[1.0, f64::NAN], &[0.5], QuantileMethod::Linear),
+            Err(StatsError::ContainsNaN { index: 1 })
+        );
+        assert_eq!(
+            percentile(&[1.0], 101.0, QuantileMethod::Linear),
+            Err(StatsError::InvalidProbability { probability: 1.01 })
+        );
+        assert!(matches!(
+            quantile(&[1.0], f64::NAN, QuantileMethod::Linear),
+            Err(StatsError::InvalidProbability { .. })
+        ));
+        assert_eq!(QuantileMethod::from_r_type(0), None);
+        for method in METHODS {
+            assert_eq!(QuantileMethod::from_r_type(method.r_type()), Some(method));
+        }
+    }
+}
@@ -0,0 +1,599 @@
+//! Robust statistics of the `Mean` API, estimators of location and scale that a few outliers
+//! cannot move arbitrarily far.
+//!
+//! The `_i` variants convert the values to `f64` and share the implementation of the `_f` ones.
+
+use crate::{check_empty, check_f, to_f, Mean, StatsError};
+
+/// Scale of the median absolute deviation that makes it consistent for the standard deviation of
+/// normal data, `1 / Φ⁻¹(3/4)`
+const NORMAL_MAD_SCALE: f64 = 1.482_602_218_505_602;
+
+/// Maximum number of iterations of the Huber estimators, they converge linearly
+const HUBER_ITERATIONS: usize = 1000;
+
+fn sorted(data: &[f64]) -> Vec<f64> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+/// Median of sorted values, NaN for none
+fn median_sorted(sorted: &[f64]) -> f64 {
+    let len = sorted.len();
+    if len == 0 {
+        return f64::NAN;
+    }
+    (sorted[len / 2] + sorted[(len - 1) / 2]) / 2.0
+}
+
+/// Key of `x` whose unsigned order matches `f64::total_cmp`
+fn order_key(x: f64) -> u64 {
+    let bits = x.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | 1 << 63
+    }
+}
+
+fn from_order_key(key: u64) -> f64 {
+    if key >> 63 == 1 {
+        f64::from_bits(key & !(1 << 63))
+    } else {
+        f64::from_bits(!key)
+    }
+}
+
+/// Number of Walsh averages of sorted values that are at most `bound`, in linear time because the
+/// averages grow with both indices
+fn walsh_rank(sorted: &[f64], bound: f64) -> u128 {
+    let mut count = 0;
+    let mut end = sorted.len();
+    for (i, x) in sorted.iter().enumerate() {
+        while end > i && (x + sorted[end - 1]) / 2.0 > bound {
+            end -= 1;
+        }
+        if end <= i {
+            break;
+        }
+        count += (end - i) as u128;
+    }
+    count
+}
+
+/// `rank`-th smallest Walsh average of sorted values without NaN averages, counted from 1. A
+/// bisection over the order of `f64` that needs at most 64 counts of the averages below a bound
+fn walsh_select(sorted: &[f64], rank: u128) -> f64 {
+    let first = sorted[0];
+    let last = sorted[sorted.len() - 1];
+    let mut low = order_key((first + first) / 2.0);
+    let mut high = order_key((last + last) / 2.0);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if walsh_rank(sorted, from_order_key(middle)) >= rank {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    from_order_key(low)
+}
+
+/// Number of values cut from each end for `proportion`, `None` outside of `[0, 0.5)`
+fn cut(len: usize, proportion: f64) -> Option<usize> {
+    if !(0.0..0.5).contains(&proportion) {
+        return None;
+    }
+    Some((proportion * len as f64) as usize)
+}
+
+/// Sorted values with the `cut` smallest and largest replaced by the nearest remaining ones
+fn winsorized(data: &[f64], proportion: f64) -> Option<Vec<f64>> {
+    let mut values = sorted(data);
+    let cut = cut(values.len(), proportion)?;
+    let len = values.len();
+    if cut > 0 {
+        let (low, high) = (values[cut], values[len - cut - 1]);
+        values[..cut].fill(low);
+        values[len - cut..].fill(high);
+    }
+    Some(values)
+}
+
+/// Standard normal distribution function at `x`, from Marsaglia's Taylor series which is accurate
+/// to double precision
+fn normal_cdf(x: f64) -> f64 {
+    if x.abs() > 8.0 {
+        return if x > 0.0 { 1.0 } else { 0.0 };
+    }
+    let (mut sum, mut term) = (x, x);
+    let square = x * x;
+    let mut i = 3.0;
+    while sum + term != sum {
+        term *= square / i;
+        sum += term;
+        i += 2.0;
+    }
+    0.5 + sum * (-square / 2.0 - 0.918_938_533_204_672_8).exp()
+}
+
+fn check_proportion(proportion: f64) -> Result<(), StatsError> {
+    if !(0.0..0.5).contains(&proportion) {
+        return Err(StatsError::InvalidProportion { proportion });
+    }
+    Ok(())
+}
+
+fn check_tuning(k: f64) -> Result<(), StatsError> {
+    if !(k > 0.0 && k.is_finite()) {
+        return Err(StatsError::InvalidTuningConstant { constant: k });
+    }
+    Ok(())
+}
+
+impl Mean {
+    /// Median absolute deviation from the median, NaN for empty data
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::median_absolute_deviation_f(&[1.0, 1.0, 2.0, 2.0, 4.0, 6.0, 9.0]), 1.0);
+    /// ```
+    pub fn median_absolute_deviation_f(data: &[f64]) -> f64 {
+        let median = median_sorted(&sorted(data));
+        let deviations: Vec<f64> = data.iter().map(|x| (x - median).abs()).collect();
+        median_sorted(&sorted(&deviations))
+    }
+
+    pub fn median_absolute_deviation_i(data: &[i64]) -> f64 {
+        Mean::median_absolute_deviation_f(&to_f(data))
+    }
+
+    /// Median absolute deviation scaled by 1.4826, which estimates the standard deviation of
+    /// normal data like `mad` in R
+    pub fn normalized_median_absolute_deviation_f(data: &[f64]) -> f64 {
+        NORMAL_MAD_SCALE * Mean::median_absolute_deviation_f(data)
+    }
+
+    pub fn normalized_median_absolute_deviation_i(data: &[i64]) -> f64 {
+        Mean::normalized_median_absolute_deviation_f(&to_f(data))
+    }
+
+    /// Mean of the values left after cutting `floor(proportion * n)` of the smallest and as many
+    /// of the largest, like `scipy.stats.trim_mean`. NaN unless `proportion` is in `[0, 0.5)`
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::trimmed_mean_f(&[1.0, 2.0, 3.0, 4.0, 100.0], 0.2), 3.0);
+    /// ```
+    pub fn trimmed_mean_f(data: &[f64], proportion: f64) -> f64 {
+        let values = sorted(data);
+        match cut(values.len(), proportion) {
+            Some(cut) => Mean::mean_f(&values[cut..values.len() - cut]),
+            None => f64::NAN,
+        }
+    }
+
+    pub fn trimmed_mean_i(data: &[i64], proportion: f64) -> f64 {
+        Mean::trimmed_mean_f(&to_f(data), proportion)
+    }
+
+    /// Mean after replacing `floor(proportion * n)` of the smallest values by the smallest one
+    /// left and as many of the largest by the largest one left.
+    /// NaN unless `proportion` is in `[0, 0.5)`
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::winsorized_mean_f(&[1.0, 2.0, 3.0, 4.0, 100.0], 0.2), 3.0);
+    /// ```
+    pub fn winsorized_mean_f(data: &[f64], proportion: f64) -> f64 {
+        match winsorized(data, proportion) {
+            Some(values) => Mean::mean_f(&values),
+            None => f64::NAN,
+        }
+    }
+
+    pub fn winsorized_mean_i(data: &[i64], proportion: f64) -> f64 {
+        Mean::winsorized_mean_f(&to_f(data), proportion)
+    }
+
+    /// Sample variance of the winsorized values, divides by `n - 1` like `winvar` of Wilcox.
+    /// NaN unless `proportion` is in `[0, 0.5)` and there are at least 2 values
+    pub fn winsorized_variance_f(data: &[f64], proportion: f64) -> f64 {
+        match winsorized(data, proportion) {
+            Some(values) => Mean::sample_variance_f(&values),
+            None => f64::NAN,
+        }
+    }
+
+    pub fn winsorized_variance_i(data: &[i64], proportion: f64) -> f64 {
+        Mean::winsorized_variance_f(&to_f(data), proportion)
+    }
+
+    /// Interquartile mean, the mean of the middle half of the values. When `n` is not a multiple
+    /// of 4 the values at the quartiles count with the fraction of them inside the middle half.
+    /// NaN for empty data
+    /// ```
+    /// use datas::Mean;
+    /// let data = [1.0, 3.0, 5.0, 7.0, 9.0, 11.0, 13.0, 15.0, 17.0];
+    /// assert_eq!(Mean::interquartile_mean_f(&data), 9.0);
+    /// ```
+    pub fn interquartile_mean_f(data: &[f64]) -> f64 {
+        let values = sorted(data);
+        let len = values.len() as f64;
+        let (low, high) = (len / 4.0, 3.0 * len / 4.0);
+        let mut sum = 0.0;
+        for (i, value) in values.iter().enumerate() {
+            // overlap of the value's share [i, i + 1] with the middle half
+            let inside = (high.min(i as f64 + 1.0) - low.max(i as f64)).max(0.0);
+            if inside > 0.0 {
+                sum += inside * value;
+            }
+        }
+        sum / (high - low)
+    }
+
+    pub fn interquartile_mean_i(data: &[i64]) -> f64 {
+        Mean::interquartile_mean_f(&to_f(data))
+    }
+
+    /// Hodges–Lehmann estimator, the median of the `n (n + 1) / 2` Walsh averages `(x_i + x_j) / 2`
+    /// for `i <= j`. Selects the median without building the averages, in `O(n log n)` time and
+    /// linear memory. NaN for empty data, NaN values or both infinities
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::hodges_lehmann_f(&[1.0, 2.0, 3.0, 100.0]), 2.75);
+    /// ```
+    pub fn hodges_lehmann_f(data: &[f64]) -> f64 {
+        let sorted = sorted(data);
+        let (Some(&first), Some(&last)) = (sorted.first(), sorted.last()) else {
+            return f64::NAN;
+        };
+        if first.is_nan() || last.is_nan() || first == f64::NEG_INFINITY && last == f64::INFINITY {
+            return f64::NAN;
+        }
+        let len = sorted.len() as u128;
+        let count = len * (len + 1) / 2;
+        let upper = walsh_select(&sorted, count / 2 + 1);
+        if count % 2 == 1 {
+            return upper;
+        }
+        (walsh_select(&sorted, count / 2) + upper) / 2.0
+    }
+
+    pub fn hodges_lehmann_i(data: &[i64]) -> f64 {
+        Mean::hodges_lehmann_f(&to_f(data))
+    }
+
+    /// Huber M-estimator of location with tuning constant `k`, 1.5 is customary. Values further
+    /// than `k` normalized median absolute deviations from the estimate count as if they were at
+    /// that distance, like `huber` of the R package MASS. The median when the median absolute
+    /// deviation is zero, NaN for empty data, NaN values or `k` that is not positive
+    /// ```
+    /// use datas::Mean;
+    /// let location = Mean::huber_location_f(&[2.0, 3.0, 3.5, 4.0, 4.5, 5.0, 50.0], 1.5);
+    /// assert!(location > 3.5 && location < 4.5);
+    /// ```
+    pub fn huber_location_f(data: &[f64], k: f64) -> f64 {
+        if check_tuning(k).is_err() || data.iter().any(|x| x.is_nan()) {
+            return f64::NAN;
+        }
+        let mut location = median_sorted(&sorted(data));
+        let scale = Mean::normalized_median_absolute_deviation_f(data);
+        if scale == 0.0 {
+            return location;
+        }
+        if !scale.is_finite() {
+            return f64::NAN;
+        }
+        for _ in 0..HUBER_ITERATIONS {
+            let (low, high) = (location - k * scale, location + k * scale);
+            // an iterate that overflowed can leave no interval to clamp to
+            if low.is_nan() || high.is_nan() {
+                return f64::NAN;
+            }
+            let next = data.iter().map(|x| x.clamp(low, high)).sum::<f64>() / data.len() as f64;
+            let converged = (next - location).abs() <= 1e-14 * scale;
+            location = next;
+            if converged {
+                break;
+            }
+        }
+        location
+    }
+
+    pub fn huber_location_i(data: &[i64], k: f64) -> f64 {
+        Mean::huber_location_f(&to_f(data), k)
+    }
+
+    /// Huber M-estimator of scale with tuning constant `k`, estimated jointly with a location by
+    /// Huber's proposal 2 like `hubers` of the R package MASS. Consistent for the standard
+    /// deviation of normal data. Zero when the median absolute deviation is zero, NaN for fewer
+    /// than 2 values, NaN values or `k` that is not positive
+    pub fn huber_scale_f(data: &[f64], k: f64) -> f64 {
+        if check_tuning(k).is_err() || data.len() < 2 || data.iter().any(|x| x.is_nan()) {
+            return f64::NAN;
+        }
+        let mut location = median_sorted(&sorted(data));
+        let mut scale = Mean::normalized_median_absolute_deviation_f(data);
+        if scale == 0.0 {
+            return scale;
+        }
+        if !scale.is_finite() {
+            return f64::NAN;
+        }
+        // expected square of the clipped standard normal variable, makes the estimate consistent
+        let inside = 2.0 * normal_cdf(k) - 1.0;
+        let density = (-k * k / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt();
+        let beta = inside + k * k * (1.0 - inside) - 2.0 * k * density;
+        for _ in 0..HUBER_ITERATIONS {
+            let (low, high) = (location - k * scale, location + k * scale);
+            if low.is_nan() || high.is_nan() {
+                return f64::NAN;
+            }
+            let clipped: Vec<f64> = data.iter().map(|x| x.clamp(low, high)).collect();
+            let next_location = Mean::mean_f(&clipped);
+            let next_scale = (Mean::sample_variance_f(&clipped) / beta).sqrt();
+            let converged = (next_location - location).abs() <= 1e-14 * scale
+                && (next_scale - scale).abs() <= 1e-14 * scale;
+            location = next_location;
+            scale = next_scale;
+            if converged {
+                break;
+            }
+        }
+        scale
+    }
+
+    pub fn huber_scale_i(data: &[i64], k: f64) -> f64 {
+        Mean::huber_scale_f(&to_f(data), k)
+    }
+
+    /// `median_absolute_deviation_f` that fails on empty data or NaN
+    pub fn try_median_absolute_deviation_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::median_absolute_deviation_f(data))
+    }
+
+    pub fn try_median_absolute_deviation_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::median_absolute_deviation_i(data))
+    }
+
+    pub fn try_normalized_median_absolute_deviation_f(data: &[f64]) -> Result<f64, StatsError> {
+        Mean::try_median_absolute_deviation_f(data).map(|mad| NORMAL_MAD_SCALE * mad)
+    }
+
+    pub fn try_normalized_median_absolute_deviation_i(data: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_median_absolute_deviation_i(data).map(|mad| NORMAL_MAD_SCALE * mad)
+    }
+
+    /// `trimmed_mean_f` that fails on empty data, NaN or `proportion` outside of `[0, 0.5)`
+    /// ```
+    /// use datas::{Mean, StatsError};
+    /// assert_eq!(
+    ///     Mean::try_trimmed_mean_f(&[1.0, 2.0], 0.5),
+    ///     Err(StatsError::InvalidProportion { proportion: 0.5 })
+    /// );
+    /// ```
+    pub fn try_trimmed_mean_f(data: &[f64], proportion: f64) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_proportion(proportion)?;
+        Ok(Mean::trimmed_mean_f(data, proportion))
+    }
+
+    pub fn try_trimmed_mean_i(data: &[i64], proportion: f64) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        check_proportion(proportion)?;
+        Ok(Mean::trimmed_mean_i(data, proportion))
+    }
+
+    pub fn try_winsorized_mean_f(data: &[f64], proportion: f64) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_proportion(proportion)?;
+        Ok(Mean::winsorized_mean_f(data, proportion))
+    }
+
+    pub fn try_winsorized_mean_i(data: &[i64], proportion: f64) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        check_proportion(proportion)?;
+        Ok(Mean::winsorized_mean_i(data, proportion))
+    }
+
+    /// `winsorized_variance_f` that fails on NaN, fewer than 2 values or `proportion` outside of
+    /// `[0, 0.5)`
+    pub fn try_winsorized_variance_f(data: &[f64], proportion: f64) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_f(data)?;
+        check_proportion(proportion)?;
+        Ok(Mean::winsorized_variance_f(data, proportion))
+    }
+
+    pub fn try_winsorized_variance_i(data: &[i64], proportion: f64) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_i(data)?;
+        check_proportion(proportion)?;
+        Ok(Mean::winsorized_variance_i(data, proportion))
+    }
+
+    pub fn try_interquartile_mean_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::interquartile_mean_f(data))
+    }
+
+    pub fn try_interquartile_mean_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::interquartile_mean_i(data))
+    }
+
+    pub fn try_hodges_lehmann_f(data: &[f64]) -> Result<f64, StatsError> {
+        check_f(data)?;
+        Ok(Mean::hodges_lehmann_f(data))
+    }
+
+    pub fn try_hodges_lehmann_i(data: &[i64]) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        Ok(Mean::hodges_lehmann_i(data))
+    }
+
+    /// `huber_location_f` that fails on empty data, NaN or `k` that is not positive and finite
+    pub fn try_huber_location_f(data: &[f64], k: f64) -> Result<f64, StatsError> {
+        check_f(data)?;
+        check_tuning(k)?;
+        Ok(Mean::huber_location_f(data, k))
+    }
+
+    pub fn try_huber_location_i(data: &[i64], k: f64) -> Result<f64, StatsError> {
+        check_empty(data)?;
+        check_tuning(k)?;
+        Ok(Mean::huber_location_i(data, k))
+    }
+
+    /// `huber_scale_f` that fails on NaN, fewer than 2 values or `k` that is not positive and
+    /// finite
+    pub fn try_huber_scale_f(data: &[f64], k: f64) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_f(data)?;
+        check_tuning(k)?;
+        Ok(Mean::huber_scale_f(data, k))
+    }
+
+    pub fn try_huber_scale_i(data: &[i64], k: f64) -> Result<f64, StatsError> {
+        Mean::try_sample_variance_i(data)?;
+        check_tuning(k)?;
+        Ok(Mean::huber_scale_i(data, k))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normal_cdf;
+    use crate::{uniform, Mean, StatsError};
+
+    /// Copper in wholemeal flour, `chem` of the R package MASS
+    const CHEM: [f64; 24] = [
+        2.90, 3.10, 3.40, 3.40, 3.70, 3.70, 2.80, 2.50, 2.40, 2.40, 2.70, 2.20, 5.28, 3.37, 3.03,
+        3.03, 28.95, 3.77, 3.40, 2.20, 3.50, 3.60, 3.70, 3.70,
+    ];
+
+    #[test]
+    fn estimators_resist_outlier() {
+        // values reported by MASS for `chem`, whose mean is pulled to 4.28 by 28.95
+        assert!((Mean::median_absolute_deviation_f(&CHEM) - 0.355).abs() < 1e-12);
+        assert!((Mean::normalized_median_absolute_deviation_f(&CHEM) - 0.526323).abs() < 1e-6);
+        assert!((Mean::huber_location_f(&CHEM, 1.5) - 3.206724).abs() < 1e-6);
+        // joint location 3.205498 as in MASS, the scale is the converged fixed point
+        assert!((Mean::huber_scale_f(&CHEM, 1.5) - 0.6736526).abs() < 1e-6);
+        assert!((Mean::hodges_lehmann_f(&CHEM) - 3.225).abs() < 1e-12);
+        assert!((Mean::trimmed_mean_f(&CHEM, 0.1) - 3.205).abs() < 1e-12);
+        assert!(Mean::interquartile_mean_f(&CHEM) < 3.5);
+    }
+
+    #[test]
+    fn trimming_and_winsorizing() {
+        let data = [10.0, 1.0, 9.0, 2.0, 8.0, 3.0, 7.0, 4.0, 6.0, 500.0];
+        assert_eq!(Mean::trimmed_mean_f(&data, 0.0), Mean::mean_f(&data));
+        assert_eq!(Mean::trimmed_mean_f(&data, 0.1), 6.125);
+        assert_eq!(Mean::trimmed_mean_f(&data, 0.25), 37.0 / 6.0);
+        // 1 becomes 2 and 500 becomes 10
+        assert_eq!(Mean::winsorized_mean_f(&data, 0.1), 6.1);
+        let winsorized = [2.0, 2.0, 9.0, 10.0, 8.0, 3.0, 7.0, 4.0, 6.0, 10.0];
+        assert_eq!(
+            Mean::winsorized_variance_f(&data, 0.1),
+            Mean::sample_variance_f(&winsorized)
+        );
+        assert_eq!(
+            Mean::winsorized_mean_i(&[10, 1, 9, 2, 8, 3, 7, 4, 6, 500], 0.1),
+            6.1
+        );
+        assert!(Mean::trimmed_mean_f(&data, 0.5).is_nan());
+        assert_eq!(
+            Mean::interquartile_mean_i(&[5, 8, 4, 38, 8, 6, 9, 7, 7, 3, 1, 6]),
+            6.5
+        );
+        assert!(Mean::interquartile_mean_f(&[]).is_nan());
+        assert_eq!(Mean::hodges_lehmann_i(&[7]), 7.0);
+    }
+
+    #[test]
+    fn hodges_lehmann_matches_all_walsh_averages() {
+        fn quadratic(data: &[f64]) -> f64 {
+            let mut averages = Vec::new();
+            for (i, x) in data.iter().enumerate() {
+                for y in &data[i..] {
+                    averages.push((x + y) / 2.0);
+                }
+            }
+            averages.sort_by(f64::total_cmp);
+            let len = averages.len();
+            (averages[len / 2] + averages[(len - 1) / 2]) / 2.0
+        }
+        for len in 1..40 {
+            let data: Vec<f64> = uniform(len, len as u64)
+                .into_iter()
+                .map(|u| (u * 50.0).floor() / 4.0 - 6.0)
+                .collect();
+            assert_eq!(Mean::hodges_lehmann_f(&data), quadratic(&data));
+        }
+        assert_eq!(Mean::hodges_lehmann_f(&CHEM), quadratic(&CHEM));
+        let infinite = [-1.0, f64::INFINITY, 3.0, 5.0];
+        assert_eq!(Mean::hodges_lehmann_f(&infinite), quadratic(&infinite));
+        assert!(Mean::hodges_lehmann_f(&[1.0, f64::NAN]).is_nan());
+        assert!(Mean::hodges_lehmann_f(&[f64::NEG_INFINITY, f64::INFINITY]).is_nan());
+        // the averages of 200 000 values would take 160 GB
+        let data: Vec<f64> = (0..200_000).map(f64::from).collect();
+        assert_eq!(Mean::hodges_lehmann_f(&data), 99_999.5);
+    }
+
+    #[test]
+    fn huber_edge_cases() {
+        // more than half of the values are equal, so the median absolute deviation is zero
+        let data = [1.0, 1.0, 1.0, 5.0];
+        assert_eq!(Mean::huber_location_f(&data, 1.5), 1.0);
+        assert_eq!(Mean::huber_scale_f(&data, 1.5), 0.0);
+        // a large constant clips nothing, so the estimates become the mean and standard deviation
+        let data = [1.0, 2.0, 4.0, 7.0, 11.0];
+        assert!((Mean::huber_location_f(&data, 100.0) - 5.0).abs() < 1e-12);
+        assert!(
+            (Mean::huber_scale_f(&data, 100.0) - Mean::sample_standard_deviation_f(&data)).abs()
+                < 1e-9
+        );
+        assert!(Mean::huber_location_i(&[1, 2], -1.0).is_nan());
+        // NaN in the data or an infinite scale made the clamp bounds NaN and panic
+        assert!(Mean::huber_location_f(&[1.0, 3.0, f64::NAN], 1.5).is_nan());
+        assert!(Mean::huber_scale_f(&[1.0, 2.0, 3.0, f64::NAN], 1.5).is_nan());
+        let huge = [-f64::MAX, -f64::MAX, f64::MAX, f64::MAX];
+        assert!(Mean::huber_location_f(&huge, 1.5).is_nan());
+        assert!(Mean::huber_scale_f(&huge, 1.5).is_nan());
+        assert!((normal_cdf(1.5) - 0.9331927987311419).abs() < 1e-15);
+        assert!((normal_cdf(-1.0) - 0.15865525393145707).abs() < 1e-15);
+    }
+
+    #[test]
+    fn try_functions() {
+        assert_eq!(
+            Mean::try_median_absolute_deviation_f(&[]),
+            Err(StatsError::Empty)
+        );
+        assert_eq!(
+            Mean::try_winsorized_mean_i(&[1, 2], -0.1),
+            Err(StatsError::InvalidProportion { proportion: -0.1 })
+        );
+        assert_eq!(
+            Mean::try_huber_location_f(&[1.0], f64::INFINITY),
+            Err(StatsError::InvalidTuningConstant {
+                constant: f64::INFINITY
+            })
+        );
+        assert_eq!(
+            Mean::try_huber_scale_i(&[1], 1.5),
+            Err(StatsError::InsufficientData {
+                required: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            Mean::try_hodges_lehmann_f(&[1.0, f64::NAN]),
+            Err(StatsError::ContainsNaN { index: 1 })
+        );
+        assert_eq!(Mean::try_trimmed_mean_i(&[1, 2, 30], 0.4), Ok(2.0));
+        assert_eq!(
+            Mean::try_normalized_median_absolute_deviation_i(&[1, 2, 3]),
+            Ok(1.482602218505602)
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//! `Deserialize` implementations that check invariants of the input instead of trusting it.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+use crate::matrix::Matrix;
+use crate::online::OnlineStats;
+use crate::sketch::{Centroid, KllSketch, P2Quantile, TDigest, MAX_COMPRESSION};
+use crate::sparse::SparseVector;
+use crate::vector::Vector;
+
+#[derive(Deserialize)]
+#[serde(rename = "Matrix")]
+struct MatrixRepr<T> {
+    data: Vec<Vec<T>>,
+    rows: u64,
+    cols: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Vector")]
+struct VectorRepr<T> {
+    components: Vec<T>,
+    dimensions: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "SparseVector")]
+struct SparseVectorRepr<T> {
+    indices: Vec<u64>,
+    values: Vec<T>,
+    dimensions: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "OnlineStats")]
+struct OnlineStatsRepr {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    range: Option<(f64, f64)>,
+    nan_index: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "TDigest")]
+struct TDigestRepr {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<Centroid>,
+    range: Option<(f64, f64)>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "KllSketch")]
+struct KllSketchRepr {
+    k: u64,
+    compactors: Vec<Vec<f64>>,
+    count: u64,
+    range: Option<(f64, f64)>,
+    state: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "P2Quantile")]
+struct P2QuantileRepr {
+    p: f64,
+    count: u64,
+    heights: [f64; 5],
+    positions: [f64; 5],
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Matrix<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = MatrixRepr::<T>::deserialize(deserializer)?;
+        let matrix = Matrix::from_rows(repr.data).map_err(D::Error::custom)?;
+        if matrix.rows() != repr.rows || matrix.cols() != repr.cols {
+            return Err(D::Error::custom(format!(
+                "matrix is declared {}x{} but data is {}x{}",
+                repr.rows,
+                repr.cols,
+                matrix.rows(),
+                matrix.cols()
+            )));
+        }
+        Ok(matrix)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Vector<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = VectorRepr::<T>::deserialize(deserializer)?;
+        if repr.components.len() as u64 != repr.dimensions {
+            return Err(D::Error::custom(format!(
+                "vector declares {} dimensions but has {} components",
+                repr.dimensions,
+                repr.components.len()
+            )));
+        }
+        Ok(Vector::from_components(repr.components))
+    }
+}
+
+impl<'de, T: Deserialize<'de> + Default + PartialEq> Deserialize<'de> for SparseVector<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = SparseVectorRepr::<T>::deserialize(deserializer)?;
+        if repr.indices.len() != repr.values.len() {
+            return Err(D::Error::custom(format!(
+                "sparse vector has {} indices but {} values",
+                repr.indices.len(),
+                repr.values.len()
+            )));
+        }
+        if repr.indices.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(D::Error::custom(
+                "sparse vector indices are not strictly increasing",
+            ));
+        }
+        if let Some(&index) = repr
+            .indices
+            .last()
+            .filter(|&&index| index >= repr.dimensions)
+        {
+            return Err(D::Error::custom(format!(
+                "index {} is out of bound for sparse vector with {} dimensions",
+                index, repr.dimensions
+            )));
+        }
+        let zero = T::default();
+        if let Some(position) = repr.values.iter().position(|value| *value == zero) {
+            return Err(D::Error::custom(format!(
+                "sparse vector stores zero at index {}",
+                repr.indices[position]
+            )));
+        }
+        Ok(SparseVector::from_parts(
+            repr.indices,
+            repr.values,
+            repr.dimensions,
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for OnlineStats {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = OnlineStatsRepr::deserialize(deserializer)?;
+        let moments = [repr.mean, repr.m2, repr.m3, repr.m4];
+        if repr.count == 0 {
+            if moments != [0.0; 4] || repr.range.is_some() || repr.nan_index.is_some() {
+                return Err(D::Error::custom("statistics of no values are not empty"));
+            }
+            return Ok(OnlineStats::new());
+        }
+        if let Some(index) = repr.nan_index.filter(|&index| index >= repr.count) {
+            return Err(D::Error::custom(format!(
+                "NaN at index {} of {} values",
+                index, repr.count
+            )));
+        }
+        let Some((min, max)) = repr.range else {
+            return Err(D::Error::custom("statistics of values have no range"));
+        };
+        // values pushed after a NaN are not checked, the statistics are undefined anyway
+        if repr.nan_index.is_none() {
+            if min.is_nan() || max.is_nan() || min > max {
+                return Err(D::Error::custom(format!(
+                    "invalid range [{}, {}]",
+                    min, max
+                )));
+            }
+            if repr.m2 < 0.0 || repr.m4 < 0.0 {
+                return Err(D::Error::custom("sum of even powers is negative"));
+            }
+        }
+        Ok(OnlineStats::from_parts(
+            repr.count,
+            moments,
+            repr.range,
+            repr.nan_index,
+        ))
+    }
+}
+
+/// Fails unless `range` is present exactly when there are `values` and contains all of them
+fn check_range<'a, E: Error>(
+    range: Option<(f64, f64)>,
+    values: impl Iterator<Item = &'a f64>,
+) -> Result<(), E> {
+    let mut values = values.peekable();
+    match (range, values.peek()) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err(E::custom("sketch of values has no range")),
+        (Some(_), None) => Err(E::custom("empty sketch has a range")),
+        (Some((min, max)), Some(_)) => {
+            if values.all(|value| (min..=max).contains(value)) {
+                Ok(())
+            } else {
+                Err(E::custom(format!(
+                    "sketch holds values outside of its range [{}, {}]",
+                    min, max
+                )))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TDigest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = TDigestRepr::deserialize(deserializer)?;
+        if !(10.0..=MAX_COMPRESSION).contains(&repr.compression) {
+            return Err(D::Error::custom(format!(
+                "t-digest compression {} is outside of [10, {}]",
+                repr.compression, MAX_COMPRESSION
+            )));
+        }
+        let all = || repr.centroids.iter().chain(&repr.buffer);
+        if all().any(|centroid| !(centroid.weight > 0.0 && centroid.weight.is_finite())) {
+            return Err(D::Error::custom("t-digest centroid weight is not positive"));
+        }
+        if repr
+            .centroids
+            .windows(2)
+            .any(|pair| pair[0].mean > pair[1].mean)
+        {
+            return Err(D::Error::custom("t-digest centroids are not sorted"));
+        }
+        check_range(repr.range, all().map(|centroid| &centroid.mean))?;
+        Ok(TDigest::from_parts(
+            repr.compression,
+            repr.centroids,
+            repr.buffer,
+            repr.range,
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for KllSketch {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = KllSketchRepr::deserialize(deserializer)?;
+        if !(8..=u16::MAX as u64).contains(&repr.k) {
+            return Err(D::Error::custom(format!(
+                "KLL sketch k {} is outside of [8, 65535]",
+                repr.k
+            )));
+        }
+        if repr.compactors.is_empty() || repr.compactors.len() > 64 {
+            return Err(D::Error::custom(format!(
+                "KLL sketch has {} compactors",
+                repr.compactors.len()
+            )));
+        }
+        // an item on level `h` stands for `2^h` values
+        let weight = repr
+            .compactors
+            .iter()
+            .enumerate()
+            .try_fold(0u64, |weight, (level, items)| {
+                (items.len() as u64)
+                    .checked_mul(1 << level)
+                    .and_then(|items| weight.checked_add(items))
+            });
+        if weight != Some(repr.count) {
+            return Err(D::Error::custom(format!(
+                "KLL sketch compactors do not hold {} values",
+                repr.count
+            )));
+        }
+        check_range(repr.range, repr.compactors.iter().flatten())?;
+        Ok(KllSketch::from_parts(
+            repr.k as usize,
+            repr.compactors,
+            repr.count,
+            repr.range,
+            repr.state,
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for P2Quantile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = P2QuantileRepr::deserialize(deserializer)?;
+        if !(0.0..=1.0).contains(&repr.p) {
+            return Err(D::Error::custom(format!(
+                "P² probability {} is outside of [0, 1]",
+                repr.p
+            )));
+        }
+        let (markers, count) = if repr.count < 5 {
+            // values are kept as they arrive until there are five markers
+            ([1.0, 2.0, 3.0, 4.0, 5.0], repr.count as usize)
+        } else {
+            let n = repr.positions;
+            if n[0] != 1.0
+                || n[4] != repr.count as f64
+                || n.iter().any(|position| position.fract() != 0.0)
+                || n.windows(2).any(|pair| pair[0] >= pair[1])
+            {
+                return Err(D::Error::custom("P² marker positions are inconsistent"));
+            }
+            (n, 5)
+        };
+        if repr.heights[..count].iter().any(|height| height.is_nan()) {
+            return Err(D::Error::custom("P² marker height is NaN"));
+        }
+        if repr.count >= 5 && repr.heights.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(D::Error::custom("P² marker heights are not sorted"));
+        }
+        Ok(P2Quantile::from_parts(
+            repr.p,
+            repr.count,
+            repr.heights,
+            markers,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixed::{SMatrix, SVector};
+    use crate::matrix::{Matrix, MatrixError};
+    use crate::online::OnlineStats;
+    use crate::sketch::{KllSketch, P2Quantile, TDigest};
+    use crate::sparse::SparseVector;
+    use crate::vector::{Vector, VectorError};
+    use crate::StatsError;
+
+    #[test]
+    fn matrix_json_roundtrip() {
+        let matrix = Matrix::<i64>::new(vec![vec![1, 2], vec![3, 4]]).unwrap();
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert_eq!(json, r#"{"data":[[1,2],[3,4]],"rows":2,"cols":2}"#);
+        assert_eq!(serde_json::from_str::<Matrix<i64>>(&json).unwrap(), matrix);
+    }
+
+    #[test]
+    fn matrix_invalid_json() {
+        let json = r#"{"data":[[1,2],[3]],"rows":2,"cols":2}"#;
+        assert!(serde_json::from_str::<Matrix<i64>>(json).is_err());
+        let json = r#"{"data":[[1,2],[3,4]],"rows":3,"cols":2}"#;
+        assert!(serde_json::from_str::<Matrix<i64>>(json).is_err());
+    }
+
+    #[test]
+    fn vector_json_roundtrip() {
+        let f_vector = Vector::<f64>::new(vec![1.5, -2.0]);
+        let json = serde_json::to_string(&f_vector).unwrap();
+        assert_eq!(json, r#"{"components":[1.5,-2.0],"dimensions":2}"#);
+        assert_eq!(
+            serde_json::from_str::<Vector<f64>>(&json).unwrap(),
+            f_vector
+        );
+        let json = r#"{"components":[1.5,-2.0],"dimensions":3}"#;
+        assert!(serde_json::from_str::<Vector<f64>>(json).is_err());
+    }
+
+    #[test]
+    fn bincode_roundtrip() {
+        let matrix = Matrix::<f64>::new(vec![vec![1.0, 2.0, 3.0]]).unwrap();
+        let bytes = bincode::serialize(&matrix).unwrap();
+        assert_eq!(bincode::deserialize::<Matrix<f64>>(&bytes).unwrap(), matrix);
+
+        let i_vector = Vector::<i64>::new(vec![7, 8]);
+        let bytes = bincode::serialize(&i_vector).unwrap();
+        assert_eq!(
+            bincode::deserialize::<Vector<i64>>(&bytes).unwrap(),
+            i_vector
+        );
+    }
+
+    #[test]
+    fn errors_roundtrip() {
+        let error = MatrixError::RowOutOfBound { row: 3, rows: 2 };
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(json, r#"{"RowOutOfBound":{"row":3,"rows":2}}"#);
+        assert_eq!(serde_json::from_str::<MatrixError>(&json).unwrap(), error);
+        let error = VectorError::DimensionMismatch {
+            expected: 2,
+            actual: 3,
+        };
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(serde_json::from_str::<VectorError>(&json).unwrap(), error);
+    }
+
+    #[test]
+    fn fixed_serde_roundtrip() {
+        let matrix = SMatrix::new([[1, 2, 3], [4, 5, 6]]);
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert_eq!(json, r#"{"data":[[1,2,3],[4,5,6]]}"#);
+        assert_eq!(
+            serde_json::from_str::<SMatrix<i64, 2, 3>>(&json).unwrap(),
+            matrix
+        );
+        assert!(serde_json::from_str::<SMatrix<i64, 3, 2>>(&json).is_err());
+
+        let vector = SVector::new([1.5, 2.5]);
+        let json = serde_json::to_string(&vector).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SVector<f64, 2>>(&json).unwrap(),
+            vector
+        );
+        assert!(serde_json::from_str::<SVector<f64, 3>>(&json).is_err());
+    }
+
+    #[test]
+    fn sparse_vector_json_roundtrip() {
+        let sparse = SparseVector::new(1000, vec![(999, 2.5), (3, 1.0)]).unwrap();
+        let json = serde_json::to_string(&sparse).unwrap();
+        assert_eq!(
+            json,
+            r#"{"indices":[3,999],"values":[1.0,2.5],"dimensions":1000}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<SparseVector<f64>>(&json).unwrap(),
+            sparse
+        );
+        for invalid in [
+            r#"{"indices":[3,999],"values":[1.0],"dimensions":1000}"#,
+            r#"{"indices":[999,3],"values":[1.0,2.5],"dimensions":1000}"#,
+            r#"{"indices":[3,3],"values":[1.0,2.5],"dimensions":1000}"#,
+            r#"{"indices":[3,999],"values":[1.0,2.5],"dimensions":999}"#,
+            r#"{"indices":[3,999],"values":[0.0,2.5],"dimensions":1000}"#,
+        ] {
+            assert!(serde_json::from_str::<SparseVector<f64>>(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn sketches_merge_after_roundtrip() {
+        let values: Vec<f64> = (0..10_000).map(|i| (i * 7919 % 10_000) as f64).collect();
+        let (left, right) = values.split_at(6000);
+        let mut digest = TDigest::default();
+        let mut sketch = KllSketch::default();
+        digest.extend(left.iter().copied());
+        sketch.extend(left.iter().copied());
+        // partial sketches built elsewhere
+        let mut other_digest = TDigest::default();
+        let mut other_sketch = KllSketch::default();
+        other_digest.extend(right.iter().copied());
+        other_sketch.extend(right.iter().copied());
+
+        let bytes = bincode::serialize(&other_digest).unwrap();
+        let received: TDigest = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(received, other_digest);
+        let mut expected = digest.clone();
+        expected.merge(&other_digest);
+        digest.merge(&received);
+        assert_eq!(digest.quantile(0.9), expected.quantile(0.9));
+
+        let json = serde_json::to_string(&other_sketch).unwrap();
+        let received: KllSketch = serde_json::from_str(&json).unwrap();
+        assert_eq!(received, other_sketch);
+        sketch.merge(&received).unwrap();
+        assert_eq!(sketch.count(), 10_000);
+
+        let mut p2 = P2Quantile::new(0.9).unwrap();
+        p2.extend(values.iter().copied());
+        let json = serde_json::to_string(&p2).unwrap();
+        assert_eq!(serde_json::from_str::<P2Quantile>(&json).unwrap(), p2);
+
+        // min and max of empty sketches are not finite
+        let empty = TDigest::default();
+        let json = serde_json::to_string(&empty).unwrap();
+        assert_eq!(serde_json::from_str::<TDigest>(&json).unwrap(), empty);
+        let empty = P2Quantile::new(0.5).unwrap();
+        let bytes = bincode::serialize(&empty).unwrap();
+        assert_eq!(bincode::deserialize::<P2Quantile>(&bytes).unwrap(), empty);
+    }
+
+    #[test]
+    fn online_stats_roundtrip() {
+        let stats: OnlineStats = [2.0, 4.0, 4.0, 5.0].into_iter().collect();
+        let bytes = bincode::serialize(&stats).unwrap();
+        let mut read: OnlineStats = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(read, stats);
+        read.push(9.0);
+        assert_eq!(read.max(), Ok(9.0));
+
+        let empty = serde_json::to_string(&OnlineStats::new()).unwrap();
+        assert_eq!(
+            serde_json::from_str::<OnlineStats>(&empty).unwrap(),
+            OnlineStats::new()
+        );
+        for invalid in [
+            r#"{"count":0,"mean":1.0,"m2":0.0,"m3":0.0,"m4":0.0,"range":null,"nan_index":null}"#,
+            r#"{"count":2,"mean":1.0,"m2":-1.0,"m3":0.0,"m4":1.0,"range":[0.0,2.0],"nan_index":null}"#,
+            r#"{"count":2,"mean":1.0,"m2":2.0,"m3":0.0,"m4":2.0,"range":[2.0,0.0],"nan_index":null}"#,
+            r#"{"count":2,"mean":1.0,"m2":2.0,"m3":0.0,"m4":2.0,"range":null,"nan_index":null}"#,
+            r#"{"count":2,"mean":1.0,"m2":2.0,"m3":0.0,"m4":2.0,"range":[0.0,2.0],"nan_index":2}"#,
+        ] {
+            assert!(serde_json::from_str::<OnlineStats>(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn sketches_invalid_json() {
+        for invalid in [
+            r#"{"compression":5.0,"centroids":[],"buffer":[],"range":null}"#,
+            r#"{"compression":1e19,"centroids":[],"buffer":[],"range":null}"#,
+            r#"{"compression":100.0,"centroids":[{"mean":2.0,"weight":1.0},{"mean":1.0,"weight":1.0}],"buffer":[],"range":[1.0,2.0]}"#,
+            r#"{"compression":100.0,"centroids":[{"mean":1.0,"weight":0.0}],"buffer":[],"range":[1.0,1.0]}"#,
+            r#"{"compression":100.0,"centroids":[],"buffer":[{"mean":3.0,"weight":1.0}],"range":[1.0,2.0]}"#,
+            r#"{"compression":100.0,"centroids":[],"buffer":[],"range":[1.0,2.0]}"#,
+        ] {
+            assert!(serde_json::from_str::<TDigest>(invalid).is_err());
+        }
+        let json = r#"{"k":200,"compactors":[[1.0],[2.0]],"count":3,"range":[1.0,2.0],"state":1}"#;
+        assert!(serde_json::from_str::<KllSketch>(json).is_ok());
+        for invalid in [
+            r#"{"k":200,"compactors":[[1.0],[2.0]],"count":2,"range":[1.0,2.0],"state":1}"#,
+            r#"{"k":4,"compactors":[[1.0],[2.0]],"count":3,"range":[1.0,2.0],"state":1}"#,
+            r#"{"k":200,"compactors":[],"count":0,"range":null,"state":1}"#,
+            r#"{"k":200,"compactors":[[1.0],[2.0]],"count":3,"range":null,"state":1}"#,
+        ] {
+            assert!(serde_json::from_str::<KllSketch>(invalid).is_err());
+        }
+        // a single item on the top level stands for 2^63 values, twice that overflows the count
+        let compactors = format!("{}[5.0]", "[],".repeat(63));
+        let json = format!(
+            r#"{{"k":200,"compactors":[{}],"count":{},"range":[5.0,5.0],"state":1}}"#,
+            compactors,
+            1u64 << 63
+        );
+        let mut sketch = serde_json::from_str::<KllSketch>(&json).unwrap();
+        let copy = sketch.clone();
+        assert_eq!(sketch.merge(&copy), Err(StatsError::CountOverflow));
+        assert_eq!(sketch.count(), 1 << 63);
+        assert_eq!(sketch.quantile(0.5), Ok(5.0));
+        for invalid in [
+            r#"{"p":1.5,"count":0,"heights":[0.0,0.0,0.0,0.0,0.0],"positions":[1.0,2.0,3.0,4.0,5.0]}"#,
+            r#"{"p":0.5,"count":6,"heights":[1.0,2.0,3.0,4.0,5.0],"positions":[1.0,2.0,3.0,4.0,5.0]}"#,
+            r#"{"p":0.5,"count":6,"heights":[1.0,3.0,2.0,4.0,5.0],"positions":[1.0,2.0,3.0,4.0,6.0]}"#,
+        ] {
+            assert!(serde_json::from_str::<P2Quantile>(invalid).is_err());
+        }
+    }
+}
@@ -0,0 +1,624 @@
+//! Vectorized kernels for slices of `f64`, `f32` and `i64`.
+//!
+//! The instruction set is picked at runtime: AVX2 or SSE2 on x86 and x86_64 and a portable
+//! implementation everywhere else. Float sums are split across lanes, so results can differ from a
+//! sequential sum in the last bits. Integer arithmetic wraps on overflow on every backend.
+//! SSE2 and AVX2 have no 64 bit integer multiplication, `i64` products always use the portable code.
+
+use crate::vector::VectorError;
+
+/// Instruction set used by the kernels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Portable code, available on every target
+    Scalar,
+    /// 128 bit registers
+    Sse2,
+    /// 256 bit registers
+    Avx2,
+}
+
+impl Backend {
+    /// Fastest backend supported by the running CPU
+    pub fn detect() -> Backend {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return Backend::Avx2;
+            }
+            if is_x86_feature_detected!("sse2") {
+                return Backend::Sse2;
+            }
+        }
+        Backend::Scalar
+    }
+
+    /// Whether the running CPU supports the backend
+    pub fn is_available(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Backend::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+            _ => false,
+        }
+    }
+
+    /// The backend if the CPU supports it, the portable one otherwise
+    fn usable(self) -> Backend {
+        if self.is_available() {
+            self
+        } else {
+            Backend::Scalar
+        }
+    }
+
+    /// Sum of products of elements with the same index
+    pub fn dot<T: SimdElement>(self, a: &[T], b: &[T]) -> Result<T, VectorError> {
+        check_lengths(a.len(), b.len())?;
+        Ok(T::dot(self.usable(), a, b))
+    }
+
+    /// `y += alpha * x`
+    pub fn axpy<T: SimdElement>(self, alpha: T, x: &[T], y: &mut [T]) -> Result<(), VectorError> {
+        check_lengths(y.len(), x.len())?;
+        T::axpy(self.usable(), alpha, x, y);
+        Ok(())
+    }
+
+    /// Euclidean norm
+    pub fn norm<T: SimdElement>(self, x: &[T]) -> f64 {
+        T::norm(self.usable(), x)
+    }
+
+    /// `y += x` element-wise
+    pub fn add_assign<T: SimdElement>(self, y: &mut [T], x: &[T]) -> Result<(), VectorError> {
+        check_lengths(y.len(), x.len())?;
+        T::add(self.usable(), y, x);
+        Ok(())
+    }
+
+    /// `y -= x` element-wise
+    pub fn sub_assign<T: SimdElement>(self, y: &mut [T], x: &[T]) -> Result<(), VectorError> {
+        check_lengths(y.len(), x.len())?;
+        T::sub(self.usable(), y, x);
+        Ok(())
+    }
+
+    /// `y *= x` element-wise
+    pub fn mul_assign<T: SimdElement>(self, y: &mut [T], x: &[T]) -> Result<(), VectorError> {
+        check_lengths(y.len(), x.len())?;
+        T::mul(self.usable(), y, x);
+        Ok(())
+    }
+}
+
+fn check_lengths(expected: usize, actual: usize) -> Result<(), VectorError> {
+    if expected != actual {
+        return Err(VectorError::DimensionMismatch {
+            expected: expected as u64,
+            actual: actual as u64,
+        });
+    }
+    Ok(())
+}
+
+/// Dot product with the fastest available backend
+/// ```
+/// use datas::simd;
+/// let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+/// let b = [5.0, 4.0, 3.0, 2.0, 1.0];
+/// assert_eq!(simd::dot(&a, &b), Ok(35.0));
+/// ```
+pub fn dot<T: SimdElement>(a: &[T], b: &[T]) -> Result<T, VectorError> {
+    Backend::detect().dot(a, b)
+}
+
+/// `y += alpha * x` with the fastest available backend
+pub fn axpy<T: SimdElement>(alpha: T, x: &[T], y: &mut [T]) -> Result<(), VectorError> {
+    Backend::detect().axpy(alpha, x, y)
+}
+
+/// Euclidean norm with the fastest available backend
+pub fn norm<T: SimdElement>(x: &[T]) -> f64 {
+    Backend::detect().norm(x)
+}
+
+/// `y += x` element-wise with the fastest available backend
+pub fn add_assign<T: SimdElement>(y: &mut [T], x: &[T]) -> Result<(), VectorError> {
+    Backend::detect().add_assign(y, x)
+}
+
+/// `y -= x` element-wise with the fastest available backend
+pub fn sub_assign<T: SimdElement>(y: &mut [T], x: &[T]) -> Result<(), VectorError> {
+    Backend::detect().sub_assign(y, x)
+}
+
+/// `y *= x` element-wise with the fastest available backend
+pub fn mul_assign<T: SimdElement>(y: &mut [T], x: &[T]) -> Result<(), VectorError> {
+    Backend::detect().mul_assign(y, x)
+}
+
+mod sealed {
+    use super::Backend;
+
+    /// Kernels of an element type, slices have equal lengths and the backend is supported by the CPU
+    pub trait Kernels: Copy {
+        fn dot(backend: Backend, a: &[Self], b: &[Self]) -> Self;
+        fn axpy(backend: Backend, alpha: Self, x: &[Self], y: &mut [Self]);
+        fn norm(backend: Backend, x: &[Self]) -> f64;
+        fn add(backend: Backend, y: &mut [Self], x: &[Self]);
+        fn sub(backend: Backend, y: &mut [Self], x: &[Self]);
+        fn mul(backend: Backend, y: &mut [Self], x: &[Self]);
+    }
+}
+
+/// Element types with vectorized kernels: `f64`, `f32` and `i64`
+pub trait SimdElement: sealed::Kernels {}
+
+impl SimdElement for f64 {}
+impl SimdElement for f32 {}
+impl SimdElement for i64 {}
+
+/// Calls the kernel of the backend, the last argument is the portable implementation
+macro_rules! dispatch {
+    ($backend:expr, $sse2:path, $avx2:path, ($($arg:expr),*), $portable:expr) => {
+        match $backend {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            // SAFETY: `Backend::usable` only hands out backends the running CPU supports
+            Backend::Avx2 => unsafe { $avx2($($arg),*) },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            // SAFETY: as above
+            Backend::Sse2 => unsafe { $sse2($($arg),*) },
+            _ => $portable,
+        }
+    };
+}
+
+macro_rules! float_element {
+    ($t:ty, $sse2:ident, $avx2:ident) => {
+        impl sealed::Kernels for $t {
+            fn dot(backend: Backend, a: &[$t], b: &[$t]) -> $t {
+                dispatch!(
+                    backend,
+                    x86::$sse2::dot,
+                    x86::$avx2::dot,
+                    (a, b),
+                    a.iter().zip(b).map(|(x, y)| x * y).sum()
+                )
+            }
+
+            fn axpy(backend: Backend, alpha: $t, x: &[$t], y: &mut [$t]) {
+                dispatch!(
+                    backend,
+                    x86::$sse2::axpy,
+                    x86::$avx2::axpy,
+                    (alpha, x, y),
+                    y.iter_mut().zip(x).for_each(|(y, x)| *y += alpha * x)
+                )
+            }
+
+            fn norm(backend: Backend, x: &[$t]) -> f64 {
+                (Self::dot(backend, x, x) as f64).sqrt()
+            }
+
+            fn add(backend: Backend, y: &mut [$t], x: &[$t]) {
+                dispatch!(
+                    backend,
+                    x86::$sse2::add,
+                    x86::$avx2::add,
+                    (y, x),
+                    y.iter_mut().zip(x).for_each(|(y, x)| *y += x)
+                )
+            }
+
+            fn sub(backend: Backend, y: &mut [$t], x: &[$t]) {
+                dispatch!(
+                    backend,
+                    x86::$sse2::sub,
+                    x86::$avx2::sub,
+                    (y, x),
+                    y.iter_mut().zip(x).for_each(|(y, x)| *y -= x)
+                )
+            }
+
+            fn mul(backend: Backend, y: &mut [$t], x: &[$t]) {
+                dispatch!(
+                    backend,
+                    x86::$sse2::mul,
+                    x86::$avx2::mul,
+                    (y, x),
+                    y.iter_mut().zip(x).for_each(|(y, x)| *y *= x)
+                )
+            }
+        }
+    };
+}
+
+float_element!(f64, sse2_f64, avx2_f64);
+float_element!(f32, sse2_f32, avx2_f32);
+
+impl sealed::Kernels for i64 {
+    fn dot(_: Backend, a: &[i64], b: &[i64]) -> i64 {
+        a.iter()
+            .zip(b)
+            .fold(0, |sum, (x, y)| sum.wrapping_add(x.wrapping_mul(*y)))
+    }
+
+    fn axpy(_: Backend, alpha: i64, x: &[i64], y: &mut [i64]) {
+        y.iter_mut()
+            .zip(x)
+            .for_each(|(y, x)| *y = y.wrapping_add(alpha.wrapping_mul(*x)));
+    }
+
+    fn norm(_: Backend, x: &[i64]) -> f64 {
+        x.iter()
+            .map(|&value| (value as f64).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    fn add(backend: Backend, y: &mut [i64], x: &[i64]) {
+        dispatch!(
+            backend,
+            x86::sse2_i64::add,
+            x86::avx2_i64::add,
+            (y, x),
+            y.iter_mut()
+                .zip(x)
+                .for_each(|(y, x)| *y = y.wrapping_add(*x))
+        )
+    }
+
+    fn sub(backend: Backend, y: &mut [i64], x: &[i64]) {
+        dispatch!(
+            backend,
+            x86::sse2_i64::sub,
+            x86::avx2_i64::sub,
+            (y, x),
+            y.iter_mut()
+                .zip(x)
+                .for_each(|(y, x)| *y = y.wrapping_sub(*x))
+        )
+    }
+
+    fn mul(_: Backend, y: &mut [i64], x: &[i64]) {
+        y.iter_mut()
+            .zip(x)
+            .for_each(|(y, x)| *y = y.wrapping_mul(*x));
+    }
+}
+
+/// Kernels using `std::arch`, callers have to make sure the CPU supports the enabled feature
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    /// `y = y op x` over whole registers, the elements that do not fill a register are done one by one
+    macro_rules! elementwise {
+        ($name:ident, $feature:literal, $t:ty, $lanes:literal, $op:ident, $scalar:path) => {
+            #[target_feature(enable = $feature)]
+            pub unsafe fn $name(y: &mut [$t], x: &[$t]) {
+                let mut x_chunks = x.chunks_exact($lanes);
+                let mut y_chunks = y.chunks_exact_mut($lanes);
+                for (x, y) in (&mut x_chunks).zip(&mut y_chunks) {
+                    let result = $op(load(y), load(x));
+                    store(y, result);
+                }
+                for (x, y) in x_chunks.remainder().iter().zip(y_chunks.into_remainder()) {
+                    *y = $scalar(*y, *x);
+                }
+            }
+        };
+    }
+
+    macro_rules! float_kernels {
+        (
+            $module:ident, $feature:literal, $t:ty, $register:ty, $lanes:literal,
+            $zero:ident, $set1:ident, $load:ident, $store:ident, $add:ident, $sub:ident, $mul:ident
+        ) => {
+            pub mod $module {
+                use super::*;
+
+                #[inline]
+                #[target_feature(enable = $feature)]
+                unsafe fn load(values: &[$t]) -> $register {
+                    $load(values.as_ptr())
+                }
+
+                #[inline]
+                #[target_feature(enable = $feature)]
+                unsafe fn store(values: &mut [$t], register: $register) {
+                    $store(values.as_mut_ptr(), register)
+                }
+
+                /// Two independent accumulators hide the latency of the additions
+                #[target_feature(enable = $feature)]
+                pub unsafe fn dot(a: &[$t], b: &[$t]) -> $t {
+                    let (mut first, mut second) = ($zero(), $zero());
+                    let mut a_chunks = a.chunks_exact(2 * $lanes);
+                    let mut b_chunks = b.chunks_exact(2 * $lanes);
+                    for (x, y) in (&mut a_chunks).zip(&mut b_chunks) {
+                        first = $add(first, $mul(load(x), load(y)));
+                        second = $add(second, $mul(load(&x[$lanes..]), load(&y[$lanes..])));
+                    }
+                    let mut lanes = [0.0; $lanes];
+                    store(&mut lanes, $add(first, second));
+                    let rest: $t = a_chunks
+                        .remainder()
+                        .iter()
+                        .zip(b_chunks.remainder())
+                        .map(|(x, y)| x * y)
+                        .sum();
+                    lanes.iter().sum::<$t>() + rest
+                }
+
+                #[target_feature(enable = $feature)]
+                pub unsafe fn axpy(alpha: $t, x: &[$t], y: &mut [$t]) {
+                    let scale = $set1(alpha);
+                    let mut x_chunks = x.chunks_exact($lanes);
+                    let mut y_chunks = y.chunks_exact_mut($lanes);
+                    for (x, y) in (&mut x_chunks).zip(&mut y_chunks) {
+                        let result = $add(load(y), $mul(scale, load(x)));
+                        store(y, result);
+                    }
+                    for (x, y) in x_chunks.remainder().iter().zip(y_chunks.into_remainder()) {
+                        *y += alpha * x;
+                    }
+                }
+
+                elementwise!(add, $feature, $t, $lanes, $add, std::ops::Add::add);
+                elementwise!(sub, $feature, $t, $lanes, $sub, std::ops::Sub::sub);
+                elementwise!(mul, $feature, $t, $lanes, $mul, std::ops::Mul::mul);
+            }
+        };
+    }
+
+    macro_rules! integer_kernels {
+        (
+            $module:ident, $feature:literal, $register:ty, $lanes:literal,
+            $load:ident, $store:ident, $add:ident, $sub:ident
+        ) => {
+            pub mod $module {
+                use super::*;
+
+                #[inline]
+                #[target_feature(enable = $feature)]
+                unsafe fn load(values: &[i64]) -> $register {
+                    $load(values.as_ptr() as *const $register)
+                }
+
+                #[inline]
+                #[target_feature(enable = $feature)]
+                unsafe fn store(values: &mut [i64], register: $register) {
+                    $store(values.as_mut_ptr() as *mut $register, register)
+                }
+
+                elementwise!(add, $feature, i64, $lanes, $add, i64::wrapping_add);
+                elementwise!(sub, $feature, i64, $lanes, $sub, i64::wrapping_sub);
+            }
+        };
+    }
+
+    float_kernels!(
+        sse2_f64,
+        "sse2",
+        f64,
+        __m128d,
+        2,
+        _mm_setzero_pd,
+        _mm_set1_pd,
+        _mm_loadu_pd,
+        _mm_storeu_pd,
+        _mm_add_pd,
+        _mm_sub_pd,
+        _mm_mul_pd
+    );
+    float_kernels!(
+        avx2_f64,
+        "avx2",
+        f64,
+        __m256d,
+        4,
+        _mm256_setzero_pd,
+        _mm256_set1_pd,
+        _mm256_loadu_pd,
+        _mm256_storeu_pd,
+        _mm256_add_pd,
+        _mm256_sub_pd,
+        _mm256_mul_pd
+    );
+    float_kernels!(
+        sse2_f32,
+        "sse2",
+        f32,
+        __m128,
+        4,
+        _mm_setzero_ps,
+        _mm_set1_ps,
+        _mm_loadu_ps,
+        _mm_storeu_ps,
+        _mm_add_ps,
+        _mm_sub_ps,
+        _mm_mul_ps
+    );
+    float_kernels!(
+        avx2_f32,
+        "avx2",
+        f32,
+        __m256,
+        8,
+        _mm256_setzero_ps,
+        _mm256_set1_ps,
+        _mm256_loadu_ps,
+        _mm256_storeu_ps,
+        _mm256_add_ps,
+        _mm256_sub_ps,
+        _mm256_mul_ps
+    );
+    integer_kernels!(
+        sse2_i64,
+        "sse2",
+        __m128i,
+        2,
+        _mm_loadu_si128,
+        _mm_storeu_si128,
+        _mm_add_epi64,
+        _mm_sub_epi64
+    );
+    integer_kernels!(
+        avx2_i64,
+        "avx2",
+        __m256i,
+        4,
+        _mm256_loadu_si256,
+        _mm256_storeu_si256,
+        _mm256_add_epi64,
+        _mm256_sub_epi64
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{close_within, uniform};
+
+    const BACKENDS: [Backend; 3] = [Backend::Scalar, Backend::Sse2, Backend::Avx2];
+
+    /// Deterministic values in `[-1, 1)` with a length that leaves a remainder on every backend
+    fn values(length: usize, seed: u64) -> Vec<f64> {
+        uniform(length, seed)
+            .iter()
+            .map(|x| 2.0 * x - 1.0)
+            .collect()
+    }
+
+    #[test]
+    fn detection() {
+        let backend = Backend::detect();
+        assert!(backend.is_available());
+        assert!(Backend::Scalar.is_available());
+        #[cfg(target_arch = "x86_64")]
+        assert_ne!(backend, Backend::Scalar);
+    }
+
+    #[test]
+    fn f64_backends_agree() {
+        for length in [0, 1, 3, 7, 8, 9, 1003] {
+            let a = values(length, 1);
+            let b = values(length, 2);
+            let reference = Backend::Scalar.dot(&a, &b).unwrap();
+            for backend in BACKENDS {
+                assert!(close_within(backend.dot(&a, &b).unwrap(), reference, 1e-12));
+                assert!(close_within(
+                    backend.norm(&a),
+                    Backend::Scalar.norm(&a),
+                    1e-12
+                ));
+
+                let mut expected = b.clone();
+                Backend::Scalar.axpy(0.5, &a, &mut expected).unwrap();
+                let mut actual = b.clone();
+                backend.axpy(0.5, &a, &mut actual).unwrap();
+                assert_eq!(actual, expected);
+
+                for operation in [
+                    Backend::add_assign,
+                    Backend::sub_assign,
+                    Backend::mul_assign,
+                ] {
+                    let mut expected = b.clone();
+                    operation(Backend::Scalar, &mut expected, &a).unwrap();
+                    let mut actual = b.clone();
+                    operation(backend, &mut actual, &a).unwrap();
+                    assert_eq!(actual, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn f32_backends_agree() {
+        for length in [0, 5, 16, 17, 1001] {
+            let a: Vec<f32> = values(length, 3).iter().map(|&v| v as f32).collect();
+            let b: Vec<f32> = values(length, 4).iter().map(|&v| v as f32).collect();
+            let exact: f64 = a.iter().zip(&b).map(|(&x, &y)| x as f64 * y as f64).sum();
+            for backend in BACKENDS {
+                assert!(close_within(
+                    backend.dot(&a, &b).unwrap() as f64,
+                    exact,
+                    1e-4
+                ));
+                let mut expected = b.clone();
+                Backend::Scalar.axpy(-2.0, &a, &mut expected).unwrap();
+                let mut actual = b.clone();
+                backend.axpy(-2.0, &a, &mut actual).unwrap();
+                assert_eq!(actual, expected);
+                for operation in [
+                    Backend::add_assign,
+                    Backend::sub_assign,
+                    Backend::mul_assign,
+                ] {
+                    let mut expected = b.clone();
+                    operation(Backend::Scalar, &mut expected, &a).unwrap();
+                    let mut actual = b.clone();
+                    operation(backend, &mut actual, &a).unwrap();
+                    assert_eq!(actual, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn i64_backends_agree() {
+        let a: Vec<i64> = (0..37).map(|i| i * 7 - 100).collect();
+        let b: Vec<i64> = (0..37).map(|i| 50 - i * i).collect();
+        let expected: i64 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+        for backend in BACKENDS {
+            assert_eq!(backend.dot(&a, &b), Ok(expected));
+            for operation in [
+                Backend::add_assign,
+                Backend::sub_assign,
+                Backend::mul_assign,
+            ] {
+                let mut expected = b.clone();
+                operation(Backend::Scalar, &mut expected, &a).unwrap();
+                let mut actual = b.clone();
+                operation(backend, &mut actual, &a).unwrap();
+                assert_eq!(actual, expected);
+            }
+            let mut wrapped = vec![i64::MAX; 5];
+            backend.add_assign(&mut wrapped, &[1; 5]).unwrap();
+            assert_eq!(wrapped, vec![i64::MIN; 5]);
+        }
+        assert_eq!(norm(&[3i64, 4]), 5.0);
+    }
+
+    #[test]
+    fn length_mismatch() {
+        let mismatch = Err(VectorError::DimensionMismatch {
+            expected: 3,
+            actual: 2,
+        });
+        assert_eq!(dot(&[1.0, 2.0, 3.0], &[1.0, 2.0]), mismatch);
+        assert_eq!(
+            add_assign(&mut [1, 2, 3], &[1, 2]),
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            axpy(1.0f32, &[1.0, 2.0], &mut [0.0; 3]),
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,753 @@
+//! Quantile sketches, approximate quantiles of data streams in bounded memory.
+//!
+//! - `TDigest` clusters values into centroids that are small near the extremes, so tail quantiles
+//!   such as p99 stay accurate.
+//! - `KllSketch` keeps a weighted sample of the stream, its rank error is bounded with high
+//!   probability independently of the distribution.
+//! - `P2Quantile` tracks a single quantile with five markers and the P² algorithm.
+//!
+//! `TDigest` and `KllSketch` merge sketches of separate parts of a stream, with the `serde` feature
+//! all sketches serialize so partial results can be combined elsewhere. NaN values have no rank
+//! and are ignored.
+
+use crate::quantile::{self, check_probability, QuantileMethod};
+use crate::StatsError;
+
+/// Largest t-digest compression, the buffer of a digest holds up to five times as many centroids
+pub(crate) const MAX_COMPRESSION: f64 = 100_000.0;
+
+/// Cluster of values summarized by their mean and count
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Centroid {
+    pub(crate) mean: f64,
+    pub(crate) weight: f64,
+}
+
+/// Merging t-digest (Dunning and Ertl), centroids grow from single values at the extremes to the
+/// largest clusters around the median
+/// ```
+/// use datas::sketch::TDigest;
+/// let mut digest = TDigest::new(100.0);
+/// digest.extend((1..=10_000).map(|i| i as f64));
+/// let p99 = digest.quantile(0.99).unwrap();
+/// assert!((p99 - 9900.0).abs() < 10.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TDigest {
+    /// Bounds the number of centroids, larger values are more accurate
+    compression: f64,
+    /// Centroids sorted by mean
+    centroids: Vec<Centroid>,
+    /// Values and centroids not merged yet
+    buffer: Vec<Centroid>,
+    /// Smallest and largest value, none until a value is added
+    range: Option<(f64, f64)>,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(100.0)
+    }
+}
+
+impl TDigest {
+    /// Empty digest, `compression` is clamped to `[10, 100000]`. The digest keeps at most about
+    /// `compression` centroids
+    pub fn new(compression: f64) -> Self {
+        let compression = if compression.is_nan() {
+            10.0
+        } else {
+            compression.clamp(10.0, MAX_COMPRESSION)
+        };
+        Self::from_parts(compression, Vec::new(), Vec::new(), None)
+    }
+
+    /// Builds digest from validated parts, shared by the constructor and deserialization
+    pub(crate) fn from_parts(
+        compression: f64,
+        centroids: Vec<Centroid>,
+        buffer: Vec<Centroid>,
+        range: Option<(f64, f64)>,
+    ) -> Self {
+        Self {
+            compression,
+            centroids,
+            buffer,
+            range,
+        }
+    }
+
+    pub fn compression(&self) -> f64 {
+        self.compression
+    }
+
+    /// Number of values added
+    pub fn count(&self) -> u64 {
+        self.weight() as u64
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.range = widen(self.range, Some((value, value)));
+        self.buffer.push(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+        if self.buffer.len() >= 5 * self.compression as usize {
+            self.compress();
+        }
+    }
+
+    /// Adds values summarized by another digest
+    pub fn merge(&mut self, other: &TDigest) {
+        self.buffer.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        self.range = widen(self.range, other.range);
+        self.compress();
+    }
+
+    pub fn min(&self) -> Result<f64, StatsError> {
+        Ok(self.range()?.0)
+    }
+
+    pub fn max(&self) -> Result<f64, StatsError> {
+        Ok(self.range()?.1)
+    }
+
+    /// Approximate `q` quantile, interpolating between centroid means
+    pub fn quantile(&self, q: f64) -> Result<f64, StatsError> {
+        check_probability(q)?;
+        let (min, max) = self.range()?;
+        let merged;
+        let centroids = if self.buffer.is_empty() {
+            &self.centroids
+        } else {
+            let mut digest = self.clone();
+            digest.compress();
+            merged = digest.centroids;
+            &merged
+        };
+        if q == 0.0 || min == max {
+            return Ok(min);
+        }
+        if q == 1.0 {
+            return Ok(max);
+        }
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let index = q * total;
+        let first = centroids[0];
+        // each centroid is centered at the middle of its weight, the halves before the first and
+        // after the last one are interpolated towards the extremes
+        if index < first.weight / 2.0 {
+            return Ok(min + (first.mean - min) * index / (first.weight / 2.0));
+        }
+        let mut center = first.weight / 2.0;
+        for pair in centroids.windows(2) {
+            let gap = (pair[0].weight + pair[1].weight) / 2.0;
+            if index < center + gap {
+                let t = (index - center) / gap;
+                return Ok(pair[0].mean + t * (pair[1].mean - pair[0].mean));
+            }
+            center += gap;
+        }
+        let last = centroids[centroids.len() - 1];
+        let t = ((index - center) / (last.weight / 2.0)).min(1.0);
+        Ok(last.mean + t * (max - last.mean))
+    }
+
+    /// Approximate fraction of values less than or equal to `value`
+    pub fn cdf(&self, value: f64) -> Result<f64, StatsError> {
+        let (min, max) = self.range()?;
+        if value < min {
+            return Ok(0.0);
+        }
+        if value >= max {
+            return Ok(1.0);
+        }
+        let mut digest = self.clone();
+        digest.compress();
+        let centroids = &digest.centroids;
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let first = centroids[0];
+        if value < first.mean {
+            let t = (value - min) / (first.mean - min);
+            return Ok(t * first.weight / 2.0 / total);
+        }
+        let mut center = first.weight / 2.0;
+        for pair in centroids.windows(2) {
+            let gap = (pair[0].weight + pair[1].weight) / 2.0;
+            if value < pair[1].mean {
+                let t = (value - pair[0].mean) / (pair[1].mean - pair[0].mean);
+                return Ok((center + t * gap) / total);
+            }
+            center += gap;
+        }
+        let last = centroids[centroids.len() - 1];
+        let t = (value - last.mean) / (max - last.mean);
+        Ok((center + t * last.weight / 2.0) / total)
+    }
+
+    fn weight(&self) -> f64 {
+        self.centroids
+            .iter()
+            .chain(&self.buffer)
+            .map(|c| c.weight)
+            .sum()
+    }
+
+    fn range(&self) -> Result<(f64, f64), StatsError> {
+        self.range.ok_or(StatsError::Empty)
+    }
+
+    /// Merges the buffer into the centroids in one pass over all of them sorted by mean
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.buffer);
+        all.append(&mut self.centroids);
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = all.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::with_capacity(all.len().min(self.compression as usize));
+        let mut values = all.into_iter();
+        let Some(mut current) = values.next() else {
+            return;
+        };
+        let mut before = 0.0;
+        let mut limit = total * self.next_limit(0.0, total);
+        for next in values {
+            if before + current.weight + next.weight <= limit {
+                current.weight += next.weight;
+                current.mean += (next.mean - current.mean) * next.weight / current.weight;
+            } else {
+                before += current.weight;
+                limit = total * self.next_limit(before / total, total);
+                merged.push(current);
+                current = next;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    /// Quantile where the scale function is one larger than at `q`, a centroid starting at `q`
+    /// may cover values up to it. Uses the logistic `k2` scale function, normalized so about
+    /// `compression` centroids remain for `total` values
+    fn next_limit(&self, q: f64, total: f64) -> f64 {
+        let normalizer = 4.0 * (total / self.compression).max(1.0).ln() + 24.0;
+        let odds = (1.0 - q) / q * (-normalizer / self.compression).exp();
+        1.0 / (1.0 + odds)
+    }
+}
+
+impl Extend<f64> for TDigest {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for value in values {
+            self.push(value);
+        }
+    }
+}
+
+/// KLL sketch (Karnin, Lang and Liberty), a hierarchy of compactors where items on level `h`
+/// stand for `2^h` values
+/// ```
+/// use datas::sketch::KllSketch;
+/// let mut sketch = KllSketch::new(200);
+/// sketch.extend((0..100_000).map(|i| (i * 7919 % 100_000) as f64));
+/// let median = sketch.quantile(0.5).unwrap();
+/// assert!((median / 100_000.0 - 0.5).abs() <= sketch.normalized_rank_error());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KllSketch {
+    /// Capacity of the top compactor, the others shrink geometrically below it
+    k: usize,
+    compactors: Vec<Vec<f64>>,
+    count: u64,
+    /// Smallest and largest value, none until a value is added
+    range: Option<(f64, f64)>,
+    /// State of the generator choosing which half of a compactor is promoted
+    state: u64,
+}
+
+impl Default for KllSketch {
+    fn default() -> Self {
+        Self::new(200)
+    }
+}
+
+impl KllSketch {
+    /// Empty sketch, `k` is clamped to `[8, 65535]`. The sketch keeps about `3k` values
+    pub fn new(k: usize) -> Self {
+        Self::from_parts(
+            k.clamp(8, u16::MAX as usize),
+            vec![Vec::new()],
+            0,
+            None,
+            0x5eed,
+        )
+    }
+
+    /// Builds sketch from validated parts, shared by the constructor and deserialization
+    pub(crate) fn from_parts(
+        k: usize,
+        compactors: Vec<Vec<f64>>,
+        count: u64,
+        range: Option<(f64, f64)>,
+        state: u64,
+    ) -> Self {
+        Self {
+            k,
+            compactors,
+            count,
+            range,
+            state,
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Number of values added
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Rank error of quantiles relative to the count that holds with 99% confidence, as estimated
+    /// empirically for KLL sketches by Apache DataSketches
+    pub fn normalized_rank_error(&self) -> f64 {
+        2.296 / (self.k as f64).powf(0.9723)
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.range = widen(self.range, Some((value, value)));
+        self.compactors[0].push(value);
+        self.compact_while_full();
+    }
+
+    /// Adds values summarized by another sketch. Fails with `CountOverflow` and keeps this sketch
+    /// when the counts add up to more than `u64::MAX`, which only deserialized sketches can reach
+    pub fn merge(&mut self, other: &KllSketch) -> Result<(), StatsError> {
+        let count = self
+            .count
+            .checked_add(other.count)
+            .ok_or(StatsError::CountOverflow)?;
+        while self.compactors.len() < other.compactors.len() {
+            self.compactors.push(Vec::new());
+        }
+        for (level, items) in other.compactors.iter().enumerate() {
+            self.compactors[level].extend_from_slice(items);
+        }
+        self.count = count;
+        self.range = widen(self.range, other.range);
+        self.compact_while_full();
+        Ok(())
+    }
+
+    pub fn min(&self) -> Result<f64, StatsError> {
+        Ok(self.range()?.0)
+    }
+
+    pub fn max(&self) -> Result<f64, StatsError> {
+        Ok(self.range()?.1)
+    }
+
+    /// Approximate `q` quantile, a retained value whose rank is within `normalized_rank_error`
+    /// of `q` with high probability
+    pub fn quantile(&self, q: f64) -> Result<f64, StatsError> {
+        check_probability(q)?;
+        let (min, max) = self.range()?;
+        if q == 0.0 {
+            return Ok(min);
+        }
+        if q == 1.0 {
+            return Ok(max);
+        }
+        let target = q * self.count as f64;
+        let mut cumulative = 0.0;
+        for (value, weight) in self.weighted_items() {
+            cumulative += weight;
+            if cumulative >= target {
+                return Ok(value);
+            }
+        }
+        Ok(max)
+    }
+
+    /// Approximate fraction of values less than or equal to `value`
+    pub fn rank(&self, value: f64) -> Result<f64, StatsError> {
+        self.range()?;
+        let below: f64 = self
+            .weighted_items()
+            .into_iter()
+            .take_while(|&(item, _)| item <= value)
+            .map(|(_, weight)| weight)
+            .sum();
+        Ok(below / self.count as f64)
+    }
+
+    /// Retained values sorted with the number of values they stand for
+    fn weighted_items(&self) -> Vec<(f64, f64)> {
+        let mut items: Vec<(f64, f64)> = self
+            .compactors
+            .iter()
+            .enumerate()
+            .flat_map(|(level, items)| {
+                let weight = 2f64.powi(level as i32);
+                items.iter().map(move |&item| (item, weight))
+            })
+            .collect();
+        items.sort_by(|a, b| a.0.total_cmp(&b.0));
+        items
+    }
+
+    fn range(&self) -> Result<(f64, f64), StatsError> {
+        self.range.ok_or(StatsError::Empty)
+    }
+
+    /// Capacity of compactor on `level`, `(2/3)^depth` of `k` where depth counts levels above it
+    fn capacity(&self, level: usize) -> usize {
+        let depth = (self.compactors.len() - level - 1) as i32;
+        ((self.k as f64 * (2.0f64 / 3.0).powi(depth)).ceil() as usize).max(2)
+    }
+
+    fn compact_while_full(&mut self) {
+        loop {
+            let size: usize = self.compactors.iter().map(Vec::len).sum();
+            let capacity: usize = (0..self.compactors.len())
+                .map(|level| self.capacity(level))
+                .sum();
+            if size < capacity {
+                return;
+            }
+            let Some(level) = (0..self.compactors.len())
+                .find(|&level| self.compactors[level].len() >= self.capacity(level))
+            else {
+                return;
+            };
+            self.compact(level);
+        }
+    }
+
+    /// Sorts compactor on `level` and promotes every other item to the level above, an odd item
+    /// out stays. Levels stay below 64 as the count bounds the `2^level` values an item stands for
+    fn compact(&mut self, level: usize) {
+        if level + 1 == self.compactors.len() {
+            self.compactors.push(Vec::new());
+        }
+        let mut items = std::mem::take(&mut self.compactors[level]);
+        items.sort_by(f64::total_cmp);
+        let kept = if items.len() % 2 == 1 {
+            vec![items.remove(0)]
+        } else {
+            Vec::new()
+        };
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let offset = (self.state >> 63) as usize;
+        let promoted = items.iter().skip(offset).step_by(2).copied();
+        self.compactors[level + 1].extend(promoted);
+        self.compactors[level] = kept;
+    }
+}
+
+impl Extend<f64> for KllSketch {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for value in values {
+            self.push(value);
+        }
+    }
+}
+
+/// Single quantile estimated with the P² algorithm (Jain and Chlamtac) from five markers, whose
+/// heights are adjusted with piecewise parabolic interpolation. Uses constant memory but cannot
+/// be merged
+/// ```
+/// use datas::sketch::P2Quantile;
+/// let mut p95 = P2Quantile::new(0.95).unwrap();
+/// p95.extend((0..10_000).map(|i| (i * 7919 % 10_000) as f64));
+/// assert!((p95.quantile().unwrap() - 9500.0).abs() < 50.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct P2Quantile {
+    p: f64,
+    count: u64,
+    /// Marker heights, the first values until five were added
+    heights: [f64; 5],
+    /// 1 based ranks of the markers
+    positions: [f64; 5],
+}
+
+impl P2Quantile {
+    /// Estimator of the `p` quantile, fails unless `p` is in `[0, 1]`
+    pub fn new(p: f64) -> Result<Self, StatsError> {
+        check_probability(p)?;
+        Ok(Self::from_parts(p, 0, [0.0; 5], [1.0, 2.0, 3.0, 4.0, 5.0]))
+    }
+
+    /// Builds estimator from validated parts, shared by the constructor and deserialization
+    pub(crate) fn from_parts(p: f64, count: u64, heights: [f64; 5], positions: [f64; 5]) -> Self {
+        Self {
+            p,
+            count,
+            heights,
+            positions,
+        }
+    }
+
+    /// Probability of the estimated quantile
+    pub fn probability(&self) -> f64 {
+        self.p
+    }
+
+    /// Number of values added
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        if self.count < 5 {
+            self.heights[self.count as usize] = value;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(f64::total_cmp);
+            }
+            return;
+        }
+        self.count += 1;
+        let heights = &mut self.heights;
+        let cell = if value < heights[0] {
+            heights[0] = value;
+            0
+        } else if value >= heights[4] {
+            heights[4] = value;
+            3
+        } else {
+            (0..4).find(|&i| value < heights[i + 1]).unwrap_or(3)
+        };
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        // ranks where the inner markers should be, computed from the count instead of
+        // accumulating increments so they do not drift
+        let n = (self.count - 1) as f64;
+        let p = self.p;
+        let desired = [1.0 + n * p / 2.0, 1.0 + n * p, 1.0 + n * (1.0 + p) / 2.0];
+        for i in 1..4 {
+            let offset = desired[i - 1] - self.positions[i];
+            let (n, q) = (&self.positions, &self.heights);
+            if (offset >= 1.0 && n[i + 1] - n[i] > 1.0)
+                || (offset <= -1.0 && n[i - 1] - n[i] < -1.0)
+            {
+                let d = offset.signum();
+                let parabolic = q[i]
+                    + d / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+                self.heights[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                self.positions[i] += d;
+            }
+        }
+    }
+
+    /// Current estimate, exact (`QuantileMethod::Linear`) until more than five values were added
+    pub fn quantile(&self) -> Result<f64, StatsError> {
+        if self.count == 0 {
+            return Err(StatsError::Empty);
+        }
+        if self.count <= 5 {
+            let mut sorted = self.heights[..self.count as usize].to_vec();
+            sorted.sort_by(f64::total_cmp);
+            return Ok(quantile::quantile_sorted(
+                &sorted,
+                self.p,
+                QuantileMethod::Linear,
+            ));
+        }
+        Ok(self.heights[2])
+    }
+}
+
+impl Extend<f64> for P2Quantile {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for value in values {
+            self.push(value);
+        }
+    }
+}
+
+/// Smallest and largest value of both ranges
+pub(crate) fn widen(range: Option<(f64, f64)>, other: Option<(f64, f64)>) -> Option<(f64, f64)> {
+    match (range, other) {
+        (Some(range), Some(other)) => Some((range.0.min(other.0), range.1.max(other.1))),
+        (range, other) => range.or(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const N: usize = 100_000;
+
+    /// Permutation of `0..N` so values arrive in no particular order
+    fn stream() -> impl Iterator<Item = f64> {
+        (0..N).map(|i| (i * 7919 % N) as f64)
+    }
+
+    /// Rank of the value relative to `N`, the data holds every integer below `N` once
+    fn rank_error(value: f64, q: f64) -> f64 {
+        (value / N as f64 - q).abs()
+    }
+
+    const PROBABILITIES: [f64; 9] = [0.001, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 0.999];
+
+    #[test]
+    fn t_digest_accuracy() {
+        let mut digest = TDigest::default();
+        digest.extend(stream());
+        assert_eq!(digest.count(), N as u64);
+        for q in PROBABILITIES {
+            let value = digest.quantile(q).unwrap();
+            // the bound shrinks towards the tails, where t-digest is most accurate
+            assert!(
+                rank_error(value, q) <= 0.01 * (q * (1.0 - q)).sqrt(),
+                "{}",
+                q
+            );
+            assert!((digest.cdf(value).unwrap() - q).abs() < 1e-3);
+        }
+        assert_eq!(digest.quantile(0.0), Ok(0.0));
+        assert_eq!(digest.quantile(1.0), Ok((N - 1) as f64));
+        let mut compressed = digest.clone();
+        compressed.compress();
+        assert!(compressed.centroids.len() <= 100);
+    }
+
+    #[test]
+    fn t_digest_merge() {
+        let values: Vec<f64> = stream().collect();
+        let mut total = TDigest::default();
+        for chunk in values.chunks(N / 16) {
+            let mut part = TDigest::default();
+            part.extend(chunk.iter().copied());
+            total.merge(&part);
+        }
+        assert_eq!(total.count(), N as u64);
+        for q in PROBABILITIES {
+            // merged centroids were clustered twice, allow twice the error
+            let value = total.quantile(q).unwrap();
+            assert!(rank_error(value, q) <= 0.02 * (q * (1.0 - q)).sqrt());
+        }
+        let mut small = TDigest::default();
+        small.extend([3.0, 1.0, f64::NAN, 2.0]);
+        assert_eq!(small.count(), 3);
+        assert_eq!(small.quantile(0.5), Ok(2.0));
+        assert_eq!((small.min(), small.max()), (Ok(1.0), Ok(3.0)));
+        assert_eq!(small.cdf(0.5), Ok(0.0));
+    }
+
+    #[test]
+    fn kll_accuracy_and_merge() {
+        let mut sketch = KllSketch::default();
+        sketch.extend(stream());
+        assert_eq!(sketch.count(), N as u64);
+        let retained: usize = sketch.compactors.iter().map(Vec::len).sum();
+        assert!(retained <= 3 * sketch.k());
+        let bound = sketch.normalized_rank_error();
+        for q in PROBABILITIES {
+            let value = sketch.quantile(q).unwrap();
+            assert!(rank_error(value, q) <= bound, "{}", q);
+            assert!((sketch.rank(value).unwrap() - q).abs() <= bound);
+        }
+
+        let values: Vec<f64> = stream().collect();
+        let mut total = KllSketch::default();
+        for chunk in values.chunks(N / 10) {
+            let mut part = KllSketch::default();
+            part.extend(chunk.iter().copied());
+            total.merge(&part).unwrap();
+        }
+        assert_eq!(total.count(), N as u64);
+        // every retained item on level `h` stands for `2^h` values
+        let weight: u64 = total
+            .compactors
+            .iter()
+            .enumerate()
+            .map(|(level, items)| items.len() as u64 * (1 << level))
+            .sum();
+        assert_eq!(weight, N as u64);
+        for q in PROBABILITIES {
+            assert!(rank_error(total.quantile(q).unwrap(), q) <= bound);
+        }
+        assert_eq!(total.min(), Ok(0.0));
+        assert_eq!(total.quantile(1.0), Ok((N - 1) as f64));
+    }
+
+    #[test]
+    fn p2_estimates() {
+        for q in [0.05, 0.5, 0.9, 0.99] {
+            let mut estimator = P2Quantile::new(q).unwrap();
+            estimator.extend(stream());
+            assert_eq!(estimator.count(), N as u64);
+            assert!(
+                rank_error(estimator.quantile().unwrap(), q) < 0.005,
+                "{}",
+                q
+            );
+        }
+        let mut small = P2Quantile::new(0.5).unwrap();
+        small.extend([4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(small.quantile(), Ok(2.5));
+        small.extend([f64::NAN, 5.0]);
+        assert_eq!(small.quantile(), Ok(3.0));
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(TDigest::default().quantile(0.5), Err(StatsError::Empty));
+        assert_eq!(KllSketch::default().rank(1.0), Err(StatsError::Empty));
+        assert_eq!(
+            P2Quantile::new(0.5).unwrap().quantile(),
+            Err(StatsError::Empty)
+        );
+        assert_eq!(
+            P2Quantile::new(1.5),
+            Err(StatsError::InvalidProbability { probability: 1.5 })
+        );
+        let mut sketch = KllSketch::new(1);
+        assert_eq!(sketch.k(), 8);
+        sketch.push(1.0);
+        assert!(matches!(
+            sketch.quantile(-0.1),
+            Err(StatsError::InvalidProbability { .. })
+        ));
+        assert_eq!(TDigest::new(f64::NAN).compression(), 10.0);
+        for compression in [1e19, f64::INFINITY] {
+            let mut digest = TDigest::new(compression);
+            assert_eq!(digest.compression(), MAX_COMPRESSION);
+            digest.extend([3.0, 1.0, 2.0]);
+            assert_eq!(digest.quantile(0.5), Ok(2.0));
+        }
+    }
+}
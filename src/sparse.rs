@@ -0,0 +1,351 @@
+//! Vectors with few nonzero components, stored as index and value pairs sorted by index.
+//!
+//! Operations touch only the stored entries, so their cost depends on the number of nonzero
+//! components and not on the dimensions.
+
+use std::cmp::Ordering;
+use std::ops::{Mul, Neg};
+
+use crate::fixed::Scalar;
+use crate::vector::{Component, Vector, VectorError};
+
+/// Sparse vector, components that are not stored are zero
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SparseVector<T> {
+    /// Strictly increasing indices of the nonzero components
+    indices: Vec<u64>,
+    values: Vec<T>,
+    dimensions: u64,
+}
+
+impl<T> SparseVector<T> {
+    /// Builds vector from sorted unique indices, shared by the constructors and deserialization
+    pub(crate) fn from_parts(indices: Vec<u64>, values: Vec<T>, dimensions: u64) -> Self {
+        Self {
+            indices,
+            values,
+            dimensions,
+        }
+    }
+
+    pub fn dimensions(&self) -> u64 {
+        self.dimensions
+    }
+
+    /// Indices of the stored components in increasing order
+    pub fn indices(&self) -> &[u64] {
+        &self.indices
+    }
+
+    /// Values of the stored components, in the order of `indices`
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Number of stored (nonzero) components
+    pub fn nnz(&self) -> usize {
+        self.indices.len()
+    }
+
+    fn check_dimensions(&self, dimensions: u64) -> Result<(), VectorError> {
+        if self.dimensions != dimensions {
+            return Err(VectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: dimensions,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T: Scalar> SparseVector<T> {
+    /// Public constructor of SparseVector from `(index, value)` pairs in any order,
+    /// values of repeated indices are summed and zeros are not stored
+    /// ```
+    /// use datas::sparse::SparseVector;
+    /// let sparse = SparseVector::new(100_000, vec![(70_000, 2.0), (5, 1.0), (5, 0.5)]).unwrap();
+    /// assert_eq!(sparse.indices(), &[5, 70_000]);
+    /// assert_eq!(sparse.values(), &[1.5, 2.0]);
+    /// ```
+    pub fn new(dimensions: u64, mut entries: Vec<(u64, T)>) -> Result<Self, VectorError> {
+        if let Some(&(index, _)) = entries.iter().find(|(index, _)| *index >= dimensions) {
+            return Err(VectorError::IndexOutOfBound { index, dimensions });
+        }
+        entries.sort_by_key(|&(index, _)| index);
+        let mut indices: Vec<u64> = Vec::with_capacity(entries.len());
+        let mut values: Vec<T> = Vec::with_capacity(entries.len());
+        for (index, value) in entries {
+            if indices.last() == Some(&index) {
+                let last = values.len() - 1;
+                values[last] = values[last] + value;
+            } else {
+                indices.push(index);
+                values.push(value);
+            }
+        }
+        let mut sparse = Self::from_parts(indices, values, dimensions);
+        sparse.remove_zeros();
+        Ok(sparse)
+    }
+
+    /// Vector with every component equal to zero
+    pub fn zeros(dimensions: u64) -> Self {
+        Self::from_parts(Vec::new(), Vec::new(), dimensions)
+    }
+
+    /// Stores the nonzero components of a dense vector
+    pub fn from_vector(vector: &Vector<T>) -> Self {
+        let (indices, values) = vector
+            .components()
+            .iter()
+            .enumerate()
+            .filter(|(_, &value)| value != T::default())
+            .map(|(index, &value)| (index as u64, value))
+            .unzip();
+        Self::from_parts(indices, values, vector.dimensions())
+    }
+
+    /// Converts to dense `Vector`, allocating every component
+    pub fn to_vector(&self) -> Vector<T> {
+        let mut components = vec![T::default(); self.dimensions as usize];
+        for (&index, &value) in self.indices.iter().zip(&self.values) {
+            components[index as usize] = value;
+        }
+        Vector::from_components(components)
+    }
+
+    /// Component at `index`, zero when it is not stored and `None` outside of the vector
+    pub fn get(&self, index: u64) -> Option<T> {
+        if index >= self.dimensions {
+            return None;
+        }
+        Some(match self.indices.binary_search(&index) {
+            Ok(position) => self.values[position],
+            Err(_) => T::default(),
+        })
+    }
+
+    /// Dot product of two sparse vectors, walks both lists of indices once
+    /// ```
+    /// use datas::sparse::SparseVector;
+    /// let a = SparseVector::new(1000, vec![(1, 2), (500, 3)]).unwrap();
+    /// let b = SparseVector::new(1000, vec![(500, 4), (999, 5)]).unwrap();
+    /// assert_eq!(a.dot(&b), Ok(12));
+    /// ```
+    pub fn dot(&self, vector: &SparseVector<T>) -> Result<T, VectorError> {
+        self.check_dimensions(vector.dimensions)?;
+        let (mut i, mut j) = (0, 0);
+        let mut sum = T::default();
+        while i < self.indices.len() && j < vector.indices.len() {
+            match self.indices[i].cmp(&vector.indices[j]) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    sum = sum + self.values[i] * vector.values[j];
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        Ok(sum)
+    }
+
+    /// Dot product with a dense vector, reads only the components at stored indices
+    pub fn dot_dense(&self, vector: &Vector<T>) -> Result<T, VectorError> {
+        self.check_dimensions(vector.dimensions())?;
+        let components = vector.components();
+        Ok(self
+            .indices
+            .iter()
+            .zip(&self.values)
+            .fold(T::default(), |sum, (&index, &value)| {
+                sum + value * components[index as usize]
+            }))
+    }
+
+    /// Addition method that mutates vector similar to AddAssign trait
+    pub fn add(&mut self, vector: &SparseVector<T>) -> Result<(), VectorError> {
+        self.check_dimensions(vector.dimensions)?;
+        self.merge(vector, |a, b| a + b);
+        Ok(())
+    }
+
+    /// Subtraction method that mutates vector similar to SubAssign trait
+    pub fn sub(&mut self, vector: &SparseVector<T>) -> Result<(), VectorError> {
+        self.check_dimensions(vector.dimensions)?;
+        self.merge(vector, |a, b| a - b);
+        Ok(())
+    }
+
+    pub fn magnitude(&self) -> f64
+    where
+        T: Component,
+    {
+        self.values
+            .iter()
+            .map(|&value| value.as_f64().powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Combines components at the union of indices, a missing component counts as zero
+    fn merge(&mut self, vector: &SparseVector<T>, combine: impl Fn(T, T) -> T) {
+        let capacity = self.nnz() + vector.nnz();
+        let mut indices = Vec::with_capacity(capacity);
+        let mut values = Vec::with_capacity(capacity);
+        let zero = T::default();
+        let (mut i, mut j) = (0, 0);
+        while i < self.indices.len() || j < vector.indices.len() {
+            let order = match (self.indices.get(i), vector.indices.get(j)) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (Some(_), None) => Ordering::Less,
+                _ => Ordering::Greater,
+            };
+            let (index, value) = match order {
+                Ordering::Less => {
+                    i += 1;
+                    (self.indices[i - 1], combine(self.values[i - 1], zero))
+                }
+                Ordering::Greater => {
+                    j += 1;
+                    (vector.indices[j - 1], combine(zero, vector.values[j - 1]))
+                }
+                Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                    (
+                        self.indices[i - 1],
+                        combine(self.values[i - 1], vector.values[j - 1]),
+                    )
+                }
+            };
+            if value != zero {
+                indices.push(index);
+                values.push(value);
+            }
+        }
+        self.indices = indices;
+        self.values = values;
+    }
+
+    /// Drops stored components that became zero
+    fn remove_zeros(&mut self) {
+        let zero = T::default();
+        let mut position = 0;
+        self.indices.retain(|_| {
+            position += 1;
+            self.values[position - 1] != zero
+        });
+        self.values.retain(|&value| value != zero);
+    }
+}
+
+impl<T: Scalar> From<&Vector<T>> for SparseVector<T> {
+    fn from(vector: &Vector<T>) -> Self {
+        Self::from_vector(vector)
+    }
+}
+
+impl<T: Scalar> Neg for SparseVector<T> {
+    type Output = SparseVector<T>;
+
+    fn neg(mut self) -> SparseVector<T> {
+        self.values.iter_mut().for_each(|value| *value = -*value);
+        self
+    }
+}
+
+impl<T: Scalar> Mul<T> for SparseVector<T> {
+    type Output = SparseVector<T>;
+
+    fn mul(mut self, scalar: T) -> SparseVector<T> {
+        self.values
+            .iter_mut()
+            .for_each(|value| *value = *value * scalar);
+        self.remove_zeros();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn construction() {
+        let sparse = SparseVector::new(10, vec![(7, 1), (2, 3), (7, -1), (4, 0)]).unwrap();
+        assert_eq!(sparse.indices(), &[2]);
+        assert_eq!(sparse.values(), &[3]);
+        assert_eq!(sparse.nnz(), 1);
+        assert_eq!(sparse.get(2), Some(3));
+        assert_eq!(sparse.get(7), Some(0));
+        assert_eq!(sparse.get(10), None);
+        assert_eq!(
+            SparseVector::new(10, vec![(3, 1.0), (10, 2.0)]),
+            Err(VectorError::IndexOutOfBound {
+                index: 10,
+                dimensions: 10
+            })
+        );
+        assert_eq!(
+            SparseVector::<i64>::zeros(5).to_vector(),
+            Vector::<i64>::new(vec![0; 5])
+        );
+    }
+
+    #[test]
+    fn dense_conversion() {
+        let dense = Vector::<f64>::new(vec![0.0, 1.5, 0.0, -2.0]);
+        let sparse = SparseVector::from(&dense);
+        assert_eq!(sparse.indices(), &[1, 3]);
+        assert_eq!(sparse.to_vector(), dense);
+        assert_eq!(sparse.magnitude(), dense.magnitude());
+    }
+
+    #[test]
+    fn dot_products() {
+        let a = SparseVector::new(100_000, vec![(3, 2.0), (50_000, 1.5), (99_999, -1.0)]).unwrap();
+        let b = SparseVector::new(100_000, vec![(50_000, 2.0), (99_999, 4.0), (7, 9.0)]).unwrap();
+        assert_eq!(a.dot(&b), Ok(-1.0));
+        assert_eq!(a.dot_dense(&b.to_vector()), Ok(-1.0));
+        assert_eq!(a.dot(&SparseVector::zeros(100_000)), Ok(0.0));
+
+        let small = SparseVector::new(3, vec![(0, 1)]).unwrap();
+        let dense = Vector::<i64>::new(vec![4, 5]);
+        let mismatch = Err(VectorError::DimensionMismatch {
+            expected: 3,
+            actual: 2,
+        });
+        assert_eq!(small.dot_dense(&dense), mismatch);
+        assert_eq!(small.dot(&SparseVector::from(&dense)), mismatch);
+    }
+
+    #[test]
+    fn addition_and_scaling() {
+        let mut a = SparseVector::new(6, vec![(0, 1), (2, 2), (5, 3)]).unwrap();
+        let b = SparseVector::new(6, vec![(1, 4), (2, -2), (5, 1)]).unwrap();
+        a.add(&b).unwrap();
+        assert_eq!(a.indices(), &[0, 1, 5]);
+        assert_eq!(a.values(), &[1, 4, 4]);
+        a.sub(&b).unwrap();
+        assert_eq!(
+            a,
+            SparseVector::new(6, vec![(0, 1), (2, 2), (5, 3)]).unwrap()
+        );
+        assert_eq!(
+            a.add(&SparseVector::zeros(5)),
+            Err(VectorError::DimensionMismatch {
+                expected: 6,
+                actual: 5
+            })
+        );
+
+        let scaled = a.clone() * 3;
+        assert_eq!(scaled.values(), &[3, 6, 9]);
+        // scaling by zero leaves nothing to store
+        let zero = a.values()[0] - 1;
+        assert_eq!((a.clone() * zero).nnz(), 0);
+        assert_eq!((-a).values(), &[-1, -2, -3]);
+    }
+}
@@ -1,19 +1,126 @@
-use std::ops::Mul;
+use std::fmt;
+use std::ops::{Mul, Neg};
+
+use crate::format::{self, Cell, PrintOptions};
+use crate::norm;
+use crate::simd;
 
 /// This enum represents possible errors that can happen during usage of the library.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VectorError {
-    DimensionMismatch,
+    /// Vector with `actual` dimensions was used where `expected` were required
+    DimensionMismatch { expected: u64, actual: u64 },
+    /// Operation needs a direction but the vector has zero magnitude
+    ZeroVector,
+    /// Vector at `index` is a linear combination of the vectors before it
+    LinearlyDependent { index: u64 },
+    /// Component `index` does not exist in a vector with `dimensions` components
+    IndexOutOfBound { index: u64, dimensions: u64 },
+    /// Minkowski exponent `p` is NaN or below 1, so the distance is not a metric
+    InvalidExponent { p: f64 },
+    /// Covariance matrix of a Mahalanobis distance cannot be inverted
+    SingularCovariance,
+}
+
+impl fmt::Display for VectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VectorError::DimensionMismatch { expected, actual } => write!(
+                f,
+                "expected vector with {} dimensions, found {}",
+                expected, actual
+            ),
+            VectorError::ZeroVector => f.write_str("vector has zero magnitude"),
+            VectorError::LinearlyDependent { index } => write!(
+                f,
+                "vector {} is a linear combination of the previous vectors",
+                index
+            ),
+            VectorError::IndexOutOfBound { index, dimensions } => write!(
+                f,
+                "index {} is out of bound for vector with {} dimensions",
+                index, dimensions
+            ),
+            VectorError::InvalidExponent { p } => {
+                write!(f, "exponent {} is not at least 1", p)
+            }
+            VectorError::SingularCovariance => f.write_str("covariance matrix is singular"),
+        }
+    }
+}
+
+impl std::error::Error for VectorError {}
+
+/// Numeric types of components that can be used in floating point computations
+pub trait Component: Copy {
+    fn as_f64(self) -> f64;
+}
+
+impl Component for i64 {
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Component for f64 {
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Component for f32 {
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
 }
 
 /// Vector struct with private fields
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Vector<T> {
     /// Stores information about components of Vector inside well Vector
     components: Vec<T>,
     dimensions: u64,
 }
 
+impl<T> Vector<T> {
+    /// Builds vector from components, shared by the typed constructors
+    pub(crate) fn from_components(components: Vec<T>) -> Self {
+        let dimensions = components.len() as u64;
+        Self {
+            components,
+            dimensions,
+        }
+    }
+
+    /// Number of components of the vector
+    pub fn dimensions(&self) -> u64 {
+        self.dimensions
+    }
+
+    /// Components of the vector as stored
+    /// ```
+    /// use datas::vector::Vector;
+    /// let i_vector = Vector::<i64>::new(vec![3, 4]);
+    /// assert_eq!(i_vector.components(), &[3, 4]);
+    /// ```
+    pub fn components(&self) -> &[T] {
+        &self.components
+    }
+
+    /// Fails with `DimensionMismatch` unless both vectors have the same dimensions
+    pub(crate) fn check_dimensions<U>(&self, vector: &Vector<U>) -> Result<(), VectorError> {
+        if self.dimensions != vector.dimensions {
+            return Err(VectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: vector.dimensions,
+            });
+        }
+        Ok(())
+    }
+}
+
 impl Vector<i64> {
     /// Public constructor of Vector
     /// ```
@@ -29,11 +136,7 @@ impl Vector<i64> {
     }
 
     pub fn magnitude(&self) -> f64 {
-        self.components
-            .iter()
-            .map(|&component| (component as f64).powi(2))
-            .sum::<f64>()
-            .sqrt()
+        norm::l2(self)
     }
 
     /// Addition method that mutates vector similar to AddAssign trait
@@ -42,15 +145,21 @@ impl Vector<i64> {
             for (index, component) in self.components.iter_mut().enumerate() {
                 *component += vector.components[index];
             }
-            return Ok(());
+            Ok(())
         } else {
-            return Err(VectorError::DimensionMismatch);
+            Err(VectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: vector.dimensions,
+            })
         }
     }
 
     pub fn dot_product(&self, vector: &Vector<i64>) -> Result<i64, VectorError> {
         if self.dimensions != vector.dimensions {
-            return Err(VectorError::DimensionMismatch);
+            return Err(VectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: vector.dimensions,
+            });
         }
         Ok(self
             .components
@@ -59,6 +168,93 @@ impl Vector<i64> {
             .map(|(a, b)| a * b)
             .sum())
     }
+
+    /// Converts components to `f64`
+    pub fn to_f64(&self) -> Vector<f64> {
+        Vector::<f64>::new(self.components.iter().map(|&c| c as f64).collect())
+    }
+
+    /// Subtraction method that mutates vector similar to SubAssign trait
+    pub fn sub(&mut self, vector: &Vector<i64>) -> Result<(), VectorError> {
+        self.check_dimensions(vector)?;
+        for (component, other) in self.components.iter_mut().zip(&vector.components) {
+            *component -= other;
+        }
+        Ok(())
+    }
+
+    /// Unit vector pointing in the same direction
+    pub fn normalize(&self) -> Result<Vector<f64>, VectorError> {
+        self.to_f64().normalize_f()
+    }
+
+    /// Cross product of two 3 dimensional vectors
+    /// ```
+    /// use datas::vector::Vector;
+    /// let x = Vector::<i64>::new(vec![1, 0, 0]);
+    /// let y = Vector::<i64>::new(vec![0, 1, 0]);
+    /// assert_eq!(x.cross(&y), Ok(Vector::<i64>::new(vec![0, 0, 1])));
+    /// ```
+    pub fn cross(&self, vector: &Vector<i64>) -> Result<Vector<i64>, VectorError> {
+        check_cross_dimensions(self.dimensions, vector.dimensions)?;
+        let (a, b) = (&self.components, &vector.components);
+        Ok(Vector::<i64>::new(vec![
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]))
+    }
+
+    /// Projection of vector onto the direction of `vector`
+    pub fn project_onto(&self, vector: &Vector<i64>) -> Result<Vector<f64>, VectorError> {
+        self.to_f64().project_onto_f(&vector.to_f64())
+    }
+
+    /// Component of vector perpendicular to `vector`, what is left after projection
+    pub fn reject_from(&self, vector: &Vector<i64>) -> Result<Vector<f64>, VectorError> {
+        self.to_f64().reject_from_f(&vector.to_f64())
+    }
+
+    /// Angle between vectors in radians, in range `[0, PI]`
+    pub fn angle_between(&self, vector: &Vector<i64>) -> Result<f64, VectorError> {
+        self.to_f64().angle_between_f(&vector.to_f64())
+    }
+
+    /// Linear interpolation, `t = 0` gives this vector and `t = 1` gives `vector`
+    pub fn lerp(&self, vector: &Vector<i64>, t: f64) -> Result<Vector<f64>, VectorError> {
+        self.to_f64().lerp_f(&vector.to_f64(), t)
+    }
+
+    /// Whether cosine of the angle between vectors is within `tol` of 0, zero vector is orthogonal to any vector
+    pub fn is_orthogonal(&self, vector: &Vector<i64>, tol: f64) -> Result<bool, VectorError> {
+        self.to_f64().is_orthogonal_f(&vector.to_f64(), tol)
+    }
+
+    /// Whether absolute cosine of the angle between vectors is within `tol` of 1, zero vector is parallel to any vector
+    pub fn is_parallel(&self, vector: &Vector<i64>, tol: f64) -> Result<bool, VectorError> {
+        self.to_f64().is_parallel_f(&vector.to_f64(), tol)
+    }
+}
+
+fn check_cross_dimensions(first: u64, second: u64) -> Result<(), VectorError> {
+    match (first, second) {
+        (3, 3) => Ok(()),
+        (3, actual) | (actual, _) => Err(VectorError::DimensionMismatch {
+            expected: 3,
+            actual,
+        }),
+    }
+}
+
+impl Neg for Vector<i64> {
+    type Output = Vector<i64>;
+
+    fn neg(mut self) -> Vector<i64> {
+        self.components
+            .iter_mut()
+            .for_each(|component| *component = -*component);
+        self
+    }
 }
 
 impl Mul<i64> for Vector<i64> {
@@ -81,12 +277,18 @@ impl Vector<f64> {
         }
     }
 
+    /// Euclidean length, falls back to `norm::l2` when squares of components overflow or underflow
+    /// ```
+    /// use datas::vector::Vector;
+    /// let huge = Vector::<f64>::new(vec![3e200, 4e200]);
+    /// assert!((huge.magnitude() / 5e200 - 1.0).abs() < 1e-15);
+    /// ```
     pub fn magnitude(&self) -> f64 {
-        self.components
-            .iter()
-            .map(|&component| component.powi(2))
-            .sum::<f64>()
-            .sqrt()
+        let fast = simd::norm(&self.components);
+        if fast.is_finite() && fast >= f64::MIN_POSITIVE.sqrt() {
+            return fast;
+        }
+        norm::l2(self)
     }
 
     pub fn add_i(&mut self, vector: &Vector<i64>) -> Result<(), VectorError> {
@@ -94,26 +296,25 @@ impl Vector<f64> {
             for (index, component) in self.components.iter_mut().enumerate() {
                 *component += vector.components[index] as f64;
             }
-            return Ok(());
+            Ok(())
         } else {
-            return Err(VectorError::DimensionMismatch);
+            Err(VectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: vector.dimensions,
+            })
         }
     }
 
     pub fn add_f(&mut self, vector: &Vector<f64>) -> Result<(), VectorError> {
-        if self.dimensions == vector.dimensions {
-            for (index, component) in self.components.iter_mut().enumerate() {
-                *component += vector.components[index];
-            }
-            return Ok(());
-        } else {
-            return Err(VectorError::DimensionMismatch);
-        }
+        simd::add_assign(&mut self.components, &vector.components)
     }
 
     pub fn dot_product_i(&self, vector: &Vector<i64>) -> Result<f64, VectorError> {
         if self.dimensions != vector.dimensions {
-            return Err(VectorError::DimensionMismatch);
+            return Err(VectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: vector.dimensions,
+            });
         }
         Ok(self
             .components
@@ -124,15 +325,144 @@ impl Vector<f64> {
     }
 
     pub fn dot_product_f(&self, vector: &Vector<f64>) -> Result<f64, VectorError> {
-        if self.dimensions != vector.dimensions {
-            return Err(VectorError::DimensionMismatch);
+        simd::dot(&self.components, &vector.components)
+    }
+
+    pub fn sub_i(&mut self, vector: &Vector<i64>) -> Result<(), VectorError> {
+        self.check_dimensions(vector)?;
+        for (component, other) in self.components.iter_mut().zip(&vector.components) {
+            *component -= *other as f64;
         }
-        Ok(self
-            .components
-            .iter()
-            .zip(&vector.components)
-            .map(|(a, b)| a * b)
-            .sum())
+        Ok(())
+    }
+
+    pub fn sub_f(&mut self, vector: &Vector<f64>) -> Result<(), VectorError> {
+        simd::sub_assign(&mut self.components, &vector.components)
+    }
+
+    /// Unit vector pointing in the same direction
+    /// ```
+    /// use datas::vector::Vector;
+    /// let f_vector = Vector::<f64>::new(vec![3.0, 4.0]);
+    /// assert_eq!(f_vector.normalize_f(), Ok(Vector::<f64>::new(vec![0.6, 0.8])));
+    /// ```
+    pub fn normalize_f(&self) -> Result<Vector<f64>, VectorError> {
+        let magnitude = self.magnitude();
+        if magnitude == 0.0 {
+            return Err(VectorError::ZeroVector);
+        }
+        Ok(Vector::<f64>::new(
+            self.components.iter().map(|c| c / magnitude).collect(),
+        ))
+    }
+
+    /// Cross product of two 3 dimensional vectors
+    pub fn cross_f(&self, vector: &Vector<f64>) -> Result<Vector<f64>, VectorError> {
+        check_cross_dimensions(self.dimensions, vector.dimensions)?;
+        let (a, b) = (&self.components, &vector.components);
+        Ok(Vector::<f64>::new(vec![
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]))
+    }
+
+    /// Projection of vector onto the direction of `vector`
+    /// ```
+    /// use datas::vector::Vector;
+    /// let f_vector = Vector::<f64>::new(vec![2.0, 3.0]);
+    /// let x = Vector::<f64>::new(vec![4.0, 0.0]);
+    /// assert_eq!(f_vector.project_onto_f(&x), Ok(Vector::<f64>::new(vec![2.0, 0.0])));
+    /// ```
+    pub fn project_onto_f(&self, vector: &Vector<f64>) -> Result<Vector<f64>, VectorError> {
+        let squared = vector.dot_product_f(vector)?;
+        if squared == 0.0 {
+            return Err(VectorError::ZeroVector);
+        }
+        let scale = self.dot_product_f(vector)? / squared;
+        Ok(vector.clone() * scale)
+    }
+
+    /// Component of vector perpendicular to `vector`, what is left after projection
+    pub fn reject_from_f(&self, vector: &Vector<f64>) -> Result<Vector<f64>, VectorError> {
+        let projection = self.project_onto_f(vector)?;
+        let mut rejection = self.clone();
+        rejection.sub_f(&projection)?;
+        Ok(rejection)
+    }
+
+    /// Angle between vectors in radians, in range `[0, PI]`
+    pub fn angle_between_f(&self, vector: &Vector<f64>) -> Result<f64, VectorError> {
+        self.check_dimensions(vector)?;
+        let (a, b) = (self.magnitude(), vector.magnitude());
+        if a == 0.0 || b == 0.0 {
+            return Err(VectorError::ZeroVector);
+        }
+        // Kahan's formula stays accurate for nearly parallel vectors where acos of the cosine does not
+        let (mut difference, mut sum) = (0.0, 0.0);
+        for (x, y) in self.components.iter().zip(&vector.components) {
+            difference += (x * b - y * a).powi(2);
+            sum += (x * b + y * a).powi(2);
+        }
+        Ok(2.0 * difference.sqrt().atan2(sum.sqrt()))
+    }
+
+    /// Linear interpolation, `t = 0` gives this vector and `t = 1` gives `vector`
+    pub fn lerp_f(&self, vector: &Vector<f64>, t: f64) -> Result<Vector<f64>, VectorError> {
+        self.check_dimensions(vector)?;
+        Ok(Vector::<f64>::new(
+            self.components
+                .iter()
+                .zip(&vector.components)
+                .map(|(a, b)| a + t * (b - a))
+                .collect(),
+        ))
+    }
+
+    /// Whether cosine of the angle between vectors is within `tol` of 0, zero vector is orthogonal to any vector
+    pub fn is_orthogonal_f(&self, vector: &Vector<f64>, tol: f64) -> Result<bool, VectorError> {
+        let dot = self.dot_product_f(vector)?;
+        Ok(dot.abs() <= tol * self.magnitude() * vector.magnitude())
+    }
+
+    /// Whether absolute cosine of the angle between vectors is within `tol` of 1, zero vector is parallel to any vector
+    pub fn is_parallel_f(&self, vector: &Vector<f64>, tol: f64) -> Result<bool, VectorError> {
+        let dot = self.dot_product_f(vector)?;
+        let magnitudes = self.magnitude() * vector.magnitude();
+        Ok(magnitudes - dot.abs() <= tol * magnitudes)
+    }
+}
+
+impl Vector<f32> {
+    /// Public constructor of Vector with single precision components, which halve the memory of
+    /// large collections such as embeddings
+    /// ```
+    /// use datas::vector::Vector;
+    /// let f_vector = Vector::<f32>::new(vec![3.0, 4.0]);
+    /// assert_eq!(f_vector.magnitude(), 5.0);
+    /// ```
+    pub fn new(components: Vec<f32>) -> Self {
+        Self::from_components(components)
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        // kernels accumulate squares in f32, which overflow and underflow much sooner than f64
+        let fast = simd::norm(&self.components);
+        if fast.is_finite() && fast >= (f32::MIN_POSITIVE as f64).sqrt() {
+            return fast;
+        }
+        norm::l2(self)
+    }
+}
+
+impl Neg for Vector<f64> {
+    type Output = Vector<f64>;
+
+    fn neg(mut self) -> Vector<f64> {
+        self.components
+            .iter_mut()
+            .for_each(|component| *component = -*component);
+        self
     }
 }
 
@@ -158,6 +488,94 @@ impl Mul<i64> for Vector<f64> {
     }
 }
 
+impl<T: fmt::Display> Vector<T> {
+    /// Renders vector NumPy style with aligned components
+    /// ```
+    /// use datas::format::PrintOptions;
+    /// use datas::vector::Vector;
+    /// let i_vector = Vector::<i64>::new(vec![3, 40]);
+    /// assert_eq!(i_vector.to_string_with(&PrintOptions::default()), "[ 3 40]");
+    /// ```
+    pub fn to_string_with(&self, options: &PrintOptions) -> String {
+        let elide = self.components.len() > options.threshold;
+        let cells = format::render_row(&self.components, elide, options);
+        let width = format::cell_width(cells.iter());
+        format!("[{}]", format::join_cells(&cells, width))
+    }
+
+    /// Renders vector as LaTeX column `bmatrix`
+    /// ```
+    /// use datas::format::PrintOptions;
+    /// use datas::vector::Vector;
+    /// let i_vector = Vector::<i64>::new(vec![3, 4]);
+    /// assert_eq!(
+    ///     i_vector.to_latex(&PrintOptions::default()),
+    ///     "\\begin{bmatrix}\n3 \\\\\n4\n\\end{bmatrix}"
+    /// );
+    /// ```
+    pub fn to_latex(&self, options: &PrintOptions) -> String {
+        let elide = self.components.len() > options.threshold;
+        let cells: Vec<String> = format::render_row(&self.components, elide, options)
+            .into_iter()
+            .map(|cell| match cell {
+                Cell::Value(s) => s,
+                Cell::Ellipsis => String::from("\\vdots"),
+            })
+            .collect();
+        let mut body = cells.join(" \\\\\n");
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        format!("\\begin{{bmatrix}}\n{}\\end{{bmatrix}}", body)
+    }
+
+    /// Renders vector as single row Markdown table with component indices in the header
+    /// ```
+    /// use datas::format::PrintOptions;
+    /// use datas::vector::Vector;
+    /// let i_vector = Vector::<i64>::new(vec![3, 4]);
+    /// assert_eq!(
+    ///     i_vector.to_markdown(&PrintOptions::default()),
+    ///     "| 0 | 1 |\n|---|---|\n| 3 | 4 |"
+    /// );
+    /// ```
+    pub fn to_markdown(&self, options: &PrintOptions) -> String {
+        let elide = self.components.len() > options.threshold;
+        let header: Vec<String> =
+            format::visible_indices(self.components.len(), elide, options.edge_items)
+                .into_iter()
+                .map(|index| match index {
+                    Some(i) => i.to_string(),
+                    None => String::from("..."),
+                })
+                .collect();
+        let cells: Vec<String> = format::render_row(&self.components, elide, options)
+            .into_iter()
+            .map(|cell| match cell {
+                Cell::Value(s) => s,
+                Cell::Ellipsis => String::from("..."),
+            })
+            .collect();
+        format!(
+            "| {} |\n|{}|\n| {} |",
+            header.join(" | "),
+            vec!["---"; header.len()].join("|"),
+            cells.join(" | ")
+        )
+    }
+}
+
+/// Formats vector NumPy style, precision can be given as `{:.2}`
+impl<T: fmt::Display> fmt::Display for Vector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = PrintOptions {
+            precision: f.precision(),
+            ..PrintOptions::default()
+        };
+        f.write_str(&self.to_string_with(&options))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,7 +619,7 @@ mod tests {
     fn ivec_addition() {
         let mut i_vector = Vector::<i64>::new(vec![3, 4]);
         let i_vector_2 = Vector::<i64>::new(vec![3, 4]);
-        let _ = i_vector.add(&i_vector_2).expect("mismatched dimensions");
+        i_vector.add(&i_vector_2).expect("mismatched dimensions");
         assert_eq!(
             i_vector,
             Vector::<i64> {
@@ -215,7 +633,7 @@ mod tests {
     fn fvec_and_fvec_addition() {
         let mut f_vector = Vector::<f64>::new(vec![3.5, 4.2]);
         let f_vector_2 = Vector::<f64>::new(vec![3.2, 4.1]);
-        let _ = f_vector.add_f(&f_vector_2).expect("mismatched dimensions");
+        f_vector.add_f(&f_vector_2).expect("mismatched dimensions");
         assert_eq!(
             f_vector,
             Vector::<f64> {
@@ -229,7 +647,7 @@ mod tests {
     fn fvec_and_ivec_addition() {
         let mut f_vector = Vector::<f64>::new(vec![3.5, 4.2]);
         let i_vector = Vector::<i64>::new(vec![3, 4]);
-        let _ = f_vector.add_i(&i_vector).expect("mismatched dimensions");
+        f_vector.add_i(&i_vector).expect("mismatched dimensions");
         assert_eq!(
             f_vector,
             Vector::<f64> {
@@ -244,7 +662,13 @@ mod tests {
         let mut i_vector = Vector::<i64>::new(vec![3, 4]);
         let i_vector_2 = Vector::<i64>::new(vec![3, 4, 5]);
         let res = i_vector.add(&i_vector_2).err();
-        assert_eq!(res, Some(VectorError::DimensionMismatch));
+        assert_eq!(
+            res,
+            Some(VectorError::DimensionMismatch {
+                expected: 2,
+                actual: 3
+            })
+        );
     }
 
     #[test]
@@ -252,7 +676,13 @@ mod tests {
         let mut f_vector = Vector::<f64>::new(vec![3.3, 4.1]);
         let f_vector_2 = Vector::<f64>::new(vec![3.2, 4.34, 5.2]);
         let res = f_vector.add_f(&f_vector_2).err();
-        assert_eq!(res, Some(VectorError::DimensionMismatch));
+        assert_eq!(
+            res,
+            Some(VectorError::DimensionMismatch {
+                expected: 2,
+                actual: 3
+            })
+        );
     }
 
     #[test]
@@ -260,7 +690,13 @@ mod tests {
         let mut f_vector = Vector::<f64>::new(vec![3.3, 4.1]);
         let i_vector = Vector::<i64>::new(vec![3, 4, 52]);
         let res = f_vector.add_i(&i_vector).err();
-        assert_eq!(res, Some(VectorError::DimensionMismatch));
+        assert_eq!(
+            res,
+            Some(VectorError::DimensionMismatch {
+                expected: 2,
+                actual: 3
+            })
+        );
     }
 
     #[test]
@@ -278,7 +714,13 @@ mod tests {
         let vec2 = Vector::<i64>::new(vec![4, 5]);
 
         let result = vec1.dot_product(&vec2);
-        assert_eq!(result, Err(VectorError::DimensionMismatch));
+        assert_eq!(
+            result,
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
     }
 
     #[test]
@@ -305,7 +747,13 @@ mod tests {
         let vec2 = Vector::<f64>::new(vec![4.0, 5.0]);
 
         let result = vec1.dot_product_f(&vec2);
-        assert_eq!(result, Err(VectorError::DimensionMismatch));
+        assert_eq!(
+            result,
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
     }
 
     #[test]
@@ -314,7 +762,13 @@ mod tests {
         let vec2 = Vector::<i64>::new(vec![40, 50]);
 
         let result = vec1.dot_product_i(&vec2);
-        assert_eq!(result, Err(VectorError::DimensionMismatch));
+        assert_eq!(
+            result,
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
     }
 
     #[test]
@@ -342,4 +796,139 @@ mod tests {
         let result = vec1.dot_product_i(&vec2);
         assert_eq!(result, Ok(0.0));
     }
+
+    #[test]
+    fn ivec_display() {
+        let i_vector = Vector::<i64>::new(vec![1, -20, 3]);
+        assert_eq!(i_vector.to_string(), "[  1 -20   3]");
+        assert_eq!(Vector::<i64>::new(vec![]).to_string(), "[]");
+    }
+
+    #[test]
+    fn fvec_display_precision() {
+        let f_vector = Vector::<f64>::new(vec![1.0, 2.26]);
+        assert_eq!(format!("{:.1}", f_vector), "[1.0 2.3]");
+    }
+
+    #[test]
+    fn ivec_display_elision() {
+        let i_vector = Vector::<i64>::new((0..2000).collect());
+        assert_eq!(i_vector.to_string(), "[   0    1    2 ... 1997 1998 1999]");
+        let options = PrintOptions {
+            threshold: 3,
+            edge_items: 1,
+            ..PrintOptions::default()
+        };
+        let i_vector = Vector::<i64>::new(vec![1, 2, 3, 4]);
+        assert_eq!(
+            i_vector.to_latex(&options),
+            "\\begin{bmatrix}\n1 \\\\\n\\vdots \\\\\n4\n\\end{bmatrix}"
+        );
+        assert_eq!(
+            i_vector.to_markdown(&options),
+            "| 0 | ... | 3 |\n|---|---|---|\n| 1 | ... | 4 |"
+        );
+    }
+
+    #[test]
+    fn ivec_subtraction_and_negation() {
+        let mut i_vector = Vector::<i64>::new(vec![3, 4]);
+        i_vector.sub(&Vector::<i64>::new(vec![1, 6])).unwrap();
+        assert_eq!(i_vector, Vector::<i64>::new(vec![2, -2]));
+        assert_eq!(-i_vector, Vector::<i64>::new(vec![-2, 2]));
+
+        let mut f_vector = Vector::<f64>::new(vec![1.5, 2.0]);
+        f_vector.sub_i(&Vector::<i64>::new(vec![1, 1])).unwrap();
+        f_vector.sub_f(&Vector::<f64>::new(vec![0.5, 0.5])).unwrap();
+        assert_eq!(-f_vector, Vector::<f64>::new(vec![0.0, -0.5]));
+        assert_eq!(
+            Vector::<i64>::new(vec![1]).sub(&Vector::<i64>::new(vec![1, 2])),
+            Err(VectorError::DimensionMismatch {
+                expected: 1,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn vec_normalize() {
+        let i_vector = Vector::<i64>::new(vec![0, 3, 4]);
+        assert_eq!(
+            i_vector.normalize(),
+            Ok(Vector::<f64>::new(vec![0.0, 0.6, 0.8]))
+        );
+        assert_eq!(
+            Vector::<f64>::new(vec![0.0, 0.0]).normalize_f(),
+            Err(VectorError::ZeroVector)
+        );
+    }
+
+    #[test]
+    fn vec_cross() {
+        let a = Vector::<i64>::new(vec![1, 2, 3]);
+        let b = Vector::<i64>::new(vec![4, 5, 6]);
+        assert_eq!(a.cross(&b), Ok(Vector::<i64>::new(vec![-3, 6, -3])));
+        let a = Vector::<f64>::new(vec![0.0, 1.0, 0.0]);
+        let b = Vector::<f64>::new(vec![0.0, 0.0, 1.0]);
+        assert_eq!(a.cross_f(&b), Ok(Vector::<f64>::new(vec![1.0, 0.0, 0.0])));
+        assert_eq!(
+            Vector::<i64>::new(vec![1, 2]).cross(&Vector::<i64>::new(vec![1, 2, 3])),
+            Err(VectorError::DimensionMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn vec_projection_and_rejection() {
+        let a = Vector::<i64>::new(vec![2, 3]);
+        let x = Vector::<i64>::new(vec![5, 0]);
+        assert_eq!(a.project_onto(&x), Ok(Vector::<f64>::new(vec![2.0, 0.0])));
+        assert_eq!(a.reject_from(&x), Ok(Vector::<f64>::new(vec![0.0, 3.0])));
+        assert_eq!(
+            a.project_onto(&Vector::<i64>::new(vec![0, 0])),
+            Err(VectorError::ZeroVector)
+        );
+    }
+
+    #[test]
+    fn vec_angle_between() {
+        let x = Vector::<i64>::new(vec![1, 0]);
+        let y = Vector::<i64>::new(vec![0, 2]);
+        assert_eq!(x.angle_between(&y), Ok(std::f64::consts::FRAC_PI_2));
+        let a = Vector::<f64>::new(vec![1.0, 1.0]);
+        let b = Vector::<f64>::new(vec![-2.0, -2.0]);
+        assert_eq!(a.angle_between_f(&b), Ok(std::f64::consts::PI));
+        assert_eq!(
+            x.angle_between(&Vector::<i64>::new(vec![0, 0])),
+            Err(VectorError::ZeroVector)
+        );
+    }
+
+    #[test]
+    fn vec_lerp() {
+        let a = Vector::<i64>::new(vec![0, 10]);
+        let b = Vector::<i64>::new(vec![10, 20]);
+        assert_eq!(a.lerp(&b, 0.25), Ok(Vector::<f64>::new(vec![2.5, 12.5])));
+    }
+
+    #[test]
+    fn vec_orthogonal_and_parallel() {
+        let a = Vector::<i64>::new(vec![1, 2]);
+        let b = Vector::<i64>::new(vec![-2, 1]);
+        let c = Vector::<i64>::new(vec![-3, -6]);
+        assert_eq!(a.is_orthogonal(&b, 1e-12), Ok(true));
+        assert_eq!(a.is_orthogonal(&c, 1e-12), Ok(false));
+        assert_eq!(a.is_parallel(&c, 1e-12), Ok(true));
+        assert_eq!(a.is_parallel(&b, 1e-12), Ok(false));
+
+        let a = Vector::<f64>::new(vec![1.0, 0.0]);
+        let b = Vector::<f64>::new(vec![1.0, 0.001]);
+        assert_eq!(a.is_parallel_f(&b, 1e-9), Ok(false));
+        assert_eq!(a.is_parallel_f(&b, 1e-6), Ok(true));
+        let c = Vector::<f64>::new(vec![0.001, 1.0]);
+        assert_eq!(a.is_orthogonal_f(&c, 1e-2), Ok(true));
+        assert_eq!(a.is_orthogonal_f(&c, 1e-4), Ok(false));
+    }
 }
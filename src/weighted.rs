@@ -0,0 +1,546 @@
+//! Weighted statistics of the `Mean` API.
+//!
+//! Weights are non-negative and need not sum to one. Like `weighted_average_f` the functions panic
+//! when data and weights have different lengths. Unbiased variances depend on what the weights
+//! mean, which is selected by `WeightKind`.
+
+use crate::{check_weighted, to_f, Mean, StatsError};
+
+/// Meaning of weights for unbiased variance and covariance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WeightKind {
+    /// Weights count how often each value was observed, sums of squares are divided by
+    /// `Σw - 1` so a weight of 2 is the same as repeating the value
+    Frequency,
+    /// Weights are inversely proportional to the variance of each value, such as survey or
+    /// importance weights. Sums of squares are divided by `Σw - Σw² / Σw`, which does not change
+    /// when all weights are scaled
+    Reliability,
+}
+
+fn check_lengths(data: &[f64], weights: &[f64]) {
+    if data.len() != weights.len() {
+        panic!("data and weights must have the same length");
+    }
+}
+
+/// True when the statistic is undefined because of NaN or negative weights
+fn invalid(data: &[f64], weights: &[f64]) -> bool {
+    data.iter().any(|x| x.is_nan()) || weights.iter().any(|&w| w.is_nan() || w < 0.0)
+}
+
+/// Divisor of sums of squares for unbiased estimates, `Σw` without `kind`
+fn denominator(weights: &[f64], kind: Option<WeightKind>) -> f64 {
+    let sum: f64 = weights.iter().sum();
+    match kind {
+        None => sum,
+        Some(WeightKind::Frequency) => sum - 1.0,
+        Some(WeightKind::Reliability) => sum - weights.iter().map(|w| w * w).sum::<f64>() / sum,
+    }
+}
+
+/// Weighted sum of products of deviations from the weighted means
+fn co_moment(x: &[f64], y: &[f64], weights: &[f64]) -> f64 {
+    let (mean_x, mean_y) = (
+        Mean::weighted_average_f(x, weights),
+        Mean::weighted_average_f(y, weights),
+    );
+    x.iter()
+        .zip(y)
+        .zip(weights)
+        .map(|((x, y), w)| w * (x - mean_x) * (y - mean_y))
+        .sum()
+}
+
+fn covariance(x: &[f64], y: &[f64], weights: &[f64], kind: Option<WeightKind>) -> f64 {
+    check_lengths(x, weights);
+    check_lengths(y, weights);
+    if invalid(x, weights) || y.iter().any(|y| y.is_nan()) {
+        return f64::NAN;
+    }
+    let denominator = denominator(weights, kind);
+    if denominator.is_nan() || denominator <= 0.0 {
+        return f64::NAN;
+    }
+    co_moment(x, y, weights) / denominator
+}
+
+/// Weighted quantiles generalizing `QuantileMethod::AveragedInvertedCdf`: the smallest value
+/// whose cumulative weight reaches `p Σw`, averaged with the next value when it is reached exactly
+fn quantiles(data: &[f64], weights: &[f64], probabilities: &[f64]) -> Vec<f64> {
+    check_lengths(data, weights);
+    let mut pairs: Vec<(f64, f64)> = data
+        .iter()
+        .copied()
+        .zip(weights.iter().copied())
+        .filter(|&(_, w)| w != 0.0)
+        .collect();
+    if invalid(data, weights) || pairs.is_empty() {
+        return vec![f64::NAN; probabilities.len()];
+    }
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let cumulative: Vec<f64> = pairs
+        .iter()
+        .scan(0.0, |sum, &(_, w)| {
+            *sum += w;
+            Some(*sum)
+        })
+        .collect();
+    let total = cumulative[cumulative.len() - 1];
+    // bound of the rounding of the running sums and of `p * total`
+    let tolerance = f64::EPSILON * (pairs.len() + 1) as f64 * total;
+    probabilities
+        .iter()
+        .map(|&p| {
+            if !(0.0..=1.0).contains(&p) {
+                return f64::NAN;
+            }
+            let target = p * total;
+            let k = cumulative
+                .partition_point(|&c| c < target - tolerance)
+                .min(pairs.len() - 1);
+            if cumulative[k] <= target + tolerance && k + 1 < pairs.len() {
+                (pairs[k].0 + pairs[k + 1].0) / 2.0
+            } else {
+                pairs[k].0
+            }
+        })
+        .collect()
+}
+
+/// Fails with `InsufficientData` when weights describe fewer than 2 values, the frequency weights
+/// must sum to more than 1 and there must be 2 reliability weights that are not zero
+fn check_kind(weights: &[f64], kind: WeightKind) -> Result<(), StatsError> {
+    let actual = match kind {
+        WeightKind::Frequency => weights.iter().sum::<f64>(),
+        WeightKind::Reliability => weights.iter().filter(|&&w| w != 0.0).count() as f64,
+    };
+    if actual <= 1.0 {
+        return Err(StatsError::InsufficientData {
+            required: 2,
+            actual: actual as u64,
+        });
+    }
+    Ok(())
+}
+
+impl Mean {
+    /// Weighted median, the value where the cumulative weight reaches half of the total. When half
+    /// of the weight is exactly at or below a value, it is averaged with the next one, so equal
+    /// weights give `median_f`. NaN for NaN, negative weights or no positive weight
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::weighted_median_f(&[1.0, 2.0, 3.0, 4.0], &[1.0, 1.0, 1.0, 5.0]), 4.0);
+    /// assert_eq!(Mean::weighted_median_f(&[1.0, 2.0], &[1.0, 1.0]), 1.5);
+    /// ```
+    pub fn weighted_median_f(data: &[f64], weights: &[f64]) -> f64 {
+        Mean::weighted_quantile_f(data, weights, 0.5)
+    }
+
+    pub fn weighted_median_i(data: &[i64], weights: &[i64]) -> f64 {
+        Mean::weighted_median_f(&to_f(data), &to_f(weights))
+    }
+
+    /// Weighted quantile at probability `p`, the smallest value whose cumulative weight reaches
+    /// `p Σw` and the average with the next value when it is reached exactly, up to the rounding of
+    /// the summed weights. Integer weights give the quantile of repeated values under
+    /// `QuantileMethod::AveragedInvertedCdf` (R type 2). NaN unless `p` is in `[0, 1]`
+    pub fn weighted_quantile_f(data: &[f64], weights: &[f64], p: f64) -> f64 {
+        quantiles(data, weights, &[p])[0]
+    }
+
+    pub fn weighted_quantile_i(data: &[i64], weights: &[i64], p: f64) -> f64 {
+        Mean::weighted_quantile_f(&to_f(data), &to_f(weights), p)
+    }
+
+    /// `weighted_quantile_f` at several probabilities, sorting the data once
+    /// ```
+    /// use datas::Mean;
+    /// let data = [10.0, 20.0, 30.0, 40.0];
+    /// let weights = [0.1, 0.2, 0.3, 0.4];
+    /// let quantiles = Mean::weighted_quantiles_f(&data, &weights, &[0.0, 0.25, 0.9]);
+    /// assert_eq!(quantiles, vec![10.0, 20.0, 40.0]);
+    /// ```
+    pub fn weighted_quantiles_f(data: &[f64], weights: &[f64], probabilities: &[f64]) -> Vec<f64> {
+        quantiles(data, weights, probabilities)
+    }
+
+    pub fn weighted_quantiles_i(data: &[i64], weights: &[i64], probabilities: &[f64]) -> Vec<f64> {
+        Mean::weighted_quantiles_f(&to_f(data), &to_f(weights), probabilities)
+    }
+
+    /// Weighted population variance `Σ w (x - mean)² / Σw` around `weighted_average_f`.
+    /// NaN for NaN, negative weights or weights summing to zero
+    pub fn weighted_variance_f(data: &[f64], weights: &[f64]) -> f64 {
+        covariance(data, data, weights, None)
+    }
+
+    pub fn weighted_variance_i(data: &[i64], weights: &[i64]) -> f64 {
+        Mean::weighted_variance_f(&to_f(data), &to_f(weights))
+    }
+
+    /// Unbiased weighted variance for weights of the given `kind`, NaN when the weights describe
+    /// fewer than 2 values
+    /// ```
+    /// use datas::{Mean, WeightKind};
+    /// let data = [1.0, 2.0, 4.0];
+    /// // frequency weights repeat values
+    /// let repeated = Mean::sample_variance_f(&[1.0, 2.0, 2.0, 4.0, 4.0, 4.0]);
+    /// let frequency =
+    ///     Mean::weighted_sample_variance_f(&data, &[1.0, 2.0, 3.0], WeightKind::Frequency);
+    /// assert!((frequency - repeated).abs() < 1e-12);
+    /// // reliability weights only matter relative to each other
+    /// let reliability =
+    ///     Mean::weighted_sample_variance_f(&data, &[5.0, 5.0, 5.0], WeightKind::Reliability);
+    /// assert!((reliability - Mean::sample_variance_f(&data)).abs() < 1e-12);
+    /// ```
+    pub fn weighted_sample_variance_f(data: &[f64], weights: &[f64], kind: WeightKind) -> f64 {
+        covariance(data, data, weights, Some(kind))
+    }
+
+    pub fn weighted_sample_variance_i(data: &[i64], weights: &[i64], kind: WeightKind) -> f64 {
+        Mean::weighted_sample_variance_f(&to_f(data), &to_f(weights), kind)
+    }
+
+    /// Weighted population covariance `Σ w (x - mean_x) (y - mean_y) / Σw`, panics unless `x`,
+    /// `y` and `weights` have the same length
+    pub fn weighted_covariance_f(x: &[f64], y: &[f64], weights: &[f64]) -> f64 {
+        covariance(x, y, weights, None)
+    }
+
+    pub fn weighted_covariance_i(x: &[i64], y: &[i64], weights: &[i64]) -> f64 {
+        Mean::weighted_covariance_f(&to_f(x), &to_f(y), &to_f(weights))
+    }
+
+    /// Unbiased weighted covariance for weights of the given `kind`
+    pub fn weighted_sample_covariance_f(
+        x: &[f64],
+        y: &[f64],
+        weights: &[f64],
+        kind: WeightKind,
+    ) -> f64 {
+        covariance(x, y, weights, Some(kind))
+    }
+
+    pub fn weighted_sample_covariance_i(
+        x: &[i64],
+        y: &[i64],
+        weights: &[i64],
+        kind: WeightKind,
+    ) -> f64 {
+        Mean::weighted_sample_covariance_f(&to_f(x), &to_f(y), &to_f(weights), kind)
+    }
+
+    /// Kish's effective sample size `(Σw)² / Σw²`, the number of equally weighted values that
+    /// would give a weighted mean the same variance. NaN for no weights
+    /// ```
+    /// use datas::Mean;
+    /// assert_eq!(Mean::effective_sample_size_f(&[1.0, 1.0, 1.0, 1.0]), 4.0);
+    /// assert_eq!(Mean::effective_sample_size_f(&[3.0, 1.0, 0.0]), 1.6);
+    /// ```
+    pub fn effective_sample_size_f(weights: &[f64]) -> f64 {
+        let sum: f64 = weights.iter().sum();
+        sum * sum / weights.iter().map(|w| w * w).sum::<f64>()
+    }
+
+    pub fn effective_sample_size_i(weights: &[i64]) -> f64 {
+        Mean::effective_sample_size_f(&to_f(weights))
+    }
+
+    /// `weighted_median_f` that fails on mismatched lengths, empty data, NaN, negative weights or
+    /// weights summing to zero
+    /// ```
+    /// use datas::{Mean, StatsError};
+    /// assert_eq!(
+    ///     Mean::try_weighted_median_f(&[1.0, 2.0], &[1.0, -1.0]),
+    ///     Err(StatsError::NegativeWeight { index: 1 })
+    /// );
+    /// ```
+    pub fn try_weighted_median_f(data: &[f64], weights: &[f64]) -> Result<f64, StatsError> {
+        check_weighted(data, weights)?;
+        Ok(Mean::weighted_median_f(data, weights))
+    }
+
+    pub fn try_weighted_median_i(data: &[i64], weights: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_weighted_median_f(&to_f(data), &to_f(weights))
+    }
+
+    /// `weighted_quantile_f` that also fails when `p` is outside of `[0, 1]`
+    pub fn try_weighted_quantile_f(
+        data: &[f64],
+        weights: &[f64],
+        p: f64,
+    ) -> Result<f64, StatsError> {
+        Mean::try_weighted_quantiles_f(data, weights, &[p]).map(|quantiles| quantiles[0])
+    }
+
+    pub fn try_weighted_quantile_i(
+        data: &[i64],
+        weights: &[i64],
+        p: f64,
+    ) -> Result<f64, StatsError> {
+        Mean::try_weighted_quantile_f(&to_f(data), &to_f(weights), p)
+    }
+
+    pub fn try_weighted_quantiles_f(
+        data: &[f64],
+        weights: &[f64],
+        probabilities: &[f64],
+    ) -> Result<Vec<f64>, StatsError> {
+        check_weighted(data, weights)?;
+        if let Some(&probability) = probabilities.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+            return Err(StatsError::InvalidProbability { probability });
+        }
+        Ok(Mean::weighted_quantiles_f(data, weights, probabilities))
+    }
+
+    pub fn try_weighted_quantiles_i(
+        data: &[i64],
+        weights: &[i64],
+        probabilities: &[f64],
+    ) -> Result<Vec<f64>, StatsError> {
+        Mean::try_weighted_quantiles_f(&to_f(data), &to_f(weights), probabilities)
+    }
+
+    pub fn try_weighted_variance_f(data: &[f64], weights: &[f64]) -> Result<f64, StatsError> {
+        check_weighted(data, weights)?;
+        Ok(Mean::weighted_variance_f(data, weights))
+    }
+
+    pub fn try_weighted_variance_i(data: &[i64], weights: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_weighted_variance_f(&to_f(data), &to_f(weights))
+    }
+
+    /// `weighted_sample_variance_f` that fails on invalid weights or when they describe fewer
+    /// than 2 values
+    /// ```
+    /// use datas::{Mean, StatsError, WeightKind};
+    /// assert_eq!(
+    ///     Mean::try_weighted_sample_variance_f(&[1.0, 2.0], &[0.5, 0.5], WeightKind::Frequency),
+    ///     Err(StatsError::InsufficientData { required: 2, actual: 1 })
+    /// );
+    /// ```
+    pub fn try_weighted_sample_variance_f(
+        data: &[f64],
+        weights: &[f64],
+        kind: WeightKind,
+    ) -> Result<f64, StatsError> {
+        check_weighted(data, weights)?;
+        check_kind(weights, kind)?;
+        Ok(Mean::weighted_sample_variance_f(data, weights, kind))
+    }
+
+    pub fn try_weighted_sample_variance_i(
+        data: &[i64],
+        weights: &[i64],
+        kind: WeightKind,
+    ) -> Result<f64, StatsError> {
+        Mean::try_weighted_sample_variance_f(&to_f(data), &to_f(weights), kind)
+    }
+
+    /// `weighted_covariance_f` that fails on mismatched lengths of `x`, `y` and `weights`, NaN
+    /// or invalid weights
+    pub fn try_weighted_covariance_f(
+        x: &[f64],
+        y: &[f64],
+        weights: &[f64],
+    ) -> Result<f64, StatsError> {
+        check_weighted(x, weights)?;
+        check_weighted(y, weights)?;
+        Ok(Mean::weighted_covariance_f(x, y, weights))
+    }
+
+    pub fn try_weighted_covariance_i(
+        x: &[i64],
+        y: &[i64],
+        weights: &[i64],
+    ) -> Result<f64, StatsError> {
+        Mean::try_weighted_covariance_f(&to_f(x), &to_f(y), &to_f(weights))
+    }
+
+    pub fn try_weighted_sample_covariance_f(
+        x: &[f64],
+        y: &[f64],
+        weights: &[f64],
+        kind: WeightKind,
+    ) -> Result<f64, StatsError> {
+        check_weighted(x, weights)?;
+        check_weighted(y, weights)?;
+        check_kind(weights, kind)?;
+        Ok(Mean::weighted_sample_covariance_f(x, y, weights, kind))
+    }
+
+    pub fn try_weighted_sample_covariance_i(
+        x: &[i64],
+        y: &[i64],
+        weights: &[i64],
+        kind: WeightKind,
+    ) -> Result<f64, StatsError> {
+        Mean::try_weighted_sample_covariance_f(&to_f(x), &to_f(y), &to_f(weights), kind)
+    }
+
+    /// `effective_sample_size_f` that fails on no weights, NaN, negative weights or weights
+    /// summing to zero
+    pub fn try_effective_sample_size_f(weights: &[f64]) -> Result<f64, StatsError> {
+        check_weighted(weights, weights)?;
+        Ok(Mean::effective_sample_size_f(weights))
+    }
+
+    pub fn try_effective_sample_size_i(weights: &[i64]) -> Result<f64, StatsError> {
+        Mean::try_effective_sample_size_f(&to_f(weights))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::quantile::{self, QuantileMethod};
+    use crate::{close, Mean, StatsError, WeightKind};
+
+    /// Values repeated as often as their integer weight
+    fn repeat(data: &[f64], weights: &[f64]) -> Vec<f64> {
+        data.iter()
+            .zip(weights)
+            .flat_map(|(&x, &w)| std::iter::repeat(x).take(w as usize))
+            .collect()
+    }
+
+    const DATA: [f64; 6] = [3.5, -1.0, 7.0, 2.0, 10.0, 2.5];
+    const WEIGHTS: [f64; 6] = [2.0, 1.0, 0.0, 3.0, 1.0, 4.0];
+
+    #[test]
+    fn integer_weights_repeat_values() {
+        let repeated = repeat(&DATA, &WEIGHTS);
+        for p in [0.0, 0.1, 0.25, 0.5, 0.6, 0.75, 0.9, 1.0] {
+            assert_eq!(
+                Mean::weighted_quantile_f(&DATA, &WEIGHTS, p),
+                quantile::quantile(&repeated, p, QuantileMethod::AveragedInvertedCdf).unwrap(),
+                "{}",
+                p
+            );
+        }
+        assert_eq!(
+            Mean::weighted_median_f(&DATA, &WEIGHTS),
+            Mean::median_f(&repeated)
+        );
+        assert!(close(
+            Mean::weighted_variance_f(&DATA, &WEIGHTS),
+            Mean::variance_f(&repeated)
+        ));
+        assert!(close(
+            Mean::weighted_sample_variance_f(&DATA, &WEIGHTS, WeightKind::Frequency),
+            Mean::sample_variance_f(&repeated)
+        ));
+        let integers = [35, -10, 70, 20, 100, 25];
+        assert_eq!(
+            Mean::weighted_median_i(&integers, &[2, 1, 0, 3, 1, 4]),
+            10.0 * Mean::weighted_median_f(&DATA, &WEIGHTS)
+        );
+    }
+
+    #[test]
+    fn reliability_weights_are_scale_free() {
+        let scaled: Vec<f64> = WEIGHTS.iter().map(|w| w * 0.01).collect();
+        let variance = Mean::weighted_sample_variance_f(&DATA, &WEIGHTS, WeightKind::Reliability);
+        assert!(close(
+            Mean::weighted_sample_variance_f(&DATA, &scaled, WeightKind::Reliability),
+            variance
+        ));
+        // sum of squares 763.5 / 11 divided by 11 - 31 / 11
+        assert!(close(variance, 763.5 / 90.0));
+        assert!(close(
+            Mean::weighted_variance_f(&DATA, &scaled),
+            Mean::weighted_variance_f(&DATA, &WEIGHTS)
+        ));
+        assert_eq!(
+            Mean::weighted_quantiles_f(&DATA, &scaled, &[0.2, 0.7]),
+            Mean::weighted_quantiles_f(&DATA, &WEIGHTS, &[0.2, 0.7])
+        );
+        // 0.1 + 0.2 rounds above 0.3, which must still count as reaching it
+        assert_eq!(
+            Mean::weighted_quantile_f(&[1.0, 2.0, 3.0], &[0.1, 0.2, 0.7], 0.3),
+            Mean::weighted_quantile_f(&[1.0, 2.0, 3.0], &[1.0, 2.0, 7.0], 0.3)
+        );
+        assert_eq!(
+            Mean::weighted_quantile_f(&[1.0, 2.0, 3.0], &[0.1, 0.2, 0.7], 0.3),
+            2.5
+        );
+        assert!(close(Mean::effective_sample_size_f(&WEIGHTS), 121.0 / 31.0));
+        assert!(close(Mean::effective_sample_size_f(&scaled), 121.0 / 31.0));
+        assert_eq!(Mean::effective_sample_size_i(&[5, 5]), 2.0);
+    }
+
+    #[test]
+    fn covariance() {
+        let x = [1.0, 2.0, 3.0, 4.0];
+        let y = [2.0, 1.0, 4.0, 3.0];
+        let weights = [1.0, 2.0, 1.0, 2.0];
+        // weighted means are 8 / 3 and 7 / 3, the products of deviations sum to 42 / 9
+        assert!(close(
+            Mean::weighted_covariance_f(&x, &y, &weights),
+            7.0 / 9.0
+        ));
+        assert!(close(
+            Mean::weighted_covariance_f(&x, &x, &weights),
+            Mean::weighted_variance_f(&x, &weights)
+        ));
+        assert!(close(
+            Mean::weighted_sample_covariance_i(
+                &[1, 2, 3, 4],
+                &[2, 1, 4, 3],
+                &[1, 2, 1, 2],
+                WeightKind::Frequency
+            ),
+            Mean::weighted_covariance_f(&x, &y, &weights) * 6.0 / 5.0
+        ));
+        assert!(Mean::weighted_sample_covariance_f(
+            &x,
+            &y,
+            &[0.0, 1.0, 0.0, 0.0],
+            WeightKind::Reliability
+        )
+        .is_nan());
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(Mean::weighted_median_f(&[1.0, f64::NAN], &[1.0, 1.0]).is_nan());
+        assert!(Mean::weighted_quantile_f(&[1.0, 2.0], &[1.0, 1.0], 1.5).is_nan());
+        assert!(Mean::weighted_median_f(&[1.0, 2.0], &[0.0, 0.0]).is_nan());
+        assert!(Mean::weighted_variance_f(&[1.0, 2.0], &[-1.0, 2.0]).is_nan());
+        assert!(Mean::effective_sample_size_f(&[]).is_nan());
+        assert_eq!(
+            Mean::try_weighted_quantiles_i(&[1, 2], &[1, 1], &[0.5, -0.5]),
+            Err(StatsError::InvalidProbability { probability: -0.5 })
+        );
+        assert_eq!(
+            Mean::try_weighted_variance_f(&[1.0, 2.0], &[0.0, 0.0]),
+            Err(StatsError::ZeroWeightSum)
+        );
+        assert_eq!(
+            Mean::try_weighted_sample_variance_i(&[1, 2], &[0, 3], WeightKind::Reliability),
+            Err(StatsError::InsufficientData {
+                required: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            Mean::try_weighted_covariance_f(&[1.0], &[1.0, 2.0], &[1.0, 1.0]),
+            Err(StatsError::LengthMismatch {
+                data: 1,
+                weights: 2
+            })
+        );
+        assert_eq!(
+            Mean::try_effective_sample_size_f(&[]),
+            Err(StatsError::Empty)
+        );
+        assert_eq!(Mean::try_weighted_median_i(&[1, 5, 9], &[1, 1, 1]), Ok(5.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "data and weights must have the same length")]
+    fn weighted_median_length_mismatch() {
+        Mean::weighted_median_f(&[1.0, 2.0], &[1.0]);
+    }
+}